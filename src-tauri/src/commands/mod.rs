@@ -1,131 +1,683 @@
 use anyhow::Result;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tauri::State;
-use tokio::sync::Mutex;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::db::Database;
-use crate::http::{replace_environment_variables, replace_path_parameters, HttpClient};
+use crate::http::{
+    bearer_token_from_headers, generate_auth_headers, merge_headers, replace_body_variables,
+    replace_environment_variables, replace_path_parameters, resolve_auth_variables,
+    resolve_workspace_path, HttpClient,
+};
 use crate::models::*;
-use crate::sync::SyncClient;
+use crate::sync::auto_sync::{spawn_auto_sync, AutoSyncEvent, AUTO_SYNC_EVENT};
+use crate::sync::{ProviderSyncError, SyncClient, SyncError};
 
 // State wrapper for database
 pub struct AppState {
     pub db: Database,
     pub http_client: HttpClient,
     pub sync_client: Arc<Mutex<SyncClient>>,
+    pub workspace_dir: PathBuf,
+    pub auto_sync_task: Mutex<Option<JoinHandle<()>>>,
+    pub sync_running: AtomicBool,
+    pub request_log_path: RwLock<Option<PathBuf>>,
+    pub oauth2_pending: Mutex<HashMap<Uuid, (String, String)>>, // auth owner id -> (PKCE verifier, CSRF state)
+    pub in_flight_requests: Mutex<HashMap<String, oneshot::Sender<()>>>, // client request id -> cancel
 }
 
+impl AppState {
+    // Builds the state the GUI and headless runs share from the database's settings. The
+    // custom CA bundle and proxy are applied before any HTTP client is built; sync starts
+    // on the default provider.
+    pub async fn load(db: Database, workspace_dir: PathBuf) -> Result<Self> {
+        if let Ok(Some(path)) = db.get_custom_ca_bundle_path().await {
+            match crate::http::load_ca_bundle(Path::new(&path)) {
+                Ok(certs) => crate::http::set_root_certificates(certs),
+                Err(e) => eprintln!("Failed to load custom CA bundle: {}", e),
+            }
+        }
+        if let Ok(Some(settings)) = db.get_proxy_settings().await {
+            match crate::http::build_proxy(&settings) {
+                Ok(proxy) => crate::http::set_proxy(Some(proxy)),
+                Err(e) => eprintln!("Failed to apply proxy settings: {}", e),
+            }
+        }
+
+        let http_client = HttpClient::new();
+        let request_log_path = db
+            .get_request_log_path()
+            .await
+            .ok()
+            .flatten()
+            .map(PathBuf::from);
+        if let Ok(strict) = db.get_config_flag(STRICT_CONTENT_TYPE_KEY).await {
+            http_client.set_strict_content_type(strict);
+        }
+        for (scope, json) in db.get_cookie_jars().await.unwrap_or_default() {
+            if let Err(e) = http_client.load_cookie_jar(&scope, &json) {
+                eprintln!("Failed to load cookie jar '{}': {}", scope, e);
+            }
+        }
+
+        let sync_client = SyncClient::new(crate::sync::ProviderConfig::default())?;
+
+        Ok(Self {
+            db,
+            http_client,
+            sync_client: Arc::new(Mutex::new(sync_client)),
+            workspace_dir,
+            auto_sync_task: Mutex::new(None),
+            sync_running: AtomicBool::new(false),
+            request_log_path: RwLock::new(request_log_path),
+            oauth2_pending: Mutex::new(HashMap::new()),
+            in_flight_requests: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+// Config key for formatting responses strictly by their declared content type
+pub const STRICT_CONTENT_TYPE_KEY: &str = "respect_content_type_strictly";
+
+// Config key for sharing cookies between requests through a per-environment jar
+pub const COOKIE_JAR_KEY: &str = "cookie_jar_enabled";
+
+// Event carrying download progress for `send_request_streaming`
+pub const REQUEST_PROGRESS_EVENT: &str = "request-progress";
+
+// Minimum gap between progress events so large downloads don't flood the frontend
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+// Error returned for a request aborted by `cancel_request` or a progress callback, so the
+// UI can tell it apart from a timeout
+pub const REQUEST_CANCELLED: &str = "Request cancelled";
+
 #[tauri::command]
 pub async fn send_request(
     payload: SendRequestPayload,
+    client_request_id: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<PrettyResponse, String> {
-    // Get active environment variables
-    let env_vars = state
+    let state = state.inner();
+    run_cancellable(state, client_request_id, execute_request(state, payload)).await
+}
+
+// Aborts an in-flight request by the id it was sent with; false when it already finished
+#[tauri::command]
+pub async fn cancel_request(
+    client_request_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let cancel = state
+        .in_flight_requests
+        .lock()
+        .await
+        .remove(&client_request_id);
+    Ok(cancel.is_some_and(|cancel| cancel.send(()).is_ok()))
+}
+
+// Runs a send that `cancel_request` can abort by id. Cancelling drops the future, which
+// closes the connection. The registry entry is removed however the send ends.
+async fn run_cancellable(
+    state: &AppState,
+    client_request_id: Option<String>,
+    send: impl std::future::Future<Output = Result<PrettyResponse, String>>,
+) -> Result<PrettyResponse, String> {
+    let Some(id) = client_request_id else {
+        return send.await;
+    };
+    let (cancel, cancelled) = oneshot::channel();
+    state
+        .in_flight_requests
+        .lock()
+        .await
+        .insert(id.clone(), cancel);
+
+    let result = tokio::select! {
+        result = send => result,
+        Ok(()) = cancelled => Err(REQUEST_CANCELLED.to_string()),
+    };
+    state.in_flight_requests.lock().await.remove(&id);
+    result
+}
+
+#[tauri::command]
+pub async fn send_request_streaming(
+    payload: SendRequestPayload,
+    client_request_id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrettyResponse, String> {
+    let mut last_emit: Option<Instant> = None;
+    let state = state.inner();
+    let send = execute_request_with_progress(state, payload, |received, total| {
+        if last_emit.is_none_or(|at| at.elapsed() >= PROGRESS_EMIT_INTERVAL) {
+            last_emit = Some(Instant::now());
+            let _ = app.emit(
+                REQUEST_PROGRESS_EVENT,
+                RequestProgress {
+                    client_request_id: client_request_id.clone(),
+                    bytes_received: received,
+                    total_bytes: total,
+                },
+            );
+        }
+        ControlFlow::Continue(())
+    });
+    let response = run_cancellable(state, Some(client_request_id.clone()), send).await?;
+
+    // Throttling may have skipped the last chunk, so always report completion
+    let _ = app.emit(
+        REQUEST_PROGRESS_EVENT,
+        RequestProgress {
+            client_request_id,
+            bytes_received: response.size as u64,
+            total_bytes: Some(response.size as u64),
+        },
+    );
+
+    Ok(response)
+}
+
+// Every piece of the payload that may hold {{tokens}}, before substitution
+fn payload_texts<'a>(
+    payload: &'a SendRequestPayload,
+    base_url_override: Option<&'a str>,
+    body_text: &'a str,
+) -> Vec<&'a str> {
+    let mut texts = vec![payload.url.as_str()];
+    texts.extend(base_url_override);
+    for (key, value, _) in &payload.query_params {
+        texts.extend([key.as_str(), value.as_str()]);
+    }
+    for (key, value) in &payload.headers {
+        texts.extend([key.as_str(), value.as_str()]);
+    }
+    texts.push(body_text);
+    texts
+}
+
+// Resolves {{response.<request name>.<path>}} tokens from the latest response to that
+// request in history. Tokens that can't be resolved stay as they are, with a warning.
+async fn resolve_response_tokens(
+    state: &AppState,
+    texts: &[&str],
+    variables: &mut HashMap<String, String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let tokens: Vec<String> = texts
+        .iter()
+        .flat_map(|text| crate::http::extract_environment_variables(text))
+        .filter(|token| crate::http::parse_response_token(token).is_some())
+        .collect();
+    if tokens.is_empty() {
+        return Ok(());
+    }
+
+    let history = state
         .db
-        .get_active_environment()
+        .get_history(None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for token in tokens {
+        if variables.contains_key(&token) {
+            continue;
+        }
+        let Some((name, path)) = crate::http::parse_response_token(&token) else {
+            continue;
+        };
+        let Some(body) = history
+            .iter()
+            .filter(|entry| entry.request.name == name)
+            .find_map(|entry| entry.response.as_ref())
+            .map(|response| response.body.as_str())
+        else {
+            warnings.push(format!(
+                "Unresolved {{{{{}}}}}: no previous response for request '{}'",
+                token, name
+            ));
+            continue;
+        };
+
+        let selected = crate::contract::parse_json_path(&path)
+            .ok()
+            .zip(serde_json::from_str::<serde_json::Value>(body).ok())
+            .and_then(|(selectors, document)| {
+                crate::contract::select_json_path(&document, &selectors)
+                    .first()
+                    .map(|value| match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+            });
+        match selected {
+            Some(value) => {
+                variables.insert(token, value);
+            }
+            None => warnings.push(format!(
+                "Unresolved {{{{{}}}}}: '{}' matched nothing in the last response of '{}'",
+                token, path, name
+            )),
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_request(
+    state: &AppState,
+    payload: SendRequestPayload,
+) -> Result<PrettyResponse, String> {
+    execute_request_with_progress(state, payload, |_, _| ControlFlow::Continue(())).await
+}
+
+// The active environment, or the one the payload names
+async fn payload_environment(
+    state: &AppState,
+    payload: &SendRequestPayload,
+) -> Result<Option<Environment>, String> {
+    let Some(id) = payload.environment_id.as_deref() else {
+        return state
+            .db
+            .get_active_environment()
+            .await
+            .map_err(|e| e.to_string());
+    };
+    let uuid = Uuid::parse_str(id).map_err(|e| format!("Invalid environment ID: {}", e))?;
+    let environment = state
+        .db
+        .get_environments()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|env| env.id == uuid)
+        .ok_or("Environment not found")?;
+    Ok(Some(environment))
+}
+
+// Shared send pipeline: variable substitution, auth, sending and history. `on_progress`
+// sees the body as it downloads and can abort it.
+async fn execute_request_with_progress<F>(
+    state: &AppState,
+    payload: SendRequestPayload,
+    on_progress: F,
+) -> Result<PrettyResponse, String>
+where
+    F: FnMut(u64, Option<u64>) -> ControlFlow<()> + Send,
+{
+    let active_environment = payload_environment(state, &payload).await?;
+    let base_url_override = active_environment
+        .as_ref()
+        .and_then(|env| env.base_url_override.clone())
+        .filter(|_| payload.apply_base_url_override);
+    let environment_name = active_environment.as_ref().map(|env| env.name.clone());
+    let cookie_jar = state
+        .db
+        .get_config_flag(COOKIE_JAR_KEY)
         .await
         .map_err(|e| e.to_string())?
+        .then(|| cookie_jar_scope(active_environment.as_ref().map(|env| env.id)));
+    let mut env_vars = active_environment
         .map(|env| env.variables)
         .unwrap_or_default();
 
+    // Values from earlier responses join the environment variables for substitution
+    let mut warnings = Vec::new();
+    let body_text = payload
+        .body
+        .as_ref()
+        .and_then(|body| serde_json::to_string(body).ok())
+        .unwrap_or_default();
+    let texts = payload_texts(&payload, base_url_override.as_deref(), &body_text);
+    resolve_response_tokens(state, &texts, &mut env_vars, &mut warnings).await?;
+
+    // Trace the tokens before any of them are substituted
+    let variable_trace = payload
+        .resolve_trace
+        .unwrap_or(false)
+        .then(|| crate::http::trace_variables(texts, &env_vars));
+
     // Replace path parameters first (e.g., :user_id -> 123)
     let url_with_path = replace_path_parameters(&payload.url, &payload.path_params);
 
     // Then replace environment variables in URL
-    let url = replace_environment_variables(&url_with_path, &env_vars);
+    let mut url = replace_environment_variables(&url_with_path, &env_vars);
+
+    // Point the request at the environment's host, if it overrides one
+    if let Some(base_url) = base_url_override {
+        let base_url = replace_environment_variables(&base_url, &env_vars);
+        url = crate::http::override_base_url(&url, &base_url).map_err(|e| e.to_string())?;
+    }
+
+    // Merge the enabled query parameters into the URL
+    let query_params: Vec<(String, String, bool)> = payload
+        .query_params
+        .iter()
+        .map(|(key, value, enabled)| {
+            (
+                replace_environment_variables(key, &env_vars),
+                replace_environment_variables(value, &env_vars),
+                *enabled,
+            )
+        })
+        .collect();
+    let url = crate::http::build_url_with_params(&url, &query_params);
 
     // Replace environment variables in headers
-    let mut headers = HashMap::new();
+    let mut headers = Vec::new();
     for (key, value) in &payload.headers {
         let replaced_key = replace_environment_variables(key, &env_vars);
         let replaced_value = replace_environment_variables(value, &env_vars);
-        headers.insert(replaced_key, replaced_value);
-    }
-
-    // Replace environment variables in body
-    let body = if let Some(ref body) = payload.body {
-        Some(match body {
-            RequestBody::Raw {
-                content,
-                content_type,
-            } => RequestBody::Raw {
-                content: replace_environment_variables(content, &env_vars),
-                content_type: replace_environment_variables(content_type, &env_vars),
-            },
-            RequestBody::Json(value) => {
-                let json_str = serde_json::to_string(value).map_err(|e| e.to_string())?;
-                let replaced_str = replace_environment_variables(&json_str, &env_vars);
-                let replaced_value: serde_json::Value = serde_json::from_str(&replaced_str)
-                    .map_err(|e| format!("Invalid JSON after variable replacement: {}", e))?;
-                RequestBody::Json(replaced_value)
-            }
-            RequestBody::FormData(form) => {
-                let mut replaced_form = HashMap::new();
-                for (key, field) in form {
-                    let replaced_key = replace_environment_variables(key, &env_vars);
-                    let replaced_field = match field {
-                        FormDataField::Text { value } => FormDataField::Text {
-                            value: replace_environment_variables(value, &env_vars),
-                        },
-                        FormDataField::File { path } => {
-                            // Replace environment variables in file path
-                            FormDataField::File {
-                                path: replace_environment_variables(path, &env_vars),
-                            }
-                        }
-                    };
-                    replaced_form.insert(replaced_key, replaced_field);
+        headers.push((replaced_key, replaced_value));
+    }
+
+    let request_id = payload
+        .request_id
+        .as_deref()
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|e| format!("Invalid request ID: {}", e))?;
+
+    let saved_request = match request_id {
+        Some(id) => state.db.get_request(id).await.map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    // Resolve the auth's variables against the active environment
+    // A token refreshed for an ephemeral request is used for it alone and not stored
+    let effective = effective_auth(
+        state,
+        saved_request.as_ref(),
+        payload.collection_id.as_deref(),
+    )
+    .await?;
+    let auth = match effective {
+        Some((auth, owner)) => {
+            let mut auth = resolve_auth_variables(&auth, &env_vars);
+            if let Some(token) = refresh_expired_oauth2_token(&mut auth).await? {
+                if !payload.ephemeral {
+                    store_oauth2_token(state, &owner, token).await?;
                 }
-                RequestBody::FormData(replaced_form)
             }
-            RequestBody::UrlEncoded(form) => {
-                let mut replaced_form = HashMap::new();
-                for (key, value) in form {
-                    let replaced_key = replace_environment_variables(key, &env_vars);
-                    let replaced_value = replace_environment_variables(value, &env_vars);
-                    replaced_form.insert(replaced_key, replaced_value);
-                }
-                RequestBody::UrlEncoded(replaced_form)
+            Some(auth)
+        }
+        None => None,
+    };
+
+    // Explicit request headers and query parameters still win over the auth's
+    let mut auth_header_names = Vec::new();
+    let url = match &auth {
+        Some(auth) => {
+            let mut auth_headers = generate_auth_headers(auth);
+            if let (AuthType::OAuth2ClientCredentials, Some(credentials)) =
+                (&auth.auth_type, &auth.oauth2_client_credentials)
+            {
+                let token = state
+                    .http_client
+                    .fetch_oauth2_token(credentials)
+                    .await
+                    .map_err(|e| format!("Failed to fetch OAuth2 token: {}", e))?;
+                auth_headers.insert("Authorization".to_string(), format!("Bearer {}", token));
             }
-        })
-    } else {
+            auth_header_names = auth_headers.keys().cloned().collect();
+            headers = merge_headers(&headers, &auth_headers);
+            crate::http::apply_auth_query(&url, auth)
+        }
+        None => url,
+    };
+    // Digest auth needs the server's challenge, so the client answers it when sending
+    let digest_auth = auth
+        .as_ref()
+        .filter(|auth| matches!(auth.auth_type, AuthType::Digest))
+        .and_then(|auth| auth.digest.clone());
+    // SigV4 signs the final request, body included, so the client does it when sending
+    let aws_sigv4 = auth
+        .as_ref()
+        .filter(|auth| matches!(auth.auth_type, AuthType::AwsSigV4))
+        .and_then(|auth| auth.aws_sigv4.clone());
+
+    // Load the body from a file if one was given
+    let source_body = if !payload.sends_body() {
         None
+    } else if let Some(body_file) = &payload.body_file {
+        let path = resolve_workspace_path(&state.workspace_dir, body_file);
+        Some(
+            crate::http::load_body_file(
+                &path,
+                payload.body.as_ref(),
+                &env_vars,
+                payload.body_file_raw,
+            )
+            .map_err(|e| e.to_string())?,
+        )
+    } else {
+        payload.body.clone()
+    };
+
+    // Replace environment variables in body (file bodies in raw mode are sent verbatim)
+    let body = match source_body {
+        Some(body) if payload.body_file.is_some() && payload.body_file_raw => Some(body),
+        Some(body) => Some(replace_body_variables(&body, &env_vars).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    // Binary file bodies and form-data files are relative to the workspace, like body files
+    let resolve = |path: &str| {
+        resolve_workspace_path(&state.workspace_dir, path)
+            .to_string_lossy()
+            .into_owned()
     };
+    let body = body.map(|body| match body {
+        RequestBody::BinaryFile { path } => RequestBody::BinaryFile {
+            path: resolve(&path),
+        },
+        RequestBody::FormData(mut fields) => {
+            for (_, field) in &mut fields {
+                if let FormDataField::File { path } = field {
+                    *path = resolve(path);
+                }
+            }
+            RequestBody::FormData(fields)
+        }
+        body => body,
+    });
+
+    // Warn (without failing) when the bearer token is an expired JWT
+    if let Some(token) = bearer_token_from_headers(&headers) {
+        if let Ok(info) = crate::http::inspect_jwt(&token) {
+            if info.is_expired {
+                warnings.push("Bearer token is an expired JWT".to_string());
+            }
+        }
+    }
+
+    // Certificate paths are relative to the workspace, like body files
+    let client_certificate = payload
+        .client_certificate
+        .as_ref()
+        .map(|cert| ClientCertificate {
+            cert_path: resolve_workspace_path(&state.workspace_dir, &cert.cert_path)
+                .to_string_lossy()
+                .into_owned(),
+            key_path: cert.key_path.as_ref().map(|key_path| {
+                resolve_workspace_path(&state.workspace_dir, key_path)
+                    .to_string_lossy()
+                    .into_owned()
+            }),
+            passphrase: cert
+                .passphrase
+                .as_deref()
+                .map(|passphrase| replace_environment_variables(passphrase, &env_vars)),
+        });
+
+    // Downloads land in the workspace unless given an absolute path
+    let download_to = payload.download_to.as_ref().map(|path| {
+        resolve_workspace_path(
+            &state.workspace_dir,
+            &replace_environment_variables(path, &env_vars),
+        )
+        .to_string_lossy()
+        .into_owned()
+    });
 
+    // An API key can have any name, so it's masked by name when the request is recorded;
+    // redact_url and redact_headers only know credential-like names
+    let api_key = auth.as_ref().and_then(|auth| auth.api_key.as_ref());
+    let api_key_names = |placement: ApiKeyPlacement| -> Vec<String> {
+        api_key
+            .filter(|api_key| api_key.placement == placement)
+            .map(|api_key| api_key.name.clone())
+            .into_iter()
+            .collect()
+    };
+    let secret_params = api_key_names(ApiKeyPlacement::Query);
+    // Credential-like variables are masked wherever they were substituted
+    let secret_values: Vec<String> = env_vars
+        .iter()
+        .filter(|(name, _)| crate::secrets::is_sensitive_name(name))
+        .map(|(_, value)| value.clone())
+        .collect();
+    let resolved_headers = crate::secrets::redact_headers(
+        &headers,
+        &api_key_names(ApiKeyPlacement::Header),
+        &secret_values,
+    );
+    let resolved_body = body
+        .as_ref()
+        .map(|body| crate::secrets::redact_request_body(body, &secret_values));
+
+    let (timeout, retry_count) = resolve_timeout_and_retries(state, &payload).await?;
+    let resolved_url = url.clone();
     let modified_payload = SendRequestPayload {
         method: payload.method.clone(),
         url,
         headers,
         body,
         path_params: HashMap::new(), // Path params already applied to URL
-        timeout: payload.timeout,
+        query_params: Vec::new(),    // Query params already merged into URL
+        timeout,
+        timeout_preset: None, // Resolved into `timeout`
+        connect_timeout: payload.connect_timeout,
+        body_file: None, // Body file already loaded
+        body_file_raw: false,
+        collection_id: None, // Collection auth already applied
+        request_id: None,
+        capture_informational: payload.capture_informational,
+        force_no_body: payload.force_no_body,
+        success_criteria: None, // Evaluated here once the response arrives
+        method_override: payload.method_override,
+        apply_base_url_override: false, // Already applied to the URL
+        resolve_trace: None,            // Traced here, before substitution
+        user_agent: payload
+            .user_agent
+            .as_deref()
+            .map(|user_agent| replace_environment_variables(user_agent, &env_vars)),
+        retry_count,
+        ephemeral: payload.ephemeral,
+        digest_auth,
+        aws_sigv4,
+        auth_headers: auth_header_names,
+        follow_redirects: payload.follow_redirects,
+        max_redirects: payload.max_redirects,
+        insecure: payload.insecure,
+        client_certificate,
+        cookie_jar: cookie_jar.clone(),
+        retry_on: payload.retry_on.clone(),
+        retry_backoff_ms: payload.retry_backoff_ms,
+        download_to,
+        decompress: payload.decompress,
+        environment_id: None, // Already applied to the variables
     };
 
     // Send the request
-    let response = state
+    let result = state
         .http_client
-        .send_request(modified_payload)
-        .await
-        .map_err(|e| e.to_string())?;
+        .send_request_with_progress(modified_payload, on_progress)
+        .await;
+
+    // Append to the request log, if enabled
+    let log_path = state.request_log_path.read().unwrap().clone();
+    if let Some(log_path) = log_path.filter(|_| !payload.ephemeral) {
+        let entry = crate::request_log::RequestLogEntry {
+            timestamp: chrono::Utc::now(),
+            method: payload.method.to_string(),
+            url: crate::secrets::redact_url_with(&resolved_url, &secret_params),
+            status: result.as_ref().ok().map(|r| r.status),
+            response_time: result.as_ref().ok().map(|r| r.response_time),
+            error: result
+                .as_ref()
+                .err()
+                .map(|e| crate::secrets::redact_urls_in(&e.to_string(), &secret_params)),
+        };
+        crate::request_log::spawn_append(log_path, entry);
+    }
+
+    let mut response = result.map_err(|e| e.to_string())?;
+    response.warnings.extend(warnings);
+    response.variable_trace = variable_trace;
+    apply_format_preference(state, &mut response).await;
+    response.is_success_by_criteria = payload.success_criteria.as_ref().map(|criteria| {
+        crate::contract::meets_success_criteria(criteria, response.status, &response.body)
+    });
+
+    // Ephemeral requests leave nothing behind
+    if payload.ephemeral {
+        return Ok(response);
+    }
+
+    if let Some(scope) = &cookie_jar {
+        if let Err(e) = save_cookie_jar(state, scope).await {
+            eprintln!("Failed to save cookie jar: {}", e);
+        }
+    }
+
+    // Remember the last run on the saved request
+    if let Some(request_id) = request_id {
+        if let Err(e) = state
+            .db
+            .record_request_run(request_id, response.status, response.response_time)
+            .await
+        {
+            eprintln!("Failed to record last run: {}", e);
+        }
+    }
 
     // Create HTTP request and response for history
     let http_request = HttpRequest {
         id: Some(Uuid::new_v4()),
-        name: format!("{} {}", payload.method.to_string(), payload.url),
+        // Saved requests keep their name so {{response.<name>...}} can find them
+        name: saved_request
+            .map(|request| request.name)
+            .unwrap_or_else(|| format!("{} {}", payload.method.to_string(), payload.url)),
         method: payload.method,
         url: payload.url,
         headers: payload.headers,
         body: payload.body,
         path_params: HashMap::new(),
+        query_params: payload.query_params,
         collection_id: None,
         created_at: Some(chrono::Utc::now()),
         updated_at: Some(chrono::Utc::now()),
         synced: false,
         version: 0,
         cloud_id: None,
+        last_status: None,
+        last_response_time: None,
+        last_run_at: None,
+        examples: Vec::new(),
+        success_criteria: payload.success_criteria.clone(),
+        auth: None,
+        alias: None,
+        depends_on: Vec::new(),
+        depends_on_cloud_ids: Vec::new(),
+        disabled_in_environments: Vec::new(),
     };
 
     let http_response = HttpResponse {
@@ -138,7 +690,11 @@ pub async fn send_request(
     };
 
     // Save to history
-    let history = RequestHistory::new(http_request, Some(http_response));
+    let mut history = RequestHistory::new(http_request, Some(http_response));
+    history.environment_name = environment_name;
+    history.resolved_url = crate::secrets::redact_url_with(&resolved_url, &secret_params);
+    history.resolved_headers = Some(resolved_headers);
+    history.resolved_body = resolved_body;
     if let Err(e) = state.db.save_to_history(&history).await {
         eprintln!("Failed to save request to history: {}", e);
     }
@@ -146,6 +702,228 @@ pub async fn send_request(
     Ok(response)
 }
 
+// Shows the body as received when the user prefers raw for its content type
+async fn apply_format_preference(state: &AppState, response: &mut PrettyResponse) {
+    let Some(content_type) = response.headers.get("content-type") else {
+        return;
+    };
+    let preferences = match state.db.get_format_preferences().await {
+        Ok(preferences) => preferences,
+        Err(e) => {
+            eprintln!("Failed to load format preferences: {}", e);
+            return;
+        }
+    };
+
+    let media_type = crate::http::media_type(content_type);
+    if preferences.get(&media_type) == Some(&FormatMode::Raw) && !response.body.is_empty() {
+        response.formatted_body = Some(response.body.clone());
+    }
+}
+
+#[tauri::command]
+pub async fn get_format_preferences(
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, FormatMode>, String> {
+    state
+        .db
+        .get_format_preferences()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_format_preference(
+    content_type: String,
+    mode: FormatMode,
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, FormatMode>, String> {
+    let media_type = crate::http::media_type(&content_type);
+    if media_type.is_empty() {
+        return Err("Content type is required".to_string());
+    }
+
+    state
+        .db
+        .set_format_preference(&media_type, mode)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// An explicit timeout wins over a named preset
+async fn resolve_timeout(
+    state: &AppState,
+    payload: &SendRequestPayload,
+) -> Result<Option<u64>, String> {
+    let (None, Some(name)) = (payload.timeout, &payload.timeout_preset) else {
+        return Ok(payload.timeout);
+    };
+
+    state
+        .db
+        .get_timeout_presets()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|preset| &preset.name == name)
+        .map(|preset| Some(preset.seconds))
+        .ok_or_else(|| format!("Unknown timeout preset '{}'", name))
+}
+
+// Request-level settings win over the defaults of the nearest collection up the chain
+async fn resolve_timeout_and_retries(
+    state: &AppState,
+    payload: &SendRequestPayload,
+) -> Result<(Option<u64>, Option<u32>), String> {
+    let mut timeout = resolve_timeout(state, payload).await?;
+    let mut retries = payload.retry_count;
+    for collection in collection_chain(state, payload.collection_id.as_deref()).await? {
+        timeout = timeout.or(collection.default_timeout);
+        retries = retries.or(collection.default_retries);
+    }
+
+    Ok((timeout, retries))
+}
+
+// The payload's collection followed by its ancestors, nearest first
+async fn collection_chain(
+    state: &AppState,
+    collection_id: Option<&str>,
+) -> Result<Vec<Collection>, String> {
+    let Some(collection_id) = collection_id else {
+        return Ok(Vec::new());
+    };
+    let collection_id =
+        Uuid::parse_str(collection_id).map_err(|e| format!("Invalid collection ID: {}", e))?;
+
+    let mut collections: HashMap<Uuid, Collection> = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|collection| (collection.id, collection))
+        .collect();
+    // Removing each visited collection also stops a parent cycle from a bad sync
+    let mut chain = Vec::new();
+    let mut current = collections.remove(&collection_id);
+    while let Some(collection) = current {
+        current = collection.parent_id.and_then(|id| collections.remove(&id));
+        chain.push(collection);
+    }
+
+    Ok(chain)
+}
+
+#[tauri::command]
+pub async fn list_timeout_presets(
+    state: State<'_, AppState>,
+) -> Result<Vec<TimeoutPreset>, String> {
+    state
+        .db
+        .get_timeout_presets()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_timeout_preset(
+    name: String,
+    secs: u64,
+    state: State<'_, AppState>,
+) -> Result<Vec<TimeoutPreset>, String> {
+    if name.trim().is_empty() {
+        return Err("Preset name is required".to_string());
+    }
+
+    state
+        .db
+        .save_timeout_preset(TimeoutPreset {
+            name: name.trim().to_string(),
+            seconds: secs,
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Refreshes an expired OAuth2 token in place. The new token is returned so the caller
+// can store it wherever the auth came from.
+async fn refresh_expired_oauth2_token(
+    resolved: &mut AuthConfig,
+) -> Result<Option<OAuth2Token>, String> {
+    let Some(config) = resolved.oauth2.as_mut() else {
+        return Ok(None);
+    };
+    if !config
+        .token
+        .as_ref()
+        .is_some_and(crate::oauth::needs_refresh)
+    {
+        return Ok(None);
+    }
+
+    let token = crate::oauth::refresh(config)
+        .await
+        .map_err(|e| format!("Failed to refresh OAuth2 token: {}", e))?;
+    config.token = Some(token.clone());
+
+    Ok(Some(token))
+}
+
+// Where an auth config is stored, so a refreshed or exchanged OAuth2 token goes back there
+enum AuthOwner {
+    Request(Uuid),
+    Collection(Uuid),
+}
+
+impl AuthOwner {
+    fn id(&self) -> Uuid {
+        match self {
+            Self::Request(id) | Self::Collection(id) => *id,
+        }
+    }
+}
+
+// The auth a request is sent with: a saved request's own auth, otherwise that of the
+// nearest collection up the parent chain that sets one
+async fn effective_auth(
+    state: &AppState,
+    request: Option<&HttpRequest>,
+    collection_id: Option<&str>,
+) -> Result<Option<(AuthConfig, AuthOwner)>, String> {
+    if let Some((auth, id)) = request.and_then(|request| Some((request.auth.clone()?, request.id?)))
+    {
+        return Ok(Some((auth, AuthOwner::Request(id))));
+    }
+
+    Ok(collection_chain(state, collection_id)
+        .await?
+        .into_iter()
+        .find_map(|collection| Some((collection.auth?, AuthOwner::Collection(collection.id)))))
+}
+
+// Tokens are stored without marking the request or collection unsynced; they stay on this
+// device (see strip_oauth2_token)
+async fn store_oauth2_token(
+    state: &AppState,
+    owner: &AuthOwner,
+    token: OAuth2Token,
+) -> Result<(), String> {
+    let stored = match owner {
+        AuthOwner::Request(id) => state.db.set_request_oauth2_token(*id, token).await,
+        AuthOwner::Collection(id) => state.db.set_collection_oauth2_token(*id, token).await,
+    };
+    stored.map_err(|e| e.to_string())
+}
+
+// OAuth2 tokens are per device and never leave it through sync; the pushed copy keeps
+// the OAuth2 settings without the token
+fn strip_oauth2_token(auth: Option<&mut AuthConfig>) {
+    if let Some(config) = auth.and_then(|auth| auth.oauth2.as_mut()) {
+        config.token = None;
+    }
+}
+
 // Collection commands
 #[tauri::command]
 pub async fn create_collection(
@@ -218,41 +996,182 @@ pub async fn update_collection_auth(
 }
 
 #[tauri::command]
-pub async fn update_collection_name(
-    payload: UpdateCollectionNamePayload,
+pub async fn update_collection_defaults(
+    collection_id: String,
+    default_timeout: Option<u64>,
+    default_retries: Option<u32>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let collection_uuid = Uuid::parse_str(&payload.collection_id).map_err(|e| e.to_string())?;
+    let collection_uuid = Uuid::parse_str(&collection_id).map_err(|e| e.to_string())?;
 
     state
         .db
-        .update_collection_name(collection_uuid, payload.name)
+        .update_collection_defaults(collection_uuid, default_timeout, default_retries)
         .await
         .map_err(|e| e.to_string())
 }
 
-// Request commands
 #[tauri::command]
-pub async fn save_request(
-    payload: SaveRequestPayload,
+pub async fn save_auth_preset(
+    name: String,
+    auth: AuthConfig,
     state: State<'_, AppState>,
-) -> Result<HttpRequest, String> {
-    // Convert string collection_id to UUID if provided
-    let collection_uuid = if let Some(id_str) = &payload.collection_id {
-        Some(Uuid::parse_str(id_str).map_err(|e| format!("Invalid collection ID: {}", e))?)
-    } else {
-        None
-    };
-
-    // Parse request ID if provided
-    let request_id = if let Some(id_str) = &payload.id {
-        Some(Uuid::parse_str(id_str).map_err(|e| format!("Invalid request ID: {}", e))?)
-    } else {
-        None
+) -> Result<AuthPreset, String> {
+    let now = chrono::Utc::now();
+    let preset = AuthPreset {
+        id: Uuid::new_v4(),
+        name,
+        auth,
+        created_at: now,
+        updated_at: now,
     };
 
-    // Create or update the request
-    let request = if let Some(id) = request_id {
+    state
+        .db
+        .save_auth_preset(&preset)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(preset)
+}
+
+#[tauri::command]
+pub async fn list_auth_presets(state: State<'_, AppState>) -> Result<Vec<AuthPreset>, String> {
+    state.db.get_auth_presets().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn apply_auth_preset(
+    request_id: String,
+    preset_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    apply_preset_to(state.inner(), &request_id, &preset_id).await
+}
+
+// Copies the preset's auth onto a request, or onto a collection when the ID names one.
+// Variables are kept as written so the auth follows the active environment.
+async fn apply_preset_to(state: &AppState, target_id: &str, preset_id: &str) -> Result<(), String> {
+    let target_uuid = Uuid::parse_str(target_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let preset_uuid =
+        Uuid::parse_str(preset_id).map_err(|e| format!("Invalid preset ID: {}", e))?;
+
+    let preset = state
+        .db
+        .get_auth_preset(preset_uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Auth preset not found")?;
+
+    let is_request = state
+        .db
+        .get_request(target_uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if is_request {
+        return state
+            .db
+            .set_request_auth(target_uuid, Some(preset.auth))
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    let is_collection = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter()
+        .any(|c| c.id == target_uuid);
+    if !is_collection {
+        return Err("Request or collection not found".to_string());
+    }
+
+    state
+        .db
+        .update_collection_auth(target_uuid, Some(preset.auth))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_collection_name(
+    payload: UpdateCollectionNamePayload,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let collection_uuid = Uuid::parse_str(&payload.collection_id).map_err(|e| e.to_string())?;
+
+    state
+        .db
+        .update_collection_name(collection_uuid, payload.name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Request commands
+#[tauri::command]
+pub async fn save_request(
+    payload: SaveRequestPayload,
+    state: State<'_, AppState>,
+) -> Result<HttpRequest, String> {
+    save_request_payload(state.inner(), payload).await
+}
+
+// Saves the request, then sends what was stored so the two can't drift apart
+#[tauri::command]
+pub async fn send_and_save(
+    payload: SaveRequestPayload,
+    state: State<'_, AppState>,
+) -> Result<SendAndSaveResult, String> {
+    save_and_send(state.inner(), payload).await
+}
+
+async fn save_and_send(
+    state: &AppState,
+    payload: SaveRequestPayload,
+) -> Result<SendAndSaveResult, String> {
+    let request = save_request_payload(state, payload).await?;
+    let response = execute_request(state, payload_from_request(&request)).await?;
+
+    // Sending recorded the last run on the stored request
+    let request = match request.id {
+        Some(id) => state
+            .db
+            .get_request(id)
+            .await
+            .map_err(|e| e.to_string())?
+            .unwrap_or(request),
+        None => request,
+    };
+
+    Ok(SendAndSaveResult { request, response })
+}
+
+async fn save_request_payload(
+    state: &AppState,
+    payload: SaveRequestPayload,
+) -> Result<HttpRequest, String> {
+    // Committing the request makes its draft obsolete. Saved requests keep drafts under
+    // their id; each unsaved tab has its own key.
+    let draft_key = payload.id.clone().or_else(|| payload.draft_key.clone());
+
+    // Convert string collection_id to UUID if provided
+    let collection_uuid = if let Some(id_str) = &payload.collection_id {
+        Some(Uuid::parse_str(id_str).map_err(|e| format!("Invalid collection ID: {}", e))?)
+    } else {
+        None
+    };
+
+    // Parse request ID if provided
+    let request_id = if let Some(id_str) = &payload.id {
+        Some(Uuid::parse_str(id_str).map_err(|e| format!("Invalid request ID: {}", e))?)
+    } else {
+        None
+    };
+
+    // Create or update the request
+    let request = if let Some(id) = request_id {
         // Update existing request with provided ID
         HttpRequest {
             id: Some(id),
@@ -262,12 +1181,23 @@ pub async fn save_request(
             headers: payload.headers,
             body: payload.body,
             path_params: payload.path_params,
+            query_params: payload.query_params,
             collection_id: collection_uuid,
-            created_at: Some(chrono::Utc::now()), // Will be preserved by DB if exists
+            created_at: Some(chrono::Utc::now()), // The DB keeps the stored one on update
             updated_at: Some(chrono::Utc::now()),
             synced: false,
             version: 0,
             cloud_id: None,
+            last_status: None,
+            last_response_time: None,
+            last_run_at: None,
+            examples: Vec::new(), // Kept by the DB; examples have their own commands
+            success_criteria: payload.success_criteria,
+            auth: None,             // Kept by the DB; set through auth presets
+            alias: None,            // Kept by the DB; set through set_request_alias
+            depends_on: Vec::new(), // Kept by the DB; set through set_request_dependencies
+            depends_on_cloud_ids: Vec::new(),
+            disabled_in_environments: Vec::new(), // Kept by the DB as well
         }
     } else {
         // Create new request
@@ -275,7 +1205,9 @@ pub async fn save_request(
         new_request.headers = payload.headers;
         new_request.body = payload.body;
         new_request.path_params = payload.path_params;
+        new_request.query_params = payload.query_params;
         new_request.collection_id = collection_uuid;
+        new_request.success_criteria = payload.success_criteria;
         new_request
     };
 
@@ -286,848 +1218,5198 @@ pub async fn save_request(
         .await
         .map_err(|e| e.to_string())?;
 
+    if let Some(draft_key) = draft_key {
+        state
+            .db
+            .discard_draft(&draft_key)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
     Ok(saved_request)
 }
 
 #[tauri::command]
-pub async fn get_requests(
-    collection_id: Option<String>,
+pub async fn save_draft(
+    request_id: String,
+    payload: SaveRequestPayload,
     state: State<'_, AppState>,
-) -> Result<Vec<HttpRequest>, String> {
-    let uuid = if let Some(id) = collection_id {
-        Some(Uuid::parse_str(&id).map_err(|e| e.to_string())?)
-    } else {
-        None
-    };
-
-    state.db.get_requests(uuid).await.map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-pub async fn delete_request(id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+) -> Result<(), String> {
     state
         .db
-        .delete_request(uuid)
+        .save_draft(&request_id, &payload)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn move_request(
+pub async fn get_draft(
     request_id: String,
-    new_collection_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let request_uuid = Uuid::parse_str(&request_id).map_err(|e| e.to_string())?;
-
-    // Handle moving to root (no collection)
-    let collection_uuid = if new_collection_id == "root" {
-        None
-    } else {
-        Some(Uuid::parse_str(&new_collection_id).map_err(|e| e.to_string())?)
-    };
+) -> Result<Option<SaveRequestPayload>, String> {
+    state
+        .db
+        .get_draft(&request_id)
+        .await
+        .map_err(|e| e.to_string())
+}
 
+#[tauri::command]
+pub async fn discard_draft(request_id: String, state: State<'_, AppState>) -> Result<(), String> {
     state
         .db
-        .move_request_to_collection(request_uuid, collection_uuid)
+        .discard_draft(&request_id)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn update_request_name(
-    payload: UpdateRequestNamePayload,
+pub async fn save_request_view_state(
+    request_id: String,
+    json: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let request_uuid = Uuid::parse_str(&payload.request_id).map_err(|e| e.to_string())?;
-
+    let request_uuid =
+        Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    serde_json::from_str::<serde_json::Value>(&json)
+        .map_err(|e| format!("Invalid view state JSON: {}", e))?;
     state
         .db
-        .update_request_name(request_uuid, payload.name)
+        .save_view_state(request_uuid, &json)
         .await
         .map_err(|e| e.to_string())
 }
 
-// Environment commands
 #[tauri::command]
-pub async fn create_environment(
-    payload: CreateEnvironmentPayload,
+pub async fn get_request_view_state(
+    request_id: String,
     state: State<'_, AppState>,
-) -> Result<Environment, String> {
-    let environment = Environment::new(payload.name, payload.variables);
+) -> Result<Option<String>, String> {
+    let request_uuid =
+        Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
     state
         .db
-        .create_environment(&environment)
+        .get_view_state(request_uuid)
         .await
-        .map_err(|e| e.to_string())?;
-    Ok(environment)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_environments(state: State<'_, AppState>) -> Result<Vec<Environment>, String> {
-    state.db.get_environments().await.map_err(|e| e.to_string())
+pub async fn grpc_unary_call(
+    endpoint: String,
+    service: String,
+    method: String,
+    message_json: String,
+    use_reflection: bool,
+) -> Result<GrpcResponse, String> {
+    crate::grpc::unary_call(&endpoint, &service, &method, &message_json, use_reflection)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn set_active_environment(
-    id: Option<String>,
+pub async fn get_collection_request_counts(
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let uuid = if let Some(id) = id {
-        Some(Uuid::parse_str(&id).map_err(|e| e.to_string())?)
-    } else {
-        None
-    };
-
+) -> Result<HashMap<Uuid, usize>, String> {
     state
         .db
-        .set_active_environment(uuid)
+        .get_collection_request_counts()
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_active_environment(
+pub async fn set_request_alias(
+    request_id: String,
+    alias: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Option<Environment>, String> {
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let alias = alias
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty());
+    if alias
+        .as_deref()
+        .is_some_and(|alias| alias.contains(char::is_whitespace))
+    {
+        return Err("Aliases can't contain whitespace".to_string());
+    }
+
     state
         .db
-        .get_active_environment()
+        .set_request_alias(uuid, alias)
         .await
         .map_err(|e| e.to_string())
 }
 
+// `None` clears the request's own auth, so it inherits its collection's again. Saving a
+// request without auth keeps the stored one, so this is the only way to remove it.
 #[tauri::command]
-pub async fn update_environment(
-    id: String,
-    name: String,
-    variables: std::collections::HashMap<String, String>,
+pub async fn set_request_auth(
+    request_id: String,
+    auth: Option<AuthConfig>,
     state: State<'_, AppState>,
-) -> Result<Environment, String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
     state
         .db
-        .update_environment(uuid, name, variables)
+        .set_request_auth(uuid, auth)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn delete_environment(id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-
-    // If this environment is active, deactivate it first
-    let active_env = state
+pub async fn resolve_alias(alias: String, state: State<'_, AppState>) -> Result<String, String> {
+    let request = state
         .db
-        .get_active_environment()
+        .find_request_by_alias(alias.trim())
         .await
-        .map_err(|e| e.to_string())?;
-    if let Some(active) = active_env {
-        if active.id == uuid {
-            state
-                .db
-                .set_active_environment(None)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-    }
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No request has the alias '{}'", alias))?;
+    request
+        .id
+        .map(|id| id.to_string())
+        .ok_or_else(|| "Request has no ID".to_string())
+}
+
+#[tauri::command]
+pub async fn set_request_dependencies(
+    request_id: String,
+    depends_on: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let depends_on = depends_on
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| format!("Invalid dependency ID: {}", e)))
+        .collect::<Result<Vec<_>, String>>()?;
 
     state
         .db
-        .delete_environment(uuid)
+        .set_request_dependencies(uuid, depends_on)
         .await
         .map_err(|e| e.to_string())
 }
 
-// History commands
 #[tauri::command]
-pub async fn get_request_history(
-    limit: Option<i32>,
+pub async fn run_request_with_deps(
+    request_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<RequestHistory>, String> {
-    state.db.get_history(limit).await.map_err(|e| e.to_string())
+) -> Result<PrettyResponse, String> {
+    run_with_dependencies(state.inner(), &request_id, None).await
 }
 
-#[tauri::command]
-pub async fn clear_request_history(state: State<'_, AppState>) -> Result<(), String> {
-    state.db.clear_history().await.map_err(|e| e.to_string())
-}
+// Runs the transitive dependencies of a request in order, then the request itself. Every
+// run is saved to history, so later requests read earlier responses through
+// {{response.<name>.<path>}}. `environment_id` runs them all under that environment
+// instead of the active one.
+pub async fn run_with_dependencies(
+    state: &AppState,
+    request_id: &str,
+    environment_id: Option<Uuid>,
+) -> Result<PrettyResponse, String> {
+    let payload_for = |request: &HttpRequest| SendRequestPayload {
+        environment_id: environment_id.map(|id| id.to_string()),
+        ..payload_from_request(request)
+    };
+    let uuid = Uuid::parse_str(request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let requests: HashMap<Uuid, HttpRequest> = state
+        .db
+        .get_all_requests()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|request| request.id.map(|id| (id, request)))
+        .collect();
+    if !requests.contains_key(&uuid) {
+        return Err("Request not found".to_string());
+    }
 
-// Utility commands
-#[tauri::command]
-pub async fn format_json(content: String) -> Result<String, String> {
-    let value: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let order = dependency_order(uuid, &requests)?;
+    for id in &order[..order.len() - 1] {
+        let request = &requests[id];
+        let response = execute_request(state, payload_for(request))
+            .await
+            .map_err(|e| format!("Dependency '{}' failed: {}", request.name, e))?;
+        if !response
+            .is_success_by_criteria
+            .unwrap_or(response.status < 400)
+        {
+            return Err(format!(
+                "Dependency '{}' failed with status {}",
+                request.name, response.status
+            ));
+        }
+    }
 
-    serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to format JSON: {}", e))
+    execute_request(state, payload_for(&requests[&uuid])).await
 }
 
 #[tauri::command]
-pub async fn validate_url(url: String) -> Result<bool, String> {
-    match reqwest::Url::parse(&url) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
-    }
+pub async fn set_request_environment_exclusions(
+    request_id: String,
+    environments: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    state
+        .db
+        .set_request_environment_exclusions(uuid, environments)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn extract_env_variables(text: String) -> Result<Vec<String>, String> {
-    Ok(crate::http::extract_environment_variables(&text))
+pub async fn run_collection(
+    collection_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CollectionRunResult>, String> {
+    run_collection_requests(state.inner(), &collection_id).await
 }
 
-#[tauri::command]
-pub async fn highlight_response(
-    content: String,
-    content_type: Option<String>,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let language = if let Some(ct) = &content_type {
-        let ct_lower = ct.to_lowercase();
-        if ct_lower.contains("application/json") || ct_lower.contains("text/json") {
-            "json"
-        } else if ct_lower.contains("application/xml") || ct_lower.contains("text/xml") {
-            "xml"
-        } else if ct_lower.contains("text/html") {
-            "html"
-        } else if ct_lower.contains("text/css") {
-            "css"
-        } else if ct_lower.contains("application/javascript")
-            || ct_lower.contains("text/javascript")
-        {
-            "javascript"
-        } else {
-            "txt"
-        }
-    } else {
-        // Try to auto-detect JSON
-        if serde_json::from_str::<serde_json::Value>(&content).is_ok() {
-            "json"
-        } else {
-            "txt"
-        }
-    };
-
-    state
-        .http_client
-        .highlight_syntax(&content, language)
-        .map_err(|e| e.to_string())
-}
-
-// Import/Export commands
-#[tauri::command]
-pub async fn export_collection(
-    collection_id: String,
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    let uuid = Uuid::parse_str(&collection_id).map_err(|e| e.to_string())?;
-
-    // Get collection
-    let collections = state
+// Runs a collection's requests in creation order under the active environment. Failures
+// are recorded and the run goes on; requests excluded in the environment are skipped.
+async fn run_collection_requests(
+    state: &AppState,
+    collection_id: &str,
+) -> Result<Vec<CollectionRunResult>, String> {
+    let uuid =
+        Uuid::parse_str(collection_id).map_err(|e| format!("Invalid collection ID: {}", e))?;
+    let environment_name = state
         .db
-        .get_collections()
+        .get_active_environment()
         .await
-        .map_err(|e| e.to_string())?;
-    let collection = collections
-        .into_iter()
-        .find(|c| c.id == uuid)
-        .ok_or("Collection not found")?;
-
-    // Get requests in collection
-    let requests = state
+        .map_err(|e| e.to_string())?
+        .map(|env| env.name);
+    let mut requests = state
         .db
         .get_requests(Some(uuid))
         .await
         .map_err(|e| e.to_string())?;
+    requests.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.name.cmp(&b.name)));
 
-    let export_data = serde_json::json!({
-        "collection": collection,
-        "requests": requests,
-        "exported_at": chrono::Utc::now(),
-        "version": "1.0"
-    });
+    let mut results = Vec::with_capacity(requests.len());
+    for request in requests {
+        let Some(request_id) = request.id else {
+            continue;
+        };
+        let excluded = environment_name.as_deref().is_some_and(|name| {
+            request
+                .disabled_in_environments
+                .iter()
+                .any(|excluded| excluded.eq_ignore_ascii_case(name))
+        });
+        let mut result = CollectionRunResult {
+            request_id,
+            name: request.name.clone(),
+            outcome: RunOutcome::Skipped,
+            status: None,
+            response_time: None,
+            error: None,
+        };
+        if !excluded {
+            match execute_request(state, payload_from_request(&request)).await {
+                Ok(response) => {
+                    let passed = response
+                        .is_success_by_criteria
+                        .unwrap_or(response.status < 400);
+                    result.outcome = if passed {
+                        RunOutcome::Passed
+                    } else {
+                        RunOutcome::Failed
+                    };
+                    result.status = Some(response.status);
+                    result.response_time = Some(response.response_time);
+                }
+                Err(e) => {
+                    result.outcome = RunOutcome::Failed;
+                    result.error = Some(e);
+                }
+            }
+        }
+        results.push(result);
+    }
 
-    Ok(export_data)
+    Ok(results)
 }
 
-#[tauri::command]
-pub async fn import_collection(
-    data: serde_json::Value,
-    state: State<'_, AppState>,
-) -> Result<Collection, String> {
-    let collection_data = data
-        .get("collection")
-        .ok_or("Invalid export format: missing collection")?;
-
-    let requests_data = data
-        .get("requests")
-        .ok_or("Invalid export format: missing requests")?;
+// Dependencies before dependents, ending with `target`
+fn dependency_order(
+    target: Uuid,
+    requests: &HashMap<Uuid, HttpRequest>,
+) -> Result<Vec<Uuid>, String> {
+    let mut order = Vec::new();
+    visit_dependency(target, requests, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
 
-    // Create new collection with new ID
-    let mut collection: Collection = serde_json::from_value(collection_data.clone())
-        .map_err(|e| format!("Invalid collection data: {}", e))?;
+fn visit_dependency(
+    id: Uuid,
+    requests: &HashMap<Uuid, HttpRequest>,
+    path: &mut Vec<Uuid>,
+    order: &mut Vec<Uuid>,
+) -> Result<(), String> {
+    if order.contains(&id) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|visiting| *visiting == id) {
+        let names: Vec<&str> = path[start..]
+            .iter()
+            .chain([&id])
+            .map(|id| requests[id].name.as_str())
+            .collect();
+        return Err(format!("Dependency cycle: {}", names.join(" -> ")));
+    }
 
-    collection.id = Uuid::new_v4();
-    collection.name = format!("{} (Imported)", collection.name);
+    let request = requests
+        .get(&id)
+        .ok_or_else(|| format!("Dependency {} not found", id))?;
+    path.push(id);
+    for dependency in &request.depends_on {
+        visit_dependency(*dependency, requests, path, order)?;
+    }
+    path.pop();
+    order.push(id);
+    Ok(())
+}
 
-    // Save collection
-    state
+// The OAuth2 auth a collection or request ID is sent with, and where it is stored
+async fn oauth2_auth(state: &AppState, id: &str) -> Result<(AuthConfig, AuthOwner), String> {
+    let uuid = Uuid::parse_str(id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let request = state
         .db
-        .create_collection(&collection)
+        .get_request(uuid)
         .await
         .map_err(|e| e.to_string())?;
+    let collection_id = match &request {
+        Some(request) => request.collection_id.map(|id| id.to_string()),
+        None => Some(uuid.to_string()),
+    };
 
-    // Import requests
-    let requests: Vec<HttpRequest> = serde_json::from_value(requests_data.clone())
-        .map_err(|e| format!("Invalid requests data: {}", e))?;
-
-    for mut request in requests {
-        request.id = Some(Uuid::new_v4());
-        request.collection_id = Some(collection.id);
-        request.created_at = Some(chrono::Utc::now());
-        request.updated_at = Some(chrono::Utc::now());
-
-        state
-            .db
-            .save_request(&request)
-            .await
-            .map_err(|e| e.to_string())?;
+    match effective_auth(state, request.as_ref(), collection_id.as_deref()).await? {
+        Some((auth, owner))
+            if matches!(auth.auth_type, AuthType::OAuth2AuthCode) && auth.oauth2.is_some() =>
+        {
+            Ok((auth, owner))
+        }
+        _ => Err("No OAuth2 authorization-code auth applies here".to_string()),
     }
-
-    Ok(collection)
 }
 
-#[tauri::command]
-pub async fn import_postman_collection(
-    json_data: String,
-    state: State<'_, AppState>,
-) -> Result<Collection, String> {
-    // Try to parse as Value first to detect type
-    let v: serde_json::Value = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
-
-    if v.get("openapi").is_some() || v.get("swagger").is_some() {
-        // Handle OpenAPI
-        let spec: crate::openapi::OpenApiSpec = serde_json::from_value(v)
-            .map_err(|e| format!("Invalid OpenAPI spec: {}", e))?;
-        
-        let (collections, requests) = crate::openapi::convert_openapi(spec);
-        
-        // Save collection and requests
-        for collection in &collections {
-            state.db.create_collection(collection).await.map_err(|e| e.to_string())?;
-        }
-        for request in requests {
-            state.db.save_request(&request).await.map_err(|e| e.to_string())?;
-        }
-        
-        Ok(collections.into_iter().next().unwrap())
-    } else {
-        // Parse JSON string directly
-        let postman_collection: crate::postman::PostmanCollection = serde_json::from_str(&json_data)
-            .map_err(|e| {
-                eprintln!("❌ Postman import error: {}", e);
-                // Log the problematic area of JSON
-                if let Some(line_col) = e.to_string().split("at line ").nth(1) {
-                    if let Some(line_str) = line_col.split(" column").next() {
-                        if let Ok(line_num) = line_str.parse::<usize>() {
-                            let lines: Vec<&str> = json_data.lines().collect();
-                            if line_num > 0 && line_num <= lines.len() {
-                                eprintln!("📍 Context around line {}:", line_num);
-                                let start = line_num.saturating_sub(3);
-                                let end = (line_num + 2).min(lines.len());
-                                for i in start..end {
-                                    if i == line_num - 1 {
-                                        eprintln!(">>> {}: {}", i + 1, lines[i]);
-                                    } else {
-                                        eprintln!("    {}: {}", i + 1, lines[i]);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                format!("Invalid Postman collection format: {}", e)
-            })?;
+// OAuth2 settings with the active environment's variables applied
+async fn resolved_oauth2_config(
+    state: &AppState,
+    auth: &AuthConfig,
+) -> Result<OAuth2AuthCode, String> {
+    let env_vars = state
+        .db
+        .get_active_environment()
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|env| env.variables)
+        .unwrap_or_default();
 
-        // Convert to Geni format
-        let (mut collections, requests) =
-            crate::postman::convert_postman_collection(postman_collection);
+    resolve_auth_variables(auth, &env_vars)
+        .oauth2
+        .ok_or_else(|| "Auth has no OAuth2 settings".to_string())
+}
 
-        // Mark the root collection as imported
-        if let Some(root_collection) = collections.first_mut() {
-            root_collection.name = format!("{} (Imported from Postman)", root_collection.name);
-        }
+#[tauri::command]
+pub async fn oauth2_get_auth_url(id: String, state: State<'_, AppState>) -> Result<String, String> {
+    oauth2_authorization_url(state.inner(), &id).await
+}
 
-        // Save all collections (root and sub-collections)
-        for collection in &collections {
-            state
-                .db
-                .create_collection(collection)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+async fn oauth2_authorization_url(state: &AppState, id: &str) -> Result<String, String> {
+    let (auth, owner) = oauth2_auth(state, id).await?;
+    let config = resolved_oauth2_config(state, &auth).await?;
 
-        // Import all requests
-        for request in &requests {
-            state
-                .db
-                .save_request(request)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+    let request = crate::oauth::authorization_request(&config).map_err(|e| e.to_string())?;
+    state
+        .oauth2_pending
+        .lock()
+        .await
+        .insert(owner.id(), (request.pkce_verifier, request.csrf_state));
 
-        // Return the root collection
-        Ok(collections.into_iter().next().unwrap())
-    }
+    Ok(request.url)
 }
 
-// Cloud Sync Commands
-
-// Initialize or reconfigure sync client
+// `csrf_state` is the `state` parameter the provider redirected back with
 #[tauri::command]
-pub async fn initialize_sync(
-    provider: String,
-    api_server_url: Option<String>,
-    supabase_url: Option<String>,
-    supabase_api_key: Option<String>,
-    supabase_db_uri: Option<String>,
-    google_client_id: Option<String>,
-    google_client_secret: Option<String>,
-    google_redirect_uri: Option<String>,
+pub async fn oauth2_exchange_code(
+    id: String,
+    code: String,
+    csrf_state: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    use crate::sync::{ProviderConfig, SyncProvider};
-
-    let sync_provider = SyncProvider::from_str(&provider)
-        .ok_or_else(|| format!("Invalid provider: {}", provider))?;
+) -> Result<OAuth2Token, String> {
+    oauth2_exchange(state.inner(), &id, &code, &csrf_state).await
+}
 
-    let config = ProviderConfig {
-        provider: sync_provider.clone(),
-        api_server_url,
-        supabase_url,
-        supabase_api_key,
-        supabase_db_uri,
-        google_client_id,
-        google_client_secret,
-        google_redirect_uri,
+async fn oauth2_exchange(
+    state: &AppState,
+    id: &str,
+    code: &str,
+    csrf_state: &str,
+) -> Result<OAuth2Token, String> {
+    let (auth, owner) = oauth2_auth(state, id).await?;
+    let config = resolved_oauth2_config(state, &auth).await?;
+
+    // A mismatched state leaves the authorization pending, so a forged redirect can't
+    // cancel the real one
+    let verifier = {
+        let mut pending = state.oauth2_pending.lock().await;
+        let (_, expected) = pending
+            .get(&owner.id())
+            .ok_or("No authorization in progress; request an auth URL first")?;
+        if expected != csrf_state {
+            return Err("OAuth2 state doesn't match the authorization".to_string());
+        }
+        pending.remove(&owner.id()).unwrap_or_default().0
     };
 
-    let new_client = SyncClient::new(config.clone()).map_err(|e| e.to_string())?;
-
-    // Auto-create schema for Supabase if needed
-    if sync_provider == SyncProvider::Supabase {
-        new_client
-            .ensure_schema()
-            .await
-            .map_err(|e| e.to_string())?;
-    }
+    let token = crate::oauth::exchange_code(&config, code, &verifier)
+        .await
+        .map_err(|e| e.to_string())?;
+    store_oauth2_token(state, &owner, token.clone()).await?;
 
-    let mut client = state.sync_client.lock().await;
-    *client = new_client;
+    Ok(token)
+}
 
-    // Save config to database
-    let config_json = config.to_json().map_err(|e| e.to_string())?;
-    state
+// Stores a response as a named example on the request, replacing one with the same name
+#[tauri::command]
+pub async fn save_response_example(
+    request_id: String,
+    name: String,
+    response: HttpResponse,
+    state: State<'_, AppState>,
+) -> Result<HttpRequest, String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let request = state
         .db
-        .save_sync_config(sync_provider.as_str(), &config_json)
+        .get_request(uuid)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .ok_or("Request not found")?;
+
+    let mut examples = request.examples;
+    examples.retain(|example| example.name != name);
+    examples.push(SavedResponse {
+        name,
+        status: response.status,
+        headers: response.headers.to_map(),
+        body: response.body,
+        saved_at: chrono::Utc::now(),
+    });
 
     state
         .db
-        .set_last_sync_provider(sync_provider.as_str())
+        .set_request_examples(uuid, examples)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
+        .map_err(|e| e.to_string())
 }
 
-// Load saved sync configuration
+// Re-sends a saved request and checks the live response against one of its examples.
+// Only Content-Type is compared among headers unless others are listed.
 #[tauri::command]
-pub async fn load_saved_sync_config(state: State<'_, AppState>) -> Result<Option<String>, String> {
-    use crate::sync::ProviderConfig;
+pub async fn compare_to_example(
+    request_id: String,
+    example_name: String,
+    ignore_paths: Option<Vec<String>>,
+    compare_headers: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<ContractResult, String> {
+    compare_request_to_example(
+        state.inner(),
+        &request_id,
+        &example_name,
+        &ignore_paths.unwrap_or_default(),
+        &compare_headers.unwrap_or_else(|| vec!["Content-Type".to_string()]),
+    )
+    .await
+}
 
-    // Get last used provider
-    let provider = state
+async fn compare_request_to_example(
+    state: &AppState,
+    request_id: &str,
+    example_name: &str,
+    ignore_paths: &[String],
+    compare_headers: &[String],
+) -> Result<ContractResult, String> {
+    let uuid = Uuid::parse_str(request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let request = state
         .db
-        .get_last_sync_provider()
+        .get_request(uuid)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .ok_or("Request not found")?;
+    let example = request
+        .examples
+        .iter()
+        .find(|example| example.name == example_name)
+        .cloned()
+        .ok_or_else(|| format!("Example '{}' not found", example_name))?;
+
+    let response = execute_request(state, payload_from_request(&request)).await?;
+
+    let mut result = crate::contract::compare_to_example(
+        &example,
+        response.status,
+        &response.headers.to_map(),
+        &response.body,
+        compare_headers,
+        ignore_paths,
+    )
+    .map_err(|e| e.to_string())?;
+
+    if response.is_success_by_criteria == Some(false) {
+        result.matches = false;
+        result
+            .differences
+            .push("success criteria not met".to_string());
+    }
 
-    if let Some(provider_name) = provider {
-        // Get config for that provider
-        let config_json = state
-            .db
-            .get_sync_config(&provider_name)
-            .await
-            .map_err(|e| e.to_string())?;
+    Ok(result)
+}
 
-        if let Some(json) = config_json {
-            // Initialize sync client with saved config
-            let config = ProviderConfig::from_json(&json).map_err(|e| e.to_string())?;
+// Proposes variables to capture (ids, tokens, sessions) from a JSON response body
+#[tauri::command]
+pub async fn suggest_extractors(content: String) -> Result<Vec<ResponseExtractor>, String> {
+    let document: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Response is not JSON: {}", e))?;
+    Ok(crate::contract::suggest_extractors(&document))
+}
 
-            let new_client = SyncClient::new(config).map_err(|e| e.to_string())?;
+// Sends a saved request once per payload value, injected at the target. `payload_set`
+// adds one of the built-in lists (basic, sql, unicode) after the explicit values.
+#[tauri::command]
+pub async fn fuzz_request(
+    request_id: String,
+    target: FuzzTarget,
+    payloads: Vec<String>,
+    payload_set: Option<String>,
+    delay_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FuzzResult>, String> {
+    let mut payloads = payloads;
+    if let Some(name) = payload_set {
+        payloads.extend(
+            crate::fuzz::builtin_payloads(&name)
+                .ok_or_else(|| format!("Unknown payload set '{}'", name))?,
+        );
+    }
 
-            let mut client = state.sync_client.lock().await;
-            *client = new_client;
+    run_fuzz(
+        state.inner(),
+        &request_id,
+        &target,
+        &payloads,
+        delay_ms.map(Duration::from_millis),
+    )
+    .await
+}
 
-            // Return the config JSON for the frontend
-            return Ok(Some(json));
+// Runs sequentially so results line up with payloads and the target isn't flooded. Payloads are
+// sent ephemeral so a fuzz run doesn't fill the history or overwrite the request's last run
+async fn run_fuzz(
+    state: &AppState,
+    request_id: &str,
+    target: &FuzzTarget,
+    payloads: &[String],
+    delay: Option<Duration>,
+) -> Result<Vec<FuzzResult>, String> {
+    let uuid = Uuid::parse_str(request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let request = state
+        .db
+        .get_request(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Request not found")?;
+    let mut base_payload = payload_from_request(&request);
+    base_payload.ephemeral = true;
+
+    let mut results = Vec::with_capacity(payloads.len());
+    for (index, value) in payloads.iter().enumerate() {
+        if index > 0 {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
         }
+
+        let mut payload = base_payload.clone();
+        crate::fuzz::apply_fuzz_value(&mut payload, target, value).map_err(|e| e.to_string())?;
+
+        results.push(match execute_request(state, payload).await {
+            Ok(response) => FuzzResult {
+                payload: value.clone(),
+                status: Some(response.status),
+                response_time: Some(response.response_time),
+                body_snippet: crate::fuzz::body_snippet(&response.body),
+            },
+            Err(e) => FuzzResult {
+                payload: value.clone(),
+                status: None,
+                response_time: None,
+                body_snippet: e,
+            },
+        });
     }
 
-    Ok(None)
+    Ok(results)
+}
+
+// Send payload for a stored request, including its collection auth
+fn payload_from_request(request: &HttpRequest) -> SendRequestPayload {
+    SendRequestPayload {
+        method: request.method.clone(),
+        url: request.url.clone(),
+        headers: request.headers.clone(),
+        body: request.body.clone(),
+        path_params: request.path_params.clone(),
+        query_params: request.query_params.clone(),
+        timeout: None,
+        timeout_preset: None,
+        connect_timeout: None,
+        body_file: None,
+        body_file_raw: false,
+        collection_id: request.collection_id.map(|id| id.to_string()),
+        request_id: request.id.map(|id| id.to_string()),
+        capture_informational: false,
+        force_no_body: false,
+        success_criteria: request.success_criteria.clone(),
+        method_override: false,
+        apply_base_url_override: true,
+        resolve_trace: None,
+        user_agent: None,
+        retry_count: None,
+        ephemeral: false,
+        digest_auth: None,
+        aws_sigv4: None,
+        auth_headers: Vec::new(),
+        follow_redirects: None,
+        max_redirects: None,
+        insecure: None,
+        client_certificate: None,
+        cookie_jar: None,
+        retry_on: None,
+        retry_backoff_ms: None,
+        download_to: None,
+        decompress: None,
+        environment_id: None,
+    }
 }
 
-// API Server-specific auth commands
 #[tauri::command]
-pub async fn api_server_sign_up(
-    email: String,
-    password: String,
-    name: Option<String>,
+pub async fn get_requests(
+    collection_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<TokenResponse, String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .api_server_sign_up(&email, &password, name)
+) -> Result<Vec<HttpRequest>, String> {
+    let uuid = if let Some(id) = collection_id {
+        Some(Uuid::parse_str(&id).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    state.db.get_requests(uuid).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_request(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state
+        .db
+        .delete_request(uuid)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn api_server_sign_in(
-    email: String,
-    password: String,
+pub async fn move_request(
+    request_id: String,
+    new_collection_id: String,
     state: State<'_, AppState>,
-) -> Result<TokenResponse, String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .api_server_sign_in(&email, &password)
+) -> Result<(), String> {
+    let request_uuid = Uuid::parse_str(&request_id).map_err(|e| e.to_string())?;
+
+    // Handle moving to root (no collection)
+    let collection_uuid = if new_collection_id == "root" {
+        None
+    } else {
+        Some(Uuid::parse_str(&new_collection_id).map_err(|e| e.to_string())?)
+    };
+
+    state
+        .db
+        .move_request_to_collection(request_uuid, collection_uuid)
         .await
         .map_err(|e| e.to_string())
 }
 
-// Supabase-specific auth commands
 #[tauri::command]
-pub async fn supabase_sign_up(
-    email: String,
-    password: String,
-    name: Option<String>,
+pub async fn update_request_name(
+    payload: UpdateRequestNamePayload,
     state: State<'_, AppState>,
-) -> Result<TokenResponse, String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .supabase_sign_up(&email, &password, name)
+) -> Result<(), String> {
+    let request_uuid = Uuid::parse_str(&payload.request_id).map_err(|e| e.to_string())?;
+
+    state
+        .db
+        .update_request_name(request_uuid, payload.name)
         .await
         .map_err(|e| e.to_string())
 }
 
+// Environment commands
 #[tauri::command]
-pub async fn supabase_sign_in(
-    email: String,
-    password: String,
+pub async fn create_environment(
+    payload: CreateEnvironmentPayload,
     state: State<'_, AppState>,
-) -> Result<TokenResponse, String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .supabase_sign_in(&email, &password)
+) -> Result<Environment, String> {
+    let environment = Environment::new(payload.name, payload.variables);
+    state
+        .db
+        .create_environment(&environment)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(environment)
+}
+
+// Creates an environment from process environment variables. Either a prefix
+// (stripped from the stored keys) or an explicit allowlist is required so the
+// whole environment is never imported by accident.
+#[tauri::command]
+pub async fn import_system_env(
+    name: String,
+    prefix: Option<String>,
+    allowlist: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Environment, String> {
+    let variables = select_system_env(std::env::vars(), prefix.as_deref(), allowlist.as_deref())?;
+
+    let environment = Environment::new(name, variables);
+    state
+        .db
+        .create_environment(&environment)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(environment)
+}
+
+#[tauri::command]
+pub async fn environment_to_dotenv(
+    id: String,
+    include_secrets: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let environment = state
+        .db
+        .get_environments()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|env| env.id == uuid)
+        .ok_or("Environment not found")?;
+
+    Ok(crate::dotenv::to_dotenv(
+        &environment.variables,
+        include_secrets.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+pub async fn diff_environments(
+    id_a: String,
+    id_b: String,
+    state: State<'_, AppState>,
+) -> Result<EnvDiff, String> {
+    let uuid_a = Uuid::parse_str(&id_a).map_err(|e| e.to_string())?;
+    let uuid_b = Uuid::parse_str(&id_b).map_err(|e| e.to_string())?;
+    let environments = state
+        .db
+        .get_environments()
+        .await
+        .map_err(|e| e.to_string())?;
+    let find = |uuid: Uuid| {
+        environments
+            .iter()
+            .find(|env| env.id == uuid)
+            .ok_or("Environment not found")
+    };
+
+    Ok(diff_variables(
+        &find(uuid_a)?.variables,
+        &find(uuid_b)?.variables,
+    ))
+}
+
+fn diff_variables(a: &HashMap<String, String>, b: &HashMap<String, String>) -> EnvDiff {
+    let mut diff = EnvDiff::default();
+
+    for (key, value_a) in a {
+        match b.get(key) {
+            None => diff.only_in_a.push(key.clone()),
+            Some(value_b) if value_b != value_a => {
+                let masked = crate::secrets::is_sensitive_name(key);
+                diff.differing.push(EnvValueDiff {
+                    key: key.clone(),
+                    value_a: (!masked).then(|| value_a.clone()),
+                    value_b: (!masked).then(|| value_b.clone()),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.only_in_b = b
+        .keys()
+        .filter(|key| !a.contains_key(*key))
+        .cloned()
+        .collect();
+
+    diff.only_in_a.sort();
+    diff.only_in_b.sort();
+    diff.differing.sort_by(|x, y| x.key.cmp(&y.key));
+    diff
+}
+
+#[tauri::command]
+pub async fn import_dotenv(
+    name: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<Environment, String> {
+    let variables = crate::dotenv::parse_dotenv(&content).map_err(|e| e.to_string())?;
+
+    let environment = Environment::new(name, variables);
+    state
+        .db
+        .create_environment(&environment)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(environment)
+}
+
+fn select_system_env(
+    vars: impl Iterator<Item = (String, String)>,
+    prefix: Option<&str>,
+    allowlist: Option<&[String]>,
+) -> Result<HashMap<String, String>, String> {
+    let prefix = prefix.filter(|p| !p.is_empty());
+    let allowlist = allowlist.filter(|list| !list.is_empty());
+    if prefix.is_none() && allowlist.is_none() {
+        return Err("A prefix or an allowlist of variable names is required".to_string());
+    }
+
+    Ok(vars
+        .filter_map(|(key, value)| {
+            if allowlist.is_some_and(|list| list.contains(&key)) {
+                return Some((key, value));
+            }
+            let stripped = key.strip_prefix(prefix?)?;
+            (!stripped.is_empty()).then(|| (stripped.to_string(), value))
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_environments(state: State<'_, AppState>) -> Result<Vec<Environment>, String> {
+    state.db.get_environments().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_active_environment(
+    id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let uuid = if let Some(id) = id {
+        Some(Uuid::parse_str(&id).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    state
+        .db
+        .set_active_environment(uuid)
         .await
         .map_err(|e| e.to_string())
 }
 
-// Google Drive-specific auth commands
 #[tauri::command]
-pub async fn google_drive_get_auth_url(
+pub async fn get_active_environment(
     state: State<'_, AppState>,
-) -> Result<(String, String), String> {
-    let client = state.sync_client.lock().await;
-    client
-        .google_drive_generate_auth_url()
+) -> Result<Option<Environment>, String> {
+    state
+        .db
+        .get_active_environment()
+        .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn google_drive_exchange_code(
-    code: String,
+pub async fn set_environment_base_url_override(
+    id: String,
+    base_url_override: Option<String>,
     state: State<'_, AppState>,
-) -> Result<TokenResponse, String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .google_drive_exchange_code(&code)
+) -> Result<Environment, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let base_url_override = base_url_override.filter(|url| !url.trim().is_empty());
+    state
+        .db
+        .set_environment_base_url_override(uuid, base_url_override)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn google_drive_refresh_token(state: State<'_, AppState>) -> Result<(), String> {
-    let mut client = state.sync_client.lock().await;
-    client
-        .google_drive_refresh_token()
+pub async fn update_environment(
+    id: String,
+    name: String,
+    variables: std::collections::HashMap<String, String>,
+    state: State<'_, AppState>,
+) -> Result<Environment, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    state
+        .db
+        .update_environment(uuid, name, variables)
         .await
         .map_err(|e| e.to_string())
 }
 
-// Common auth commands
 #[tauri::command]
-pub async fn logout(state: State<'_, AppState>) -> Result<(), String> {
-    use crate::sync::{ProviderConfig, SyncClient, SyncProvider};
+pub async fn delete_environment(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
-    // Sign out from current provider
-    let mut client = state.sync_client.lock().await;
-    client.sign_out();
-    drop(client); // Release the lock
+    // If this environment is active, deactivate it first
+    let active_env = state
+        .db
+        .get_active_environment()
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(active) = active_env {
+        if active.id == uuid {
+            state
+                .db
+                .set_active_environment(None)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    state
+        .db
+        .delete_environment(uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// History commands
+#[tauri::command]
+pub async fn get_request_history(
+    limit: Option<i32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RequestHistory>, String> {
+    state.db.get_history(limit).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_request_history(state: State<'_, AppState>) -> Result<(), String> {
+    state.db.clear_history().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_history_body_length(
+    history_id: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    Ok(history_body(state.inner(), &history_id).await?.len())
+}
+
+#[tauri::command]
+pub async fn get_history_body_window(
+    history_id: String,
+    offset: usize,
+    length: usize,
+    state: State<'_, AppState>,
+) -> Result<BodyWindow, String> {
+    let body = history_body(state.inner(), &history_id).await?;
+    Ok(body_window(&body, offset, length))
+}
+
+async fn history_entry(state: &AppState, history_id: &str) -> Result<RequestHistory, String> {
+    let uuid = Uuid::parse_str(history_id).map_err(|e| format!("Invalid history ID: {}", e))?;
+    state
+        .db
+        .get_history_entry(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "History entry not found".to_string())
+}
+
+async fn history_body(state: &AppState, history_id: &str) -> Result<String, String> {
+    let entry = history_entry(state, history_id).await?;
+    Ok(entry
+        .response
+        .map(|response| response.body)
+        .unwrap_or_default())
+}
+
+// Backs the response viewer's "copy as" menu
+#[tauri::command]
+pub async fn render_response_as(
+    history_id: String,
+    format: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let entry = history_entry(state.inner(), &history_id).await?;
+    render_history_entry(&entry, &format)
+}
+
+// Formats: raw, pretty-json, headers-only, curl-replay and markdown
+fn render_history_entry(entry: &RequestHistory, format: &str) -> Result<String, String> {
+    match format {
+        "curl-replay" => {
+            let url = if entry.resolved_url.is_empty() {
+                &entry.request.url
+            } else {
+                &entry.resolved_url
+            };
+            // Older entries only kept the templated headers and body
+            let Some(headers) = &entry.resolved_headers else {
+                return Ok(crate::export::request_to_curl(&entry.request, url));
+            };
+            let sent = HttpRequest {
+                headers: headers.clone(),
+                body: entry.resolved_body.clone(),
+                ..entry.request.clone()
+            };
+            return Ok(crate::export::request_to_curl(&sent, url));
+        }
+        "markdown" => return Ok(crate::export::response_to_markdown(entry)),
+        "raw" | "pretty-json" | "headers-only" => {}
+        other => return Err(format!("Unknown format '{}'", other)),
+    }
+
+    let response = entry
+        .response
+        .as_ref()
+        .ok_or("History entry has no response")?;
+    match format {
+        "raw" => Ok(response.body.clone()),
+        "pretty-json" => {
+            let value: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Response body is not JSON: {}", e))?;
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+        }
+        _ => {
+            let mut lines = vec![format!("{} {}", response.status, response.status_text)];
+            lines.extend(
+                response
+                    .headers
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value)),
+            );
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+// Both ends snap back to a character boundary, so consecutive windows never split or
+// repeat a character. A window too short to hold the character at its start is widened to
+// that character, so paging always moves forward.
+fn body_window(body: &str, offset: usize, length: usize) -> BodyWindow {
+    let floor_boundary = |mut index: usize| {
+        index = index.min(body.len());
+        while !body.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let start = floor_boundary(offset);
+    let mut end = floor_boundary(offset.saturating_add(length)).max(start);
+    if end == start && length > 0 {
+        end = body[start..]
+            .chars()
+            .next()
+            .map_or(start, |c| start + c.len_utf8());
+    }
+
+    BodyWindow {
+        offset: start,
+        length: end - start,
+        content: body[start..end].to_string(),
+    }
+}
+
+// Database maintenance commands
+#[tauri::command]
+pub async fn get_database_stats(state: State<'_, AppState>) -> Result<DatabaseStats, String> {
+    state.db.get_stats().await.map_err(|e| e.to_string())
+}
+
+// Flushes pending writes; see `Database::compact` for why the files may not shrink
+#[tauri::command]
+pub async fn compact_database(state: State<'_, AppState>) -> Result<DatabaseStats, String> {
+    state.db.compact().await.map_err(|e| e.to_string())?;
+    state.db.get_stats().await.map_err(|e| e.to_string())
+}
+
+// Records skipped while loading because they could not be deserialized
+#[tauri::command]
+pub async fn get_corrupt_records(state: State<'_, AppState>) -> Result<Vec<CorruptRecord>, String> {
+    state
+        .db
+        .get_corrupt_records()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_corrupt_record(
+    tree: String,
+    key: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .delete_corrupt_record(&tree, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Utility commands
+#[tauri::command]
+pub async fn format_json(content: String) -> Result<String, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to format JSON: {}", e))
+}
+
+// Writes a response body already received to disk and returns the path written. A
+// directory `path` gets the suggested name (a file name or a Content-Disposition value),
+// and a path without an extension takes the suggested name's, or the content type's. An
+// existing file at the resulting path is overwritten.
+#[tauri::command]
+pub async fn save_response_body(
+    content: String,
+    suggested_name: Option<String>,
+    content_type: Option<String>,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let path = response_file_path(
+        resolve_workspace_path(&state.workspace_dir, &path),
+        suggested_name.as_deref(),
+        content_type.as_deref(),
+    );
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn response_file_path(
+    mut path: PathBuf,
+    suggested_name: Option<&str>,
+    content_type: Option<&str>,
+) -> PathBuf {
+    let suggested = suggested_name.and_then(|name| {
+        crate::http::content_disposition_filename(name).or_else(|| {
+            Path::new(name)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        })
+    });
+    if path.is_dir() {
+        path.push(suggested.as_deref().unwrap_or("response"));
+    }
+    if path.extension().is_none() {
+        let extension = suggested
+            .as_deref()
+            .and_then(|name| Path::new(name).extension())
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .or_else(|| {
+                content_type
+                    .and_then(crate::http::extension_for_content_type)
+                    .map(str::to_string)
+            });
+        if let Some(extension) = extension {
+            path.set_extension(extension);
+        }
+    }
+    path
+}
+
+// Rebuilds the request and sync clients so they pick up the current root certificates
+// and proxy
+async fn rebuild_http_clients(state: &AppState) -> Result<(), String> {
+    state
+        .http_client
+        .rebuild_client()
+        .map_err(|e| e.to_string())?;
+    state
+        .sync_client
+        .lock()
+        .await
+        .rebuild_http_client()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_custom_ca_bundle(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let path = resolve_workspace_path(&state.workspace_dir, &path);
+    let certs = crate::http::load_ca_bundle(&path).map_err(|e| e.to_string())?;
+
+    crate::http::set_root_certificates(certs);
+    rebuild_http_clients(state.inner()).await?;
+
+    state
+        .db
+        .set_custom_ca_bundle_path(Some(&path.to_string_lossy()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_custom_ca_bundle(state: State<'_, AppState>) -> Result<(), String> {
+    crate::http::set_root_certificates(Vec::new());
+    rebuild_http_clients(state.inner()).await?;
+
+    state
+        .db
+        .set_custom_ca_bundle_path(None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_proxy_settings(
+    state: State<'_, AppState>,
+) -> Result<Option<ProxySettings>, String> {
+    state
+        .db
+        .get_proxy_settings()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_proxy_settings(
+    settings: ProxySettings,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let proxy = crate::http::build_proxy(&settings).map_err(|e| e.to_string())?;
+
+    crate::http::set_proxy(Some(proxy));
+    rebuild_http_clients(state.inner()).await?;
+
+    state
+        .db
+        .set_proxy_settings(Some(&settings))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_proxy_settings(state: State<'_, AppState>) -> Result<(), String> {
+    crate::http::set_proxy(None);
+    rebuild_http_clients(state.inner()).await?;
+
+    state
+        .db
+        .set_proxy_settings(None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// One jar per environment; requests without an active environment share "default"
+fn cookie_jar_scope(environment_id: Option<Uuid>) -> String {
+    environment_id
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+fn cookie_jar_scope_from(environment_id: Option<String>) -> Result<String, String> {
+    let environment_id = environment_id
+        .map(|id| Uuid::parse_str(&id))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    Ok(cookie_jar_scope(environment_id))
+}
+
+async fn save_cookie_jar(state: &AppState, scope: &str) -> Result<(), String> {
+    let json = state
+        .http_client
+        .cookie_jar(scope)
+        .to_json()
+        .map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_cookie_jar(scope, &json)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_cookie_jar_enabled(
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_config_flag(COOKIE_JAR_KEY, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_cookie_jar_enabled(state: State<'_, AppState>) -> Result<bool, String> {
+    state
+        .db
+        .get_config_flag(COOKIE_JAR_KEY)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Cookies in the jar of the given environment (or the default jar)
+#[tauri::command]
+pub async fn get_cookies(
+    environment_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<StoredCookie>, String> {
+    let scope = cookie_jar_scope_from(environment_id)?;
+    Ok(state.http_client.cookie_jar(&scope).list())
+}
+
+#[tauri::command]
+pub async fn add_cookie(
+    environment_id: Option<String>,
+    cookie: StoredCookie,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let scope = cookie_jar_scope_from(environment_id)?;
+    state
+        .http_client
+        .cookie_jar(&scope)
+        .add(&cookie)
+        .map_err(|e| e.to_string())?;
+    save_cookie_jar(state.inner(), &scope).await
+}
+
+#[tauri::command]
+pub async fn clear_cookies(
+    environment_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let scope = cookie_jar_scope_from(environment_id)?;
+    state.http_client.cookie_jar(&scope).clear();
+    save_cookie_jar(state.inner(), &scope).await
+}
+
+#[tauri::command]
+pub async fn enable_request_log(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let path = resolve_workspace_path(&state.workspace_dir, &path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    state
+        .db
+        .set_request_log_path(Some(&path.to_string_lossy()))
+        .await
+        .map_err(|e| e.to_string())?;
+    *state.request_log_path.write().unwrap() = Some(path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn disable_request_log(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .db
+        .set_request_log_path(None)
+        .await
+        .map_err(|e| e.to_string())?;
+    *state.request_log_path.write().unwrap() = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn analyze_json(content: String) -> Result<JsonMetrics, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    Ok(crate::http::analyze_json(&value))
+}
+
+// Applies `patch` to `base` as a JSON Merge Patch, for building a PATCH body from a response
+#[tauri::command]
+pub async fn merge_json(base: String, patch: String) -> Result<String, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(&base).map_err(|e| format!("Invalid base JSON: {}", e))?;
+    let patch: serde_json::Value =
+        serde_json::from_str(&patch).map_err(|e| format!("Invalid patch JSON: {}", e))?;
+
+    crate::http::merge_patch(&mut value, &patch);
+    serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to format JSON: {}", e))
+}
+
+#[tauri::command]
+pub async fn validate_url(url: String) -> Result<bool, String> {
+    match reqwest::Url::parse(&url) {
+        Ok(_) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub async fn extract_env_variables(text: String) -> Result<Vec<String>, String> {
+    Ok(crate::http::extract_environment_variables(&text))
+}
+
+#[tauri::command]
+pub async fn parse_query_params(url: String) -> Result<Vec<(String, String, bool)>, String> {
+    Ok(crate::http::parse_query_params(&url))
+}
+
+#[tauri::command]
+pub async fn build_url_with_params(
+    base: String,
+    params: Vec<(String, String, bool)>,
+) -> Result<String, String> {
+    Ok(crate::http::build_url_with_params(&base, &params))
+}
+
+#[tauri::command]
+pub async fn scan_request_for_secrets(
+    request_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SecretFinding>, String> {
+    let request_uuid =
+        Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let request = state
+        .db
+        .get_request(request_uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Request not found".to_string())?;
+
+    Ok(crate::secrets::scan_request(&request))
+}
+
+// Scrubs a response body before it is shared: JSONPaths for JSON, regexes for anything else
+#[tauri::command]
+pub async fn redact_response(content: String, paths: Vec<String>) -> Result<String, String> {
+    crate::secrets::redact_body(&content, &paths).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_mixed_content(
+    request_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<MixedContentWarning>, String> {
+    let request_uuid =
+        Uuid::parse_str(&request_id).map_err(|e| format!("Invalid request ID: {}", e))?;
+    let request = state
+        .db
+        .get_request(request_uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Request not found".to_string())?;
+    let variables = state
+        .db
+        .get_active_environment()
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|env| env.variables)
+        .unwrap_or_default();
+
+    Ok(crate::secrets::check_mixed_content(&request, &variables))
+}
+
+// Lets the UI flag mistakes (bad JSON, missing files, unresolved URLs) before sending
+#[tauri::command]
+pub async fn validate_request(
+    payload: SendRequestPayload,
+    state: State<'_, AppState>,
+) -> Result<Vec<ValidationIssue>, String> {
+    validate_with_state(state.inner(), &payload).await
+}
+
+// Checks the payload against the environment and workspace the send would use
+async fn validate_with_state(
+    state: &AppState,
+    payload: &SendRequestPayload,
+) -> Result<Vec<ValidationIssue>, String> {
+    let variables = payload_environment(state, payload)
+        .await?
+        .map(|env| env.variables)
+        .unwrap_or_default();
+
+    Ok(crate::http::validate_payload(
+        payload,
+        &variables,
+        &state.workspace_dir,
+    ))
+}
+
+#[tauri::command]
+pub async fn inspect_jwt(token: String) -> Result<JwtInfo, String> {
+    crate::http::inspect_jwt(&token).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn highlight_response(
+    content: String,
+    content_type: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let language = state
+        .http_client
+        .response_language(&content, content_type.as_deref());
+
+    state
+        .http_client
+        .highlight_syntax(&content, language)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_respect_content_type_strictly(
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_config_flag(STRICT_CONTENT_TYPE_KEY, enabled)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.http_client.set_strict_content_type(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_respect_content_type_strictly(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.http_client.strict_content_type())
+}
+
+// Import/Export commands
+#[tauri::command]
+pub async fn export_collection(
+    collection_id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let uuid = Uuid::parse_str(&collection_id).map_err(|e| e.to_string())?;
+    export_collection_data(state.inner(), uuid).await
+}
+
+async fn export_collection_data(state: &AppState, uuid: Uuid) -> Result<serde_json::Value, String> {
+    // Get collection
+    let collections = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?;
+    let collection = collections
+        .into_iter()
+        .find(|c| c.id == uuid)
+        .ok_or("Collection not found")?;
+
+    // Get requests in collection
+    let requests = state
+        .db
+        .get_requests(Some(uuid))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut export_data = serde_json::json!({
+        "collection": collection,
+        "requests": requests,
+        "exported_at": chrono::Utc::now(),
+        "version": "1.0"
+    });
+    export_data["checksum"] = crate::export::export_checksum(&export_data).into();
+
+    Ok(export_data)
+}
+
+// Whole-workspace export for moving to another machine. Credentials are left out
+// unless explicitly requested.
+#[tauri::command]
+pub async fn export_workspace(
+    include_secrets: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<WorkspaceBundle, String> {
+    build_workspace_bundle(state.inner(), include_secrets.unwrap_or(false)).await
+}
+
+async fn build_workspace_bundle(
+    state: &AppState,
+    include_secrets: bool,
+) -> Result<WorkspaceBundle, String> {
+    let mut bundle = WorkspaceBundle {
+        version: "1.0".to_string(),
+        exported_at: chrono::Utc::now(),
+        collections: state
+            .db
+            .get_collections()
+            .await
+            .map_err(|e| e.to_string())?,
+        requests: state
+            .db
+            .get_all_requests()
+            .await
+            .map_err(|e| e.to_string())?,
+        environments: state
+            .db
+            .get_environments()
+            .await
+            .map_err(|e| e.to_string())?,
+        config: state
+            .db
+            .get_config_entries()
+            .await
+            .map_err(|e| e.to_string())?,
+    };
+
+    if !include_secrets {
+        crate::export::strip_workspace_secrets(&mut bundle);
+    }
+    Ok(bundle)
+}
+
+#[tauri::command]
+pub async fn import_workspace(
+    data: String,
+    mode: ImportMode,
+    state: State<'_, AppState>,
+) -> Result<DatabaseStats, String> {
+    let bundle: WorkspaceBundle =
+        serde_json::from_str(&data).map_err(|e| format!("Invalid workspace bundle: {}", e))?;
+
+    state
+        .db
+        .import_workspace(&bundle, mode)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.db.get_stats().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_collection_markdown(
+    collection_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    collection_markdown(state.inner(), &collection_id).await
+}
+
+async fn collection_markdown(state: &AppState, collection_id: &str) -> Result<String, String> {
+    let uuid = Uuid::parse_str(collection_id).map_err(|e| e.to_string())?;
+
+    let collections = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = collections
+        .iter()
+        .find(|c| c.id == uuid)
+        .ok_or("Collection not found")?;
+
+    let requests = state
+        .db
+        .get_all_requests()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(crate::export::collection_to_markdown(
+        root,
+        &collections,
+        &requests,
+    ))
+}
+
+#[tauri::command]
+pub async fn import_collection(
+    data: serde_json::Value,
+    on_conflict: Option<ImportConflict>,
+    state: State<'_, AppState>,
+) -> Result<CollectionImport, String> {
+    import_collection_checked(
+        state.inner(),
+        &data,
+        on_conflict.unwrap_or(ImportConflict::Rename),
+    )
+    .await
+}
+
+// Imports the collection, warning (without failing) when the export was edited
+async fn import_collection_checked(
+    state: &AppState,
+    data: &serde_json::Value,
+    on_conflict: ImportConflict,
+) -> Result<CollectionImport, String> {
+    let warnings = crate::export::checksum_warning(data).into_iter().collect();
+    let collection = import_collection_data(state, data, on_conflict).await?;
+    Ok(CollectionImport {
+        collection,
+        warnings,
+    })
+}
+
+async fn import_collection_data(
+    state: &AppState,
+    data: &serde_json::Value,
+    on_conflict: ImportConflict,
+) -> Result<Collection, String> {
+    let collection_data = data
+        .get("collection")
+        .ok_or("Invalid export format: missing collection")?;
+
+    let requests_data = data
+        .get("requests")
+        .ok_or("Invalid export format: missing requests")?;
+
+    let mut collection: Collection = serde_json::from_value(collection_data.clone())
+        .map_err(|e| format!("Invalid collection data: {}", e))?;
+    let requests: Vec<HttpRequest> = serde_json::from_value(requests_data.clone())
+        .map_err(|e| format!("Invalid requests data: {}", e))?;
+
+    // The exported parent is an ID from the source workspace; the import lands under it
+    // only if it exists here, and at the top level otherwise
+    let collections = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?;
+    collection.parent_id = collection
+        .parent_id
+        .filter(|parent| collections.iter().any(|c| c.id == *parent));
+
+    // Conflicts are same-named collections under the same parent
+    let siblings: Vec<Collection> = collections
+        .into_iter()
+        .filter(|c| c.parent_id == collection.parent_id)
+        .collect();
+    let existing = siblings.iter().find(|c| c.name == collection.name);
+
+    match (existing, on_conflict) {
+        (Some(existing), ImportConflict::Skip) => return Ok(existing.clone()),
+        (Some(existing), ImportConflict::Overwrite) => {
+            // Keep identity and sync state so the overwrite syncs as an update
+            collection.id = existing.id;
+            collection.created_at = existing.created_at;
+            collection.updated_at = chrono::Utc::now();
+            collection.cloud_id = existing.cloud_id.clone();
+            collection.version = existing.version + 1;
+            collection.synced = false;
+
+            // The old subfolders and their requests go too, so none are left orphaned
+            state
+                .db
+                .delete_collection(existing.id)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        (Some(_), ImportConflict::Rename) => {
+            collection.id = Uuid::new_v4();
+            collection.name = (2..)
+                .map(|n| format!("{} ({})", collection.name, n))
+                .find(|name| !siblings.iter().any(|c| &c.name == name))
+                .unwrap();
+        }
+        (None, _) => collection.id = Uuid::new_v4(),
+    }
+
+    // Save collection
+    state
+        .db
+        .create_collection(&collection)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Import requests
+
+    for mut request in requests {
+        request.id = Some(Uuid::new_v4());
+        request.collection_id = Some(collection.id);
+        request.created_at = Some(chrono::Utc::now());
+        request.updated_at = Some(chrono::Utc::now());
+
+        state
+            .db
+            .save_request(&request)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(collection)
+}
+
+#[tauri::command]
+pub async fn import_postman_collection_merge(
+    json_data: String,
+    target_collection_id: String,
+    state: State<'_, AppState>,
+) -> Result<MergeImportResult, String> {
+    let target = Uuid::parse_str(&target_collection_id)
+        .map_err(|e| format!("Invalid collection ID: {}", e))?;
+    let postman_collection: crate::postman::PostmanCollection = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Invalid Postman collection format: {}", e))?;
+
+    merge_postman_import(state.inner(), postman_collection, target).await
+}
+
+// Re-import into an existing collection: folders are matched by name and requests by
+// method + path, so importing the same collection twice creates nothing new
+async fn merge_postman_import(
+    state: &AppState,
+    postman_collection: crate::postman::PostmanCollection,
+    target: Uuid,
+) -> Result<MergeImportResult, String> {
+    let mut existing_collections = state
+        .db
+        .get_collections()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !existing_collections.iter().any(|c| c.id == target) {
+        return Err("Target collection not found".to_string());
+    }
+
+    let (collections, requests) = crate::postman::convert_postman_collection(postman_collection);
+
+    // Map incoming collections onto the target subtree, creating missing folders.
+    // Folders always come after their parent in the converted list.
+    let mut collection_map: HashMap<Uuid, Uuid> = HashMap::new();
+    collection_map.insert(collections[0].id, target);
+    for folder in collections.iter().skip(1) {
+        let parent = folder
+            .parent_id
+            .and_then(|id| collection_map.get(&id).copied())
+            .unwrap_or(target);
+        let existing = existing_collections
+            .iter()
+            .find(|c| c.parent_id == Some(parent) && c.name == folder.name)
+            .map(|c| c.id);
+
+        let mapped = match existing {
+            Some(id) => id,
+            None => {
+                let mut new_folder = Collection::new_with_parent(
+                    folder.name.clone(),
+                    folder.description.clone(),
+                    Some(parent),
+                );
+                new_folder.auth = folder.auth.clone();
+                state
+                    .db
+                    .create_collection(&new_folder)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let id = new_folder.id;
+                existing_collections.push(new_folder);
+                id
+            }
+        };
+        collection_map.insert(folder.id, mapped);
+    }
+
+    // Existing requests in the mapped folders, keyed by folder and method + path, so a
+    // request only matches one in the folder it is imported into
+    let subtree: std::collections::HashSet<Uuid> = collection_map.values().copied().collect();
+    let mut existing_requests: HashMap<(Uuid, String), Vec<HttpRequest>> = HashMap::new();
+    for request in state
+        .db
+        .get_all_requests()
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        if let Some(folder) = request.collection_id.filter(|id| subtree.contains(id)) {
+            existing_requests
+                .entry((folder, crate::postman::request_match_key(&request)))
+                .or_default()
+                .push(request);
+        }
+    }
+
+    let mut result = MergeImportResult {
+        created: 0,
+        updated: 0,
+    };
+    for incoming in requests {
+        let folder = incoming
+            .collection_id
+            .and_then(|id| collection_map.get(&id).copied())
+            .unwrap_or(target);
+        let key = (folder, crate::postman::request_match_key(&incoming));
+        let matched = existing_requests
+            .get_mut(&key)
+            .and_then(|matches| matches.pop());
+
+        let request = match matched {
+            Some(mut existing) => {
+                existing.name = incoming.name;
+                existing.url = incoming.url;
+                existing.headers = incoming.headers;
+                existing.body = incoming.body;
+                existing.path_params = incoming.path_params;
+                existing.query_params = incoming.query_params;
+                result.updated += 1;
+                existing
+            }
+            None => {
+                let mut new_request = incoming;
+                new_request.collection_id = Some(folder);
+                result.created += 1;
+                new_request
+            }
+        };
+
+        state
+            .db
+            .save_request(&request)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn import_openapi_filtered(
+    content: String,
+    operation_ids: Vec<String>,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Collection, String> {
+    let mut spec: crate::openapi::OpenApiSpec =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid OpenAPI spec: {}", e))?;
+
+    crate::openapi::filter_operations(&mut spec, &operation_ids, &tags);
+    if let Some(environment) = crate::openapi::server_variables_environment(&spec) {
+        state
+            .db
+            .create_environment(&environment)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let (collections, requests) = crate::openapi::convert_openapi(spec);
+
+    // Save collection and requests
+    for collection in &collections {
+        state
+            .db
+            .create_collection(collection)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    for request in &requests {
+        state
+            .db
+            .save_request(request)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(collections.into_iter().next().unwrap())
+}
+
+#[tauri::command]
+pub async fn import_postman_collection(
+    json_data: String,
+    state: State<'_, AppState>,
+) -> Result<Collection, String> {
+    // Try to parse as Value first to detect type
+    let v: serde_json::Value = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+
+    if v.get("openapi").is_some() || v.get("swagger").is_some() {
+        // Handle OpenAPI
+        let spec: crate::openapi::OpenApiSpec = serde_json::from_value(v)
+            .map_err(|e| format!("Invalid OpenAPI spec: {}", e))?;
+        
+        if let Some(environment) = crate::openapi::server_variables_environment(&spec) {
+            state
+                .db
+                .create_environment(&environment)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let (collections, requests) = crate::openapi::convert_openapi(spec);
+        
+        // Save collection and requests
+        for collection in &collections {
+            state.db.create_collection(collection).await.map_err(|e| e.to_string())?;
+        }
+        for request in requests {
+            state.db.save_request(&request).await.map_err(|e| e.to_string())?;
+        }
+        
+        Ok(collections.into_iter().next().unwrap())
+    } else {
+        // Parse JSON string directly
+        let postman_collection: crate::postman::PostmanCollection = serde_json::from_str(&json_data)
+            .map_err(|e| {
+                eprintln!("❌ Postman import error: {}", e);
+                // Log the problematic area of JSON
+                if let Some(line_col) = e.to_string().split("at line ").nth(1) {
+                    if let Some(line_str) = line_col.split(" column").next() {
+                        if let Ok(line_num) = line_str.parse::<usize>() {
+                            let lines: Vec<&str> = json_data.lines().collect();
+                            if line_num > 0 && line_num <= lines.len() {
+                                eprintln!("📍 Context around line {}:", line_num);
+                                let start = line_num.saturating_sub(3);
+                                let end = (line_num + 2).min(lines.len());
+                                for i in start..end {
+                                    if i == line_num - 1 {
+                                        eprintln!(">>> {}: {}", i + 1, lines[i]);
+                                    } else {
+                                        eprintln!("    {}: {}", i + 1, lines[i]);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                format!("Invalid Postman collection format: {}", e)
+            })?;
+
+        // Convert to Geni format
+        let (mut collections, requests) =
+            crate::postman::convert_postman_collection(postman_collection);
+
+        // Mark the root collection as imported
+        if let Some(root_collection) = collections.first_mut() {
+            root_collection.name = format!("{} (Imported from Postman)", root_collection.name);
+        }
+
+        // Save all collections (root and sub-collections)
+        for collection in &collections {
+            state
+                .db
+                .create_collection(collection)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        // Import all requests
+        for request in &requests {
+            state
+                .db
+                .save_request(request)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        // Return the root collection
+        Ok(collections.into_iter().next().unwrap())
+    }
+}
+
+// Imports a single folder of a Postman collection as its own collection
+#[tauri::command]
+pub async fn import_postman_folder(
+    json_data: String,
+    folder_path: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Collection, String> {
+    let postman_collection: crate::postman::PostmanCollection = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Invalid Postman collection format: {}", e))?;
+    let (mut collections, requests) =
+        crate::postman::convert_postman_folder(&postman_collection, &folder_path)?;
+
+    if let Some(root_collection) = collections.first_mut() {
+        root_collection.name = format!("{} (Imported from Postman)", root_collection.name);
+    }
+
+    for collection in &collections {
+        state
+            .db
+            .create_collection(collection)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    for request in &requests {
+        state
+            .db
+            .save_request(request)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(collections.into_iter().next().unwrap())
+}
+
+// Creates a collection from newline-separated URLs, one request per line
+#[tauri::command]
+pub async fn import_url_list(
+    urls: String,
+    collection_name: String,
+    state: State<'_, AppState>,
+) -> Result<Collection, String> {
+    let collection_name = collection_name.trim();
+    if collection_name.is_empty() {
+        return Err("Collection name cannot be empty".to_string());
+    }
+    let (collection, requests) = crate::url_list::convert_url_list(&urls, collection_name);
+    if requests.is_empty() {
+        return Err("No URLs found".to_string());
+    }
+
+    state
+        .db
+        .create_collection(&collection)
+        .await
+        .map_err(|e| e.to_string())?;
+    for request in &requests {
+        state
+            .db
+            .save_request(request)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(collection)
+}
+
+// Request sharing commands
+#[tauri::command]
+pub async fn encode_request_link(
+    request_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let uuid = Uuid::parse_str(&request_id).map_err(|e| e.to_string())?;
+    let request = state
+        .db
+        .get_request(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Request not found")?;
+
+    crate::share::encode_request_link(&request).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn decode_request_link(link: String) -> Result<HttpRequest, String> {
+    crate::share::decode_request_link(&link).map_err(|e| e.to_string())
+}
+
+// Cloud Sync Commands
+
+// Initialize or reconfigure sync client
+#[tauri::command]
+pub async fn initialize_sync(
+    provider: String,
+    api_server_url: Option<String>,
+    supabase_url: Option<String>,
+    supabase_api_key: Option<String>,
+    supabase_db_uri: Option<String>,
+    google_client_id: Option<String>,
+    google_client_secret: Option<String>,
+    google_redirect_uri: Option<String>,
+    supabase_storage_bucket: Option<String>,
+    supabase_accept_invalid_certs: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    use crate::sync::{ProviderConfig, SyncProvider};
+
+    let sync_provider = SyncProvider::from_str(&provider)
+        .ok_or_else(|| format!("Invalid provider: {}", provider))?;
+
+    let config = ProviderConfig {
+        provider: sync_provider.clone(),
+        api_server_url,
+        supabase_url,
+        supabase_api_key,
+        supabase_db_uri,
+        google_client_id,
+        google_client_secret,
+        google_redirect_uri,
+        supabase_storage_bucket,
+        supabase_accept_invalid_certs: supabase_accept_invalid_certs.unwrap_or(false),
+    };
+
+    let new_client = SyncClient::new(config.clone()).map_err(|e| e.to_string())?;
+
+    // Auto-create schema for Supabase if needed
+    if sync_provider == SyncProvider::Supabase {
+        new_client
+            .ensure_schema()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut client = state.sync_client.lock().await;
+    *client = new_client;
+
+    // Save config to database
+    let config_json = config.to_json().map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_sync_config(sync_provider.as_str(), &config_json)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state
+        .db
+        .set_last_sync_provider(sync_provider.as_str())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// Load saved sync configuration
+#[tauri::command]
+pub async fn load_saved_sync_config(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    use crate::sync::ProviderConfig;
+
+    // Get last used provider
+    let provider = state
+        .db
+        .get_last_sync_provider()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(provider_name) = provider {
+        // Get config for that provider
+        let config_json = state
+            .db
+            .get_sync_config(&provider_name)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if let Some(json) = config_json {
+            // Initialize sync client with saved config
+            let config = ProviderConfig::from_json(&json).map_err(|e| e.to_string())?;
+
+            let new_client = SyncClient::new(config).map_err(|e| e.to_string())?;
+
+            let mut client = state.sync_client.lock().await;
+            *client = new_client;
+
+            // Return the config JSON for the frontend
+            return Ok(Some(json));
+        }
+    }
+
+    Ok(None)
+}
+
+// API Server-specific auth commands
+#[tauri::command]
+pub async fn api_server_sign_up(
+    email: String,
+    password: String,
+    name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TokenResponse, String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .api_server_sign_up(&email, &password, name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn api_server_sign_in(
+    email: String,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<TokenResponse, String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .api_server_sign_in(&email, &password)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Supabase-specific auth commands
+#[tauri::command]
+pub async fn supabase_sign_up(
+    email: String,
+    password: String,
+    name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TokenResponse, String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .supabase_sign_up(&email, &password, name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn supabase_sign_in(
+    email: String,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<TokenResponse, String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .supabase_sign_in(&email, &password)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Google Drive-specific auth commands
+#[tauri::command]
+pub async fn google_drive_get_auth_url(
+    state: State<'_, AppState>,
+) -> Result<(String, String), String> {
+    let client = state.sync_client.lock().await;
+    client
+        .google_drive_generate_auth_url()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn google_drive_exchange_code(
+    code: String,
+    state: State<'_, AppState>,
+) -> Result<TokenResponse, String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .google_drive_exchange_code(&code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn google_drive_refresh_token(state: State<'_, AppState>) -> Result<(), String> {
+    let mut client = state.sync_client.lock().await;
+    client
+        .google_drive_refresh_token()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Common auth commands
+#[tauri::command]
+pub async fn logout(state: State<'_, AppState>) -> Result<(), String> {
+    use crate::sync::{ProviderConfig, SyncClient};
+
+    // Sign out from current provider
+    let mut client = state.sync_client.lock().await;
+    client.sign_out();
+    drop(client); // Release the lock
+
+    // Clear all sync configuration from database
+    state
+        .db
+        .clear_sync_config()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Reset sync client to default (no provider)
+    let default_config = ProviderConfig {
+        api_server_url: None,
+        ..ProviderConfig::default()
+    };
+
+    let new_client = SyncClient::new(default_config).map_err(|e| e.to_string())?;
+
+    let mut client = state.sync_client.lock().await;
+    *client = new_client;
+
+    Ok(())
+}
+
+// Supabase schema management
+#[tauri::command]
+pub async fn supabase_create_schema(state: State<'_, AppState>) -> Result<String, String> {
+    let client = state.sync_client.lock().await;
+    match client.ensure_schema().await {
+        Ok(_) => Ok("Schema created successfully or already exists".to_string()),
+        Err(e) => Err(format!("Failed to create schema: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub async fn is_authenticated(state: State<'_, AppState>) -> Result<bool, String> {
+    let client = state.sync_client.lock().await;
+    Ok(client.is_authenticated())
+}
+
+#[tauri::command]
+pub async fn get_provider_capabilities(
+    state: State<'_, AppState>,
+) -> Result<crate::sync::ProviderCapabilities, String> {
+    Ok(state.sync_client.lock().await.capabilities())
+}
+
+#[tauri::command]
+pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<User>, String> {
+    let client = state.sync_client.lock().await;
+    Ok(client.get_current_user().await)
+}
+
+// Classifies a sync failure and tags it with the active provider
+async fn provider_sync_error(state: &AppState, error: anyhow::Error) -> ProviderSyncError {
+    let provider = state.sync_client.lock().await.provider_type();
+    ProviderSyncError::from_anyhow(provider, &error)
+}
+
+#[tauri::command]
+pub async fn sync_push(state: State<'_, AppState>) -> Result<(), ProviderSyncError> {
+    run_exclusive_sync(state.inner(), push_unsynced(state.inner())).await
+}
+
+async fn push_unsynced(state: &AppState) -> Result<()> {
+    // Get unsynced items
+    let mut collections = state.db.get_unsynced_collections().await?;
+
+    let mut requests = state.db.get_unsynced_requests().await?;
+
+    let environments = state.db.get_unsynced_environments().await?;
+
+    if collections.is_empty() && requests.is_empty() && environments.is_empty() {
+        return Ok(()); // Nothing to sync
+    }
+
+    let mut client = state.sync_client.lock().await;
+    if !client.capabilities().supports_individual_ops {
+        return push_workspace_blob(state, &mut client).await;
+    }
+    for collection in &mut collections {
+        strip_oauth2_token(collection.auth.as_mut());
+    }
+    for request in &mut requests {
+        strip_oauth2_token(request.auth.as_mut());
+    }
+
+    // Push to cloud
+    for collection in collections {
+        if let Some(cloud_id) = &collection.cloud_id {
+            // Update existing
+            client.push_collection(&collection).await?;
+
+            state
+                .db
+                .mark_collection_synced(collection.id, cloud_id.clone(), collection.version)
+                .await?;
+        } else {
+            // Create new
+            let cloud_id = client.push_collection(&collection).await?;
+
+            state
+                .db
+                .mark_collection_synced(collection.id, cloud_id, collection.version)
+                .await?;
+        }
+    }
+
+    // Dependencies are pushed first, so their cloud ids are known to the requests using them
+    let mut cloud_ids = request_cloud_ids(&state.db.get_all_requests().await?);
+    while !requests.is_empty() {
+        let next = requests
+            .iter()
+            .position(|request| {
+                request.depends_on.iter().all(|dependency| {
+                    cloud_ids.contains_key(dependency)
+                        || !requests.iter().any(|other| other.id == Some(*dependency))
+                })
+            })
+            .unwrap_or(0); // a dependency cycle, which is reported when run
+        let mut request = requests.remove(next);
+        set_dependency_cloud_ids(&mut request, &cloud_ids);
+
+        if let Some(ref cloud_id) = request.cloud_id {
+            client.push_request(&request).await?;
+
+            state
+                .db
+                .mark_request_synced(request.id.unwrap(), cloud_id.clone(), request.version)
+                .await?;
+        } else {
+            let cloud_id = client.push_request(&request).await?;
+            cloud_ids.insert(request.id.unwrap(), cloud_id.clone());
+
+            state
+                .db
+                .mark_request_synced(request.id.unwrap(), cloud_id, request.version)
+                .await?;
+        }
+    }
+
+    for environment in environments {
+        if let Some(ref cloud_id) = environment.cloud_id {
+            client.push_environment(&environment).await?;
+
+            state
+                .db
+                .mark_environment_synced(environment.id, cloud_id.clone(), environment.version)
+                .await?;
+        } else {
+            let cloud_id = client.push_environment(&environment).await?;
+
+            state
+                .db
+                .mark_environment_synced(environment.id, cloud_id, environment.version)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Blob providers (Google Drive, Supabase Storage) replace a single file, so the whole
+// workspace is uploaded, not just the unsynced items. Local IDs stand in for cloud IDs, so
+// pulling the file back matches every item to its local copy.
+async fn push_workspace_blob(state: &AppState, client: &mut SyncClient) -> Result<()> {
+    let mut collections = state.db.get_collections().await?;
+    for collection in &mut collections {
+        collection
+            .cloud_id
+            .get_or_insert_with(|| collection.id.to_string());
+        strip_oauth2_token(collection.auth.as_mut());
+    }
+    let mut requests = state.db.get_all_requests().await?;
+    for request in &mut requests {
+        strip_oauth2_token(request.auth.as_mut());
+        if let Some(id) = request.id {
+            request.cloud_id.get_or_insert_with(|| id.to_string());
+        }
+    }
+    let cloud_ids = request_cloud_ids(&requests);
+    for request in &mut requests {
+        set_dependency_cloud_ids(request, &cloud_ids);
+    }
+    let mut environments = state.db.get_environments().await?;
+    for environment in &mut environments {
+        environment
+            .cloud_id
+            .get_or_insert_with(|| environment.id.to_string());
+    }
+
+    client
+        .push_sync(collections.clone(), requests.clone(), environments.clone())
+        .await?;
+
+    for collection in collections {
+        let cloud_id = collection.cloud_id.unwrap_or_default();
+        state
+            .db
+            .mark_collection_synced(collection.id, cloud_id, collection.version)
+            .await?;
+    }
+    for request in requests {
+        let (Some(id), Some(cloud_id)) = (request.id, request.cloud_id) else {
+            continue;
+        };
+        state
+            .db
+            .mark_request_synced(id, cloud_id, request.version)
+            .await?;
+    }
+    for environment in environments {
+        let cloud_id = environment.cloud_id.unwrap_or_default();
+        state
+            .db
+            .mark_environment_synced(environment.id, cloud_id, environment.version)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// Local id -> cloud id of every request that has been pushed
+fn request_cloud_ids(requests: &[HttpRequest]) -> HashMap<Uuid, String> {
+    requests
+        .iter()
+        .filter_map(|request| Some((request.id?, request.cloud_id.clone()?)))
+        .collect()
+}
+
+// Local ids differ per device, so pushed dependencies are named by cloud id
+fn set_dependency_cloud_ids(request: &mut HttpRequest, cloud_ids: &HashMap<Uuid, String>) {
+    request.depends_on_cloud_ids = request
+        .depends_on
+        .iter()
+        .filter_map(|dependency| cloud_ids.get(dependency).cloned())
+        .collect();
+}
+
+#[tauri::command]
+pub async fn sync_pull(state: State<'_, AppState>) -> Result<(), ProviderSyncError> {
+    run_exclusive_sync(state.inner(), pull_remote(state.inner())).await
+}
+
+async fn pull_remote(state: &AppState) -> Result<()> {
+    // Pull from cloud
+    let mut client = state.sync_client.lock().await;
+    let pull_response = client.pull_sync().await?;
+
+    drop(client); // Release lock before database operations
+
+    // Merge collections
+    for collection in pull_response.collections {
+        state.db.merge_collection(collection).await?;
+    }
+
+    // Merge requests
+    for request in &pull_response.requests {
+        state.db.merge_request(request.clone()).await?;
+    }
+    state
+        .db
+        .relink_request_dependencies(&pull_response.requests)
+        .await?;
+
+    // Merge environments
+    for environment in pull_response.environments {
+        state.db.merge_environment(environment).await?;
+    }
+
+    Ok(())
+}
+
+// Holds the `sync_running` flag for one sync. The flag is cleared on drop, so a sync task
+// aborted mid-way (e.g. by stop_auto_sync) can't leave it set.
+struct SyncRunning<'a>(&'a AtomicBool);
+
+impl<'a> SyncRunning<'a> {
+    // None when another sync already holds the flag
+    fn acquire(flag: &'a AtomicBool) -> Option<Self> {
+        (!flag.swap(true, Ordering::SeqCst)).then_some(Self(flag))
+    }
+}
+
+impl Drop for SyncRunning<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+// Runs a manual sync while holding the `sync_running` flag, so it can't overlap an
+// auto-sync cycle or a reset. The sync future is lazy and only starts once the flag is held.
+async fn run_exclusive_sync(
+    state: &AppState,
+    sync: impl std::future::Future<Output = Result<()>>,
+) -> Result<(), ProviderSyncError> {
+    let Some(_running) = SyncRunning::acquire(&state.sync_running) else {
+        let provider = state.sync_client.lock().await.provider_type();
+        let error = SyncError::Provider("A sync is already in progress".to_string());
+        return Err(ProviderSyncError::new(provider, error));
+    };
+
+    match sync.await {
+        Ok(()) => Ok(()),
+        Err(e) => Err(provider_sync_error(state, e).await),
+    }
+}
+
+#[tauri::command]
+pub async fn sync_full(state: State<'_, AppState>) -> Result<(), ProviderSyncError> {
+    run_exclusive_sync(state.inner(), full_sync(state.inner())).await
+}
+
+// Forces a full re-push, e.g. after the cloud data was wiped or the account changed.
+// `confirm` must be set, since every record loses its link to the cloud.
+#[tauri::command]
+pub async fn reset_sync_state(confirm: bool, state: State<'_, AppState>) -> Result<usize, String> {
+    if !confirm {
+        return Err("Resetting sync state must be confirmed".to_string());
+    }
+    let Some(_running) = SyncRunning::acquire(&state.sync_running) else {
+        return Err("A sync is already in progress".to_string());
+    };
+
+    state.db.reset_sync_state().await.map_err(|e| e.to_string())
+}
+
+async fn full_sync(state: &AppState) -> Result<()> {
+    // First push unsynced items
+    push_unsynced(state).await?;
+
+    // Then pull updates
+    pull_remote(state).await?;
+
+    Ok(())
+}
+
+// One auto-sync cycle; skipped when signed out or when another sync is running
+async fn auto_sync_cycle(state: &AppState) -> AutoSyncEvent {
+    if !state.sync_client.lock().await.is_authenticated() {
+        return AutoSyncEvent::Skipped {
+            reason: "Not authenticated".to_string(),
+        };
+    }
+    let Some(_running) = SyncRunning::acquire(&state.sync_running) else {
+        return AutoSyncEvent::Skipped {
+            reason: "A sync is already in progress".to_string(),
+        };
+    };
+
+    let result = full_sync(state).await;
+
+    match result {
+        Ok(()) => AutoSyncEvent::Synced,
+        Err(error) => AutoSyncEvent::Failed {
+            error: error.to_string(),
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn start_auto_sync(
+    interval_secs: u64,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if interval_secs == 0 {
+        return Err("Auto-sync interval must be at least 1 second".to_string());
+    }
+
+    let handle = spawn_auto_sync(Duration::from_secs(interval_secs), move || {
+        let app = app.clone();
+        async move {
+            let state = app.state::<AppState>();
+            let event = auto_sync_cycle(state.inner()).await;
+            let _ = app.emit(AUTO_SYNC_EVENT, event);
+        }
+    });
+
+    // Replace any previously running task
+    if let Some(previous) = state.auto_sync_task.lock().await.replace(handle) {
+        previous.abort();
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_auto_sync(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(handle) = state.auto_sync_task.lock().await.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_sync_status(state: State<'_, AppState>) -> Result<SyncStatus, String> {
+    let unsynced_collections = state
+        .db
+        .get_unsynced_collections()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let unsynced_requests = state
+        .db
+        .get_unsynced_requests()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let unsynced_environments = state
+        .db
+        .get_unsynced_environments()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let client = state.sync_client.lock().await;
+    let is_authenticated = client.is_authenticated();
+
+    Ok(SyncStatus {
+        is_authenticated,
+        unsynced_collections_count: unsynced_collections.len(),
+        unsynced_requests_count: unsynced_requests.len(),
+        unsynced_environments_count: unsynced_environments.len(),
+        last_sync: None, // You can store this in a separate table if needed
+    })
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SyncStatus {
+    pub is_authenticated: bool,
+    pub unsynced_collections_count: usize,
+    pub unsynced_requests_count: usize,
+    pub unsynced_environments_count: usize,
+    pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[tauri::command]
+pub async fn extract_path_params(url: String) -> Result<Vec<String>, String> {
+    Ok(crate::http::extract_path_parameters(&url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock_server::{response, MockServer};
+    use crate::sync::ProviderConfig;
+
+    async fn test_state() -> AppState {
+        AppState {
+            db: Database::new_embedded().await.unwrap(),
+            http_client: HttpClient::new(),
+            sync_client: Arc::new(Mutex::new(
+                SyncClient::new(ProviderConfig::default()).unwrap(),
+            )),
+            workspace_dir: std::env::temp_dir(),
+            auto_sync_task: Mutex::new(None),
+            sync_running: AtomicBool::new(false),
+            request_log_path: RwLock::new(None),
+            oauth2_pending: Mutex::new(HashMap::new()),
+            in_flight_requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_payload(url: &str) -> SendRequestPayload {
+        SendRequestPayload {
+            method: HttpMethod::GET,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+            path_params: HashMap::new(),
+            query_params: Vec::new(),
+            timeout: None,
+            timeout_preset: None,
+            connect_timeout: None,
+            body_file: None,
+            body_file_raw: false,
+            collection_id: None,
+            request_id: None,
+            capture_informational: false,
+            force_no_body: false,
+            success_criteria: None,
+            method_override: false,
+            apply_base_url_override: true,
+            resolve_trace: None,
+            user_agent: None,
+            retry_count: None,
+            ephemeral: false,
+            digest_auth: None,
+            aws_sigv4: None,
+            auth_headers: Vec::new(),
+            follow_redirects: None,
+            max_redirects: None,
+            insecure: None,
+            client_certificate: None,
+            cookie_jar: None,
+            retry_on: None,
+            retry_backoff_ms: None,
+            download_to: None,
+            decompress: None,
+            environment_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collection_auth_resolves_environment_variables() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let mut collection = Collection::new("API".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::Bearer,
+            basic: None,
+            bearer: Some(BearerAuth {
+                token: "{{token}}".to_string(),
+            }),
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+
+        let mut variables = HashMap::new();
+        variables.insert("token".to_string(), "secret-123".to_string());
+        let environment = Environment::new("Dev".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        let mut payload = get_payload(&server.url);
+        payload.collection_id = Some(collection.id.to_string());
+        execute_request(&state, payload).await.unwrap();
+
+        let sent = server.requests()[0].to_lowercase();
+        assert!(sent.contains("authorization: bearer secret-123"));
+    }
+
+    #[tokio::test]
+    async fn test_folder_inherits_auth_from_parent_collection() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("200 OK", &[], "ok"),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let mut root = Collection::new("API".to_string(), None);
+        root.auth = Some(AuthConfig {
+            auth_type: AuthType::Bearer,
+            basic: None,
+            bearer: Some(BearerAuth {
+                token: "root-token".to_string(),
+            }),
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        let folder = Collection::new_with_parent("Users".to_string(), None, Some(root.id));
+        state.db.create_collection(&root).await.unwrap();
+        state.db.create_collection(&folder).await.unwrap();
+
+        let mut payload = get_payload(&server.url);
+        payload.collection_id = Some(folder.id.to_string());
+        execute_request(&state, payload.clone()).await.unwrap();
+
+        // An explicit Authorization header still wins
+        payload.headers = vec![("Authorization".to_string(), "Bearer mine".to_string())];
+        execute_request(&state, payload).await.unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0]
+            .to_lowercase()
+            .contains("authorization: bearer root-token"));
+        assert!(requests[1].contains("Bearer mine"));
+        assert!(!requests[1].contains("root-token"));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth_in_header_and_query() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("200 OK", &[], "ok"),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let api_key_auth = |placement| AuthConfig {
+            auth_type: AuthType::ApiKey,
+            basic: None,
+            bearer: None,
+            oauth2: None,
+            api_key: Some(ApiKeyAuth {
+                name: "appid".to_string(), // not a credential-like name
+                value: "key-123".to_string(),
+                placement,
+            }),
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        };
+        let mut header_collection = Collection::new("Header".to_string(), None);
+        header_collection.auth = Some(api_key_auth(ApiKeyPlacement::Header));
+        let mut query_collection = Collection::new("Query".to_string(), None);
+        query_collection.auth = Some(api_key_auth(ApiKeyPlacement::Query));
+        for collection in [&header_collection, &query_collection] {
+            state.db.create_collection(collection).await.unwrap();
+        }
+
+        let mut payload = get_payload(&format!("{}/items?page=2", server.url));
+        payload.collection_id = Some(header_collection.id.to_string());
+        execute_request(&state, payload.clone()).await.unwrap();
+        payload.collection_id = Some(query_collection.id.to_string());
+        execute_request(&state, payload).await.unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0].to_lowercase().contains("appid: key-123"));
+        assert!(requests[1].starts_with("GET /items?page=2&appid=key-123 "));
+        assert!(!requests[1].to_lowercase().contains("appid: "));
+
+        // The key is masked by its configured name in history
+        let history = state.db.get_history(Some(1)).await.unwrap();
+        assert_eq!(
+            history[0].resolved_url,
+            format!("{}/items?page=2&appid=REDACTED", server.url)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_key_header_is_dropped_on_cross_origin_redirect() {
+        let state = test_state().await;
+        let target = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let location = format!("{}/landing", target.url);
+        let origin =
+            MockServer::start(vec![response("302 Found", &[("Location", &location)], "")]).await;
+
+        let mut collection = Collection::new("Keyed".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::ApiKey,
+            basic: None,
+            bearer: None,
+            oauth2: None,
+            api_key: Some(ApiKeyAuth {
+                name: "appid".to_string(),
+                value: "key-123".to_string(),
+                placement: ApiKeyPlacement::Header,
+            }),
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+
+        let mut payload = get_payload(&format!("{}/start", origin.url));
+        payload.collection_id = Some(collection.id.to_string());
+        let response = execute_request(&state, payload).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert!(origin.requests()[0]
+            .to_lowercase()
+            .contains("appid: key-123"));
+        assert!(!target.requests()[0].to_lowercase().contains("appid"));
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_token_is_fetched_once_and_cached() {
+        let state = test_state().await;
+        let json = [("Content-Type", "application/json")];
+        let server = MockServer::start(vec![
+            response(
+                "200 OK",
+                &json,
+                r#"{"access_token":"cc-token","token_type":"Bearer","expires_in":3600}"#,
+            ),
+            response("200 OK", &[], "ok"),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let mut collection = Collection::new("Internal".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::OAuth2ClientCredentials,
+            basic: None,
+            bearer: None,
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: Some(OAuth2ClientCredentials {
+                token_url: format!("{}/token", server.url),
+                client_id: "service".to_string(),
+                client_secret: "s3cret".to_string(),
+                scope: Some("read".to_string()),
+            }),
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+
+        let mut payload = get_payload(&format!("{}/items", server.url));
+        payload.collection_id = Some(collection.id.to_string());
+        execute_request(&state, payload.clone()).await.unwrap();
+        execute_request(&state, payload).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("POST /token "));
+        assert!(requests[0].contains("grant_type=client_credentials&scope=read"));
+        for request in &requests[1..] {
+            assert!(request.starts_with("GET /items "));
+            assert!(request
+                .to_lowercase()
+                .contains("authorization: bearer cc-token"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_bearer_preset_to_request() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let preset = AuthPreset {
+            id: Uuid::new_v4(),
+            name: "Staging token".to_string(),
+            auth: AuthConfig {
+                auth_type: AuthType::Bearer,
+                basic: None,
+                bearer: Some(BearerAuth {
+                    token: "{{token}}".to_string(),
+                }),
+                oauth2: None,
+                api_key: None,
+                oauth2_client_credentials: None,
+                digest: None,
+                aws_sigv4: None,
+            },
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        state.db.save_auth_preset(&preset).await.unwrap();
+
+        let request = HttpRequest::new("Me".to_string(), HttpMethod::GET, server.url.clone());
+        let request = state.db.save_request(&request).await.unwrap();
+        let request_id = request.id.unwrap();
+
+        apply_preset_to(&state, &request_id.to_string(), &preset.id.to_string())
+            .await
+            .unwrap();
+
+        let stored = state.db.get_request(request_id).await.unwrap().unwrap();
+        let auth = stored.auth.clone().unwrap();
+        assert!(matches!(auth.auth_type, AuthType::Bearer));
+        assert_eq!(auth.bearer.unwrap().token, "{{token}}");
+
+        // The variable resolves against whichever environment is active at send time
+        let variables = HashMap::from([("token".to_string(), "abc".to_string())]);
+        let environment = Environment::new("Staging".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        execute_request(&state, payload_from_request(&stored))
+            .await
+            .unwrap();
+        let sent = server.requests()[0].to_lowercase();
+        assert!(sent.contains("authorization: bearer abc"));
+    }
+
+    #[tokio::test]
+    async fn test_fuzz_query_param_reports_each_payload() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("200 OK", &[], "fine"),
+            response("500 Internal Server Error", &[], "boom"),
+        ])
+        .await;
+
+        let mut request = HttpRequest::new(
+            "Search".to_string(),
+            HttpMethod::GET,
+            format!("{}/search", server.url),
+        );
+        request.query_params = vec![("q".to_string(), "books".to_string(), true)];
+        let request = state.db.save_request(&request).await.unwrap();
+
+        let results = run_fuzz(
+            &state,
+            &request.id.unwrap().to_string(),
+            &FuzzTarget::QueryParam("q".to_string()),
+            &["abc".to_string(), "' OR 1=1".to_string()],
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].status, Some(200));
+        assert_eq!(results[1].status, Some(500));
+        assert_eq!(results[1].body_snippet, "boom");
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /search?q=abc "));
+        assert!(requests[1].starts_with("GET /search?q=%27+OR+1%3D1 "));
+        assert!(state.db.get_history(None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_history_records_templated_and_resolved_url() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let variables = HashMap::from([
+            ("base_url".to_string(), server.url.clone()),
+            ("api_key".to_string(), "k-123".to_string()),
+        ]);
+        let environment = Environment::new("Local".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        let templated = "{{base_url}}/users?api_key={{api_key}}&page=2";
+        execute_request(&state, get_payload(templated))
+            .await
+            .unwrap();
+
+        let history = state.db.get_history(Some(1)).await.unwrap();
+        assert_eq!(history[0].request.url, templated);
+        assert_eq!(
+            history[0].resolved_url,
+            format!("{}/users?api_key=REDACTED&page=2", server.url)
+        );
+        assert_eq!(history[0].environment_name.as_deref(), Some("Local"));
+    }
+
+    #[tokio::test]
+    async fn test_curl_replay_uses_the_sent_headers_and_body() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let variables = HashMap::from([
+            ("tenant".to_string(), "acme".to_string()),
+            ("api_token".to_string(), "t-123".to_string()),
+        ]);
+        let environment = Environment::new("Local".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        let mut payload = get_payload(&format!("{}/users", server.url));
+        payload.method = HttpMethod::POST;
+        payload.headers = vec![
+            ("X-Tenant".to_string(), "{{tenant}}".to_string()),
+            ("X-Trace".to_string(), "{{api_token}}-1".to_string()),
+        ];
+        payload.body = Some(RequestBody::Json(
+            serde_json::json!({"tenant": "{{tenant}}", "token": "{{api_token}}"}),
+        ));
+        execute_request(&state, payload).await.unwrap();
+        assert!(server.requests()[0].contains("t-123"));
+
+        let history = state.db.get_history(Some(1)).await.unwrap();
+        let curl = render_history_entry(&history[0], "curl-replay").unwrap();
+        assert!(curl.contains("X-Tenant: acme"));
+        assert!(curl.contains("X-Trace: REDACTED-1"));
+        assert!(curl.contains(r#""tenant":"acme""#));
+        assert!(!curl.contains("t-123"));
+    }
+
+    #[tokio::test]
+    async fn test_base_url_override_swaps_host_and_keeps_path() {
+        let state = test_state().await;
+        let hardcoded = MockServer::start(vec![response("200 OK", &[], "prod")]).await;
+        let staging = MockServer::start(vec![response("200 OK", &[], "staging")]).await;
+
+        let environment = Environment::new("Staging".to_string(), HashMap::new());
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_environment_base_url_override(environment.id, Some(staging.url.clone()))
+            .await
+            .unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        let url = format!("{}/users/7?expand=roles", hardcoded.url);
+        let response = execute_request(&state, get_payload(&url)).await.unwrap();
+        assert_eq!(response.body, "staging");
+        assert!(staging.requests()[0].starts_with("GET /users/7?expand=roles "));
+
+        let mut payload = get_payload(&url);
+        payload.apply_base_url_override = false;
+        let response = execute_request(&state, payload).await.unwrap();
+        assert_eq!(response.body, "prod");
+    }
+
+    fn rendered_entry() -> RequestHistory {
+        let request = HttpRequest::new("Get".to_string(), HttpMethod::GET, "https://".into());
+        let response = HttpResponse {
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: ResponseHeaders(vec![
+                ("x-request-id".to_string(), "abc".to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ]),
+            body: r#"{"id":1,"tags":["a"]}"#.to_string(),
+            response_time: 1,
+            size: 21,
+        };
+        RequestHistory::new(request, Some(response))
+    }
+
+    #[test]
+    fn test_render_response_headers_only() {
+        let rendered = render_history_entry(&rendered_entry(), "headers-only").unwrap();
+        assert_eq!(
+            rendered,
+            "200 OK\nx-request-id: abc\ncontent-type: application/json"
+        );
+    }
+
+    #[test]
+    fn test_render_response_pretty_json() {
+        let rendered = render_history_entry(&rendered_entry(), "pretty-json").unwrap();
+        assert_eq!(
+            rendered,
+            "{\n  \"id\": 1,\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+        assert!(render_history_entry(&rendered_entry(), "yaml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_history_body_windows_reconstruct_the_body() {
+        let state = test_state().await;
+        let body = "héllo wörld, ünïcode bödy";
+        let request = HttpRequest::new("Get".to_string(), HttpMethod::GET, "https://".into());
+        let response = HttpResponse {
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: ResponseHeaders::default(),
+            body: body.to_string(),
+            response_time: 1,
+            size: body.len(),
+        };
+        let history = RequestHistory::new(request, Some(response));
+        state.db.save_to_history(&history).await.unwrap();
+
+        let full = history_body(&state, &history.id.to_string()).await.unwrap();
+        assert_eq!(full.len(), body.len());
+
+        // Byte 2 falls inside 'é', so the first window stops before it
+        let first = body_window(&full, 0, 2);
+        assert_eq!(first.content, "h");
+        let second = body_window(&full, first.offset + first.length, full.len());
+        assert_eq!(format!("{}{}", first.content, second.content), body);
+
+        // A window shorter than the character at its start returns the whole character
+        let emoji = "🦀x";
+        let window = body_window(emoji, 0, 1);
+        assert_eq!((window.content.as_str(), window.length), ("🦀", 4));
+        let next = body_window(emoji, window.offset + window.length, 1);
+        assert_eq!(next.content, "x");
+        assert_eq!(body_window(emoji, emoji.len(), 1).length, 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_and_save_stores_the_body_that_was_sent() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("201 Created", &[], "{}")]).await;
+
+        let payload = SaveRequestPayload {
+            id: None,
+            name: "Create user".to_string(),
+            method: HttpMethod::POST,
+            url: format!("{}/users", server.url),
+            headers: Vec::new(),
+            body: Some(RequestBody::Json(serde_json::json!({"name": "Ada"}))),
+            path_params: HashMap::new(),
+            query_params: Vec::new(),
+            collection_id: None,
+            success_criteria: None,
+            draft_key: None,
+        };
+        let result = save_and_send(&state, payload).await.unwrap();
+
+        assert_eq!(result.response.status, 201);
+        assert_eq!(result.request.last_status, Some(201));
+        let stored = state.db.get_request(result.request.id.unwrap()).await;
+        let Some(RequestBody::Json(body)) = stored.unwrap().unwrap().body else {
+            panic!("expected a JSON body");
+        };
+        assert!(server.requests()[0].ends_with(&body.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_draft_is_cleared_when_request_is_saved() {
+        let state = test_state().await;
+        let request = HttpRequest::new(
+            "Users".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/users".to_string(),
+        );
+        let request = state.db.save_request(&request).await.unwrap();
+        let request_id = request.id.unwrap().to_string();
+
+        let draft = SaveRequestPayload {
+            id: Some(request_id.clone()),
+            name: "Users".to_string(),
+            method: HttpMethod::GET,
+            url: "https://api.example.com/users?page=2".to_string(),
+            headers: Vec::new(),
+            body: None,
+            path_params: HashMap::new(),
+            query_params: Vec::new(),
+            collection_id: None,
+            success_criteria: None,
+            draft_key: None,
+        };
+        state.db.save_draft(&request_id, &draft).await.unwrap();
+
+        let stored = state.db.get_draft(&request_id).await.unwrap().unwrap();
+        assert_eq!(stored.url, "https://api.example.com/users?page=2");
+
+        let saved = save_request_payload(&state, stored).await.unwrap();
+        assert_eq!(saved.url, "https://api.example.com/users?page=2");
+        assert!(state.db.get_draft(&request_id).await.unwrap().is_none());
+
+        // Unsaved tabs keep separate drafts; saving one leaves the other
+        let unsaved = |tab: &str| SaveRequestPayload {
+            id: None,
+            name: "Untitled".to_string(),
+            method: HttpMethod::GET,
+            url: format!("https://api.example.com/{}", tab),
+            headers: Vec::new(),
+            body: None,
+            path_params: HashMap::new(),
+            query_params: Vec::new(),
+            collection_id: None,
+            success_criteria: None,
+            draft_key: Some(tab.to_string()),
+        };
+        for tab in ["tab-1", "tab-2"] {
+            state.db.save_draft(tab, &unsaved(tab)).await.unwrap();
+        }
+        save_request_payload(&state, unsaved("tab-1"))
+            .await
+            .unwrap();
+        assert!(state.db.get_draft("tab-1").await.unwrap().is_none());
+        let other = state.db.get_draft("tab-2").await.unwrap().unwrap();
+        assert_eq!(other.url, "https://api.example.com/tab-2");
+
+        // Deleting a request drops its draft
+        state.db.save_draft(&request_id, &draft).await.unwrap();
+        state.db.delete_request(request.id.unwrap()).await.unwrap();
+        assert!(state.db.get_draft(&request_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_references_previous_response() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("201 Created", &[], r#"{"data":{"id":42}}"#),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let create = HttpRequest::new(
+            "Create user".to_string(),
+            HttpMethod::POST,
+            format!("{}/users", server.url),
+        );
+        let create = state.db.save_request(&create).await.unwrap();
+
+        execute_request(&state, payload_from_request(&create))
+            .await
+            .unwrap();
+
+        let url = format!("{}/users/{{{{response.Create user.data.id}}}}", server.url);
+        let response = execute_request(&state, get_payload(&url)).await.unwrap();
+
+        assert!(response.warnings.is_empty());
+        assert!(server.requests()[1].starts_with("GET /users/42 "));
+    }
+
+    #[tokio::test]
+    async fn test_run_request_with_deps_runs_chain_in_order() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("200 OK", &[], r#"{"token":"t-1"}"#),
+            response("201 Created", &[], r#"{"id":7}"#),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let login = HttpRequest::new(
+            "Login".to_string(),
+            HttpMethod::POST,
+            format!("{}/login", server.url),
+        );
+        let login = state.db.save_request(&login).await.unwrap();
+        let mut create = HttpRequest::new(
+            "Create".to_string(),
+            HttpMethod::POST,
+            format!("{}/items?token={{{{response.Login.token}}}}", server.url),
+        );
+        create.depends_on = vec![login.id.unwrap()];
+        let create = state.db.save_request(&create).await.unwrap();
+        let mut fetch = HttpRequest::new(
+            "Fetch".to_string(),
+            HttpMethod::GET,
+            format!("{}/items/{{{{response.Create.id}}}}", server.url),
+        );
+        fetch.depends_on = vec![create.id.unwrap()];
+        let fetch = state.db.save_request(&fetch).await.unwrap();
+
+        let response = run_with_dependencies(&state, &fetch.id.unwrap().to_string(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.body, "ok");
+        let requests = server.requests();
+        assert!(requests[0].starts_with("POST /login "));
+        assert!(requests[1].starts_with("POST /items?token=t-1 "));
+        assert!(requests[2].starts_with("GET /items/7 "));
+    }
+
+    #[tokio::test]
+    async fn test_run_request_with_deps_rejects_cycles() {
+        let state = test_state().await;
+        let a = HttpRequest::new("A".to_string(), HttpMethod::GET, "http://a".to_string());
+        let b = HttpRequest::new("B".to_string(), HttpMethod::GET, "http://b".to_string());
+        let (a_id, b_id) = (a.id.unwrap(), b.id.unwrap());
+        state.db.save_request(&a).await.unwrap();
+        state.db.save_request(&b).await.unwrap();
+        for (id, dependency) in [(a_id, b_id), (b_id, a_id)] {
+            state
+                .db
+                .set_request_dependencies(id, vec![dependency])
+                .await
+                .unwrap();
+        }
+
+        let error = run_with_dependencies(&state, &a_id.to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(error, "Dependency cycle: A -> B -> A");
+    }
+
+    #[tokio::test]
+    async fn test_run_collection_skips_requests_excluded_in_environment() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let collection = Collection::new("Cleanup".to_string(), None);
+        state.db.create_collection(&collection).await.unwrap();
+        let mut cleanup = HttpRequest::new(
+            "Delete all".to_string(),
+            HttpMethod::DELETE,
+            format!("{}/all", server.url),
+        );
+        cleanup.collection_id = Some(collection.id);
+        let cleanup = state.db.save_request(&cleanup).await.unwrap();
+        let cleanup_id = cleanup.id.unwrap();
+        state
+            .db
+            .set_request_environment_exclusions(cleanup_id, vec!["production".to_string()])
+            .await
+            .unwrap();
+
+        let production = Environment::new("production".to_string(), HashMap::new());
+        let staging = Environment::new("staging".to_string(), HashMap::new());
+        for environment in [&production, &staging] {
+            state.db.create_environment(environment).await.unwrap();
+        }
+        let collection_id = collection.id.to_string();
+
+        state
+            .db
+            .set_active_environment(Some(production.id))
+            .await
+            .unwrap();
+        let results = run_collection_requests(&state, &collection_id)
+            .await
+            .unwrap();
+        assert_eq!(results[0].outcome, RunOutcome::Skipped);
+        assert!(server.requests().is_empty());
+
+        state
+            .db
+            .set_active_environment(Some(staging.id))
+            .await
+            .unwrap();
+        let results = run_collection_requests(&state, &collection_id)
+            .await
+            .unwrap();
+        assert_eq!(results[0].outcome, RunOutcome::Passed);
+        assert_eq!(results[0].status, Some(200));
+        assert!(server.requests()[0].starts_with("DELETE /all "));
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_response_token_warns() {
+        let state = test_state().await;
+        let mut variables = HashMap::new();
+        let mut warnings = Vec::new();
+        resolve_response_tokens(
+            &state,
+            &["{{response.Login.token}}"],
+            &mut variables,
+            &mut warnings,
+        )
+        .await
+        .unwrap();
+
+        assert!(variables.is_empty());
+        assert!(warnings[0].contains("no previous response for request 'Login'"));
+    }
+
+    #[test]
+    fn test_diff_variables_reports_changed_and_missing_keys() {
+        let staging = HashMap::from([
+            ("region".to_string(), "eu-west-1".to_string()),
+            ("base_url".to_string(), "https://staging.test".to_string()),
+            ("api_token".to_string(), "staging-token".to_string()),
+        ]);
+        let prod = HashMap::from([
+            ("region".to_string(), "eu-west-1".to_string()),
+            ("base_url".to_string(), "https://example.com".to_string()),
+            ("api_token".to_string(), "prod-token".to_string()),
+            ("cdn_url".to_string(), "https://cdn.example.com".to_string()),
+        ]);
+
+        let diff = diff_variables(&staging, &prod);
+        assert!(diff.only_in_a.is_empty());
+        assert_eq!(diff.only_in_b, vec!["cdn_url"]);
+        assert_eq!(diff.differing.len(), 2);
+        assert_eq!(diff.differing[0].key, "api_token");
+        assert_eq!(diff.differing[0].value_a, None);
+        assert_eq!(diff.differing[1].key, "base_url");
+        assert_eq!(
+            diff.differing[1].value_b.as_deref(),
+            Some("https://example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_format_preference_skips_formatting() {
+        let state = test_state().await;
+        let html = "<html><body><p>hi</p></body></html>";
+        let html_type = [("Content-Type", "text/html; charset=utf-8")];
+        let json_type = [("Content-Type", "application/json")];
+        let server = MockServer::start(vec![
+            response("200 OK", &html_type, html),
+            response("200 OK", &json_type, r#"{"a":1}"#),
+        ])
+        .await;
+        let url = server.url.clone();
+        state
+            .db
+            .set_format_preference("text/html", FormatMode::Raw)
+            .await
+            .unwrap();
+
+        let response = execute_request(&state, get_payload(&url)).await.unwrap();
+        assert_eq!(response.formatted_body.as_deref(), Some(html));
+
+        // Types without a preference are still pretty-printed
+        let response = execute_request(&state, get_payload(&url)).await.unwrap();
+        assert_eq!(response.formatted_body.as_deref(), Some("{\n  \"a\": 1\n}"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_trace_reports_environment_source() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let mut variables = HashMap::new();
+        variables.insert("base_url".to_string(), server.url.clone());
+        variables.insert("api_key".to_string(), "k-123".to_string());
+        let environment = Environment::new("Local".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(environment.id))
+            .await
+            .unwrap();
+
+        let mut payload = get_payload("{{base_url}}/users/{{user_id}}");
+        payload
+            .headers
+            .push(("X-Api-Key".to_string(), "{{api_key}}".to_string()));
+        payload.resolve_trace = Some(true);
+        let response = execute_request(&state, payload).await.unwrap();
+
+        let trace = response.variable_trace.unwrap().variables;
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0].token, "base_url");
+        assert_eq!(trace[0].source, VariableSource::Environment);
+        assert_eq!(trace[0].value.as_deref(), Some(server.url.as_str()));
+        assert_eq!(trace[1].token, "user_id");
+        assert_eq!(trace[1].source, VariableSource::Unresolved);
+        assert_eq!(trace[2].value.as_deref(), Some("REDACTED"));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_preset_applies_unless_timeout_is_explicit() {
+        let state = test_state().await;
+        state
+            .db
+            .save_timeout_preset(TimeoutPreset {
+                name: "slow-report".to_string(),
+                seconds: 120,
+            })
+            .await
+            .unwrap();
+
+        let mut payload = get_payload("https://api.example.com");
+        payload.timeout_preset = Some("slow-report".to_string());
+        assert_eq!(resolve_timeout(&state, &payload).await, Ok(Some(120)));
+
+        payload.timeout_preset = Some("fast".to_string());
+        assert_eq!(resolve_timeout(&state, &payload).await, Ok(Some(5)));
+
+        payload.timeout = Some(12);
+        assert_eq!(resolve_timeout(&state, &payload).await, Ok(Some(12)));
+
+        payload.timeout = None;
+        payload.timeout_preset = Some("missing".to_string());
+        assert!(resolve_timeout(&state, &payload).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_collection_defaults_apply_unless_request_sets_its_own() {
+        let state = test_state().await;
+        let mut parent = Collection::new("API".to_string(), None);
+        parent.default_timeout = Some(60);
+        parent.default_retries = Some(2);
+        let mut child = Collection::new_with_parent("Reports".to_string(), None, Some(parent.id));
+        child.default_timeout = Some(30);
+        for collection in [&parent, &child] {
+            state.db.create_collection(collection).await.unwrap();
+        }
+
+        let mut payload = get_payload("https://api.example.com/reports");
+        payload.collection_id = Some(child.id.to_string());
+        assert_eq!(
+            resolve_timeout_and_retries(&state, &payload).await,
+            Ok((Some(30), Some(2)))
+        );
+
+        payload.timeout = Some(5);
+        payload.retry_count = Some(0);
+        assert_eq!(
+            resolve_timeout_and_retries(&state, &payload).await,
+            Ok((Some(5), Some(0)))
+        );
+    }
+
+    // A workspace holding "Users" with one request, plus an export of another "Users"
+    async fn conflicting_import(state: &AppState) -> (Collection, serde_json::Value) {
+        let existing = Collection::new("Users".to_string(), None);
+        state.db.create_collection(&existing).await.unwrap();
+        let mut old_request = HttpRequest::new(
+            "Old".to_string(),
+            HttpMethod::GET,
+            "https://old.example.com".to_string(),
+        );
+        old_request.collection_id = Some(existing.id);
+        state.db.save_request(&old_request).await.unwrap();
+
+        let exported = Collection::new("Users".to_string(), None);
+        let new_request = HttpRequest::new(
+            "New".to_string(),
+            HttpMethod::GET,
+            "https://new.example.com".to_string(),
+        );
+        let data = serde_json::json!({ "collection": exported, "requests": [new_request] });
+        (existing, data)
+    }
+
+    #[tokio::test]
+    async fn test_tampered_export_warns_on_import() {
+        let state = test_state().await;
+        let collection = Collection::new("Users".to_string(), None);
+        state.db.create_collection(&collection).await.unwrap();
+        let mut request = HttpRequest::new(
+            "List".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/users".to_string(),
+        );
+        request.collection_id = Some(collection.id);
+        state.db.save_request(&request).await.unwrap();
+
+        let mut data = export_collection_data(&state, collection.id).await.unwrap();
+        let clean = import_collection_checked(&state, &data, ImportConflict::Rename)
+            .await
+            .unwrap();
+        assert!(clean.warnings.is_empty());
+
+        data["requests"][0]["url"] = "https://evil.example.com/users".into();
+        let tampered = import_collection_checked(&state, &data, ImportConflict::Rename)
+            .await
+            .unwrap();
+        assert_eq!(tampered.warnings.len(), 1);
+        assert!(tampered.warnings[0].contains("checksum"));
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_rename_adds_suffix() {
+        let state = test_state().await;
+        let (existing, data) = conflicting_import(&state).await;
+
+        let first = import_collection_data(&state, &data, ImportConflict::Rename)
+            .await
+            .unwrap();
+        let second = import_collection_data(&state, &data, ImportConflict::Rename)
+            .await
+            .unwrap();
+
+        assert_eq!(first.name, "Users (2)");
+        assert_eq!(second.name, "Users (3)");
+        assert_ne!(first.id, existing.id);
+        assert_eq!(state.db.get_collections().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_overwrite_replaces_requests() {
+        let state = test_state().await;
+        let (existing, data) = conflicting_import(&state).await;
+        let folder = Collection::new_with_parent("Admin".to_string(), None, Some(existing.id));
+        state.db.create_collection(&folder).await.unwrap();
+        let mut nested = HttpRequest::new(
+            "Nested".to_string(),
+            HttpMethod::GET,
+            "https://old.example.com/admin".to_string(),
+        );
+        nested.collection_id = Some(folder.id);
+        state.db.save_request(&nested).await.unwrap();
+
+        let imported = import_collection_data(&state, &data, ImportConflict::Overwrite)
+            .await
+            .unwrap();
+
+        assert_eq!(imported.id, existing.id);
+        assert_eq!(state.db.get_collections().await.unwrap().len(), 1);
+        let requests = state.db.get_all_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].name, "New");
+        assert_eq!(requests[0].collection_id, Some(existing.id));
+    }
+
+    #[tokio::test]
+    async fn test_import_from_another_workspace_conflicts_at_the_top_level() {
+        let state = test_state().await;
+        let (existing, mut data) = conflicting_import(&state).await;
+        // A parent ID from the source workspace, which doesn't exist here
+        data["collection"]["parent_id"] = Uuid::new_v4().to_string().into();
+
+        let imported = import_collection_data(&state, &data, ImportConflict::Overwrite)
+            .await
+            .unwrap();
+
+        assert_eq!(imported.id, existing.id);
+        assert_eq!(imported.parent_id, None);
+        assert_eq!(state.db.get_collections().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_skip_keeps_existing() {
+        let state = test_state().await;
+        let (existing, data) = conflicting_import(&state).await;
+
+        let result = import_collection_data(&state, &data, ImportConflict::Skip)
+            .await
+            .unwrap();
+
+        assert_eq!(result.id, existing.id);
+        assert_eq!(state.db.get_collections().await.unwrap().len(), 1);
+        let requests = state.db.get_requests(Some(existing.id)).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].name, "Old");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_reports_progress_per_chunk() {
+        let state = test_state().await;
+        let chunked = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                       5\r\nhello\r\n6\r\n world\r\n1\r\n!\r\n0\r\n\r\n";
+        let server = MockServer::start(vec![chunked.to_string()]).await;
+
+        let mut progress = Vec::new();
+        let response = execute_request_with_progress(&state, get_payload(&server.url), |r, t| {
+            progress.push((r, t));
+            ControlFlow::Continue(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(response.body, "hello world!");
+        assert!(!progress.is_empty());
+        assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(progress.last(), Some(&(12, None)));
+
+        // Breaking out of the callback aborts the download
+        let server = MockServer::start(vec![chunked.to_string()]).await;
+        let result = execute_request_with_progress(&state, get_payload(&server.url), |_, _| {
+            ControlFlow::Break(())
+        })
+        .await;
+        assert_eq!(result.unwrap_err(), "Request cancelled");
+    }
+
+    #[test]
+    fn test_response_file_path_defaults_name_and_extension() {
+        let dir = std::env::temp_dir();
+
+        // A directory takes the suggested name
+        assert_eq!(
+            response_file_path(
+                dir.clone(),
+                Some(r#"attachment; filename="data.csv""#),
+                None
+            ),
+            dir.join("data.csv")
+        );
+        assert_eq!(
+            response_file_path(dir.clone(), None, Some("application/json")),
+            dir.join("response.json")
+        );
+        // A missing extension comes from the suggested name, then the content type
+        assert_eq!(
+            response_file_path(dir.join("out"), Some("report.pdf"), Some("text/plain")),
+            dir.join("out.pdf")
+        );
+        assert_eq!(
+            response_file_path(dir.join("out"), None, Some("text/html; charset=utf-8")),
+            dir.join("out.html")
+        );
+        // An explicit extension is left alone
+        assert_eq!(
+            response_file_path(dir.join("out.log"), Some("report.pdf"), Some("text/plain")),
+            dir.join("out.log")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_returns_cancel_error() {
+        let state = test_state().await;
+        // Accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let mut payload = get_payload(&url);
+        payload.timeout = Some(30);
+
+        let send = run_cancellable(
+            &state,
+            Some("req-1".to_string()),
+            execute_request(&state, payload),
+        );
+        let cancel = async {
+            let (socket, _) = listener.accept().await.unwrap();
+            while !state.in_flight_requests.lock().await.contains_key("req-1") {
+                tokio::task::yield_now().await;
+            }
+            let cancel = state.in_flight_requests.lock().await.remove("req-1");
+            cancel.unwrap().send(()).unwrap();
+            socket
+        };
+        let (result, _socket) = tokio::join!(send, cancel);
+
+        assert_eq!(result.unwrap_err(), REQUEST_CANCELLED);
+        assert!(state.in_flight_requests.lock().await.is_empty());
+
+        // A request that finishes on its own leaves nothing behind
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let send = execute_request(&state, get_payload(&server.url));
+        let result = run_cancellable(&state, Some("req-2".to_string()), send).await;
+        assert_eq!(result.unwrap().status, 200);
+        assert!(state.in_flight_requests.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auto_sync_fires_against_mock_provider() {
+        let login = r#"{"access_token":"token","refresh_token":null,"user":{"id":"1","email":"dev@example.com","name":null}}"#;
+        let server = MockServer::start(vec![
+            response("200 OK", &[("Content-Type", "application/json")], login),
+            response("200 OK", &[("Content-Type", "application/json")], "[]"),
+        ])
+        .await;
+
+        let state = Arc::new(test_state().await);
+        *state.sync_client.lock().await = SyncClient::new_api_server(&server.url).unwrap();
+        state
+            .sync_client
+            .lock()
+            .await
+            .api_server_sign_in("dev@example.com", "password")
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task_state = state.clone();
+        let handle = spawn_auto_sync(Duration::from_millis(50), move || {
+            let state = task_state.clone();
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(auto_sync_cycle(&state).await);
+            }
+        });
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        handle.abort();
+
+        assert_eq!(event, AutoSyncEvent::Synced);
+        assert!(server
+            .requests()
+            .iter()
+            .any(|r| r.starts_with("GET /api/collections")));
+        assert!(handle.await.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_aborted_auto_sync_releases_the_running_flag() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers the sign-in, then holds every later request open so the sync hangs
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let login = r#"{"access_token":"token","refresh_token":null,"user":{"id":"1","email":"dev@example.com","name":null}}"#;
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = socket.read(&mut [0u8; 4096]).await;
+            let reply = response("200 OK", &[("Content-Type", "application/json")], login);
+            let _ = socket.write_all(reply.as_bytes()).await;
+            drop(socket);
+
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let state = Arc::new(test_state().await);
+        *state.sync_client.lock().await = SyncClient::new_api_server(&url).unwrap();
+        state
+            .sync_client
+            .lock()
+            .await
+            .api_server_sign_in("dev@example.com", "password")
+            .await
+            .unwrap();
+
+        let task_state = state.clone();
+        let handle = tokio::spawn(async move { auto_sync_cycle(&task_state).await });
+        while !state.sync_running.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+
+        assert!(!state.sync_running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_manual_sync_is_refused_while_another_sync_runs() {
+        let state = test_state().await;
+        let running = SyncRunning::acquire(&state.sync_running).unwrap();
+
+        let errors = [
+            run_exclusive_sync(&state, push_unsynced(&state)).await,
+            run_exclusive_sync(&state, pull_remote(&state)).await,
+            run_exclusive_sync(&state, full_sync(&state)).await,
+        ];
+        for error in errors {
+            let error = error.unwrap_err();
+            assert!(
+                error.to_string().contains("already in progress"),
+                "{}",
+                error
+            );
+        }
+
+        // The refused syncs must not clear the flag held by the running one
+        assert!(state.sync_running.load(Ordering::SeqCst));
+        drop(running);
+        assert!(!state.sync_running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_auto_sync_skips_when_signed_out() {
+        let state = test_state().await;
+        assert_eq!(
+            auto_sync_cycle(&state).await,
+            AutoSyncEvent::Skipped {
+                reason: "Not authenticated".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_records_last_run_without_marking_unsynced() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("201 Created", &[], "ok")]).await;
+
+        let request = HttpRequest::new("Create".to_string(), HttpMethod::GET, server.url.clone());
+        let saved = state.db.save_request(&request).await.unwrap();
+        let id = saved.id.unwrap();
+        state
+            .db
+            .mark_request_synced(id, "cloud-1".to_string(), saved.version)
+            .await
+            .unwrap();
+
+        let mut payload = get_payload(&server.url);
+        payload.request_id = Some(id.to_string());
+        execute_request(&state, payload).await.unwrap();
+
+        let updated = state.db.get_request(id).await.unwrap().unwrap();
+        assert_eq!(updated.last_status, Some(201));
+        assert!(updated.last_response_time.is_some());
+        assert!(updated.last_run_at.is_some());
+        assert!(updated.synced);
+        assert_eq!(updated.version, saved.version);
+    }
 
-    // Clear all sync configuration from database
-    state
-        .db
-        .clear_sync_config()
-        .await
-        .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_force_no_body_omits_get_body() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+
+        let mut payload = get_payload(&server.url);
+        payload.body = Some(RequestBody::Json(serde_json::json!({ "filter": "all" })));
+        payload.force_no_body = true;
+        execute_request(&state, payload).await.unwrap();
+
+        let sent = server.requests()[0].to_lowercase();
+        let (head, body) = sent.split_once("\r\n\r\n").unwrap();
+        assert!(body.is_empty());
+        assert!(!head.contains("content-type"));
+        assert!(head
+            .lines()
+            .filter_map(|line| line.strip_prefix("content-length:"))
+            .all(|len| len.trim() == "0"));
+    }
 
-    // Reset sync client to default (no provider)
-    let default_config = ProviderConfig {
-        provider: SyncProvider::ApiServer,
-        api_server_url: None,
-        supabase_url: None,
-        supabase_api_key: None,
-        supabase_db_uri: None,
-        google_client_id: None,
-        google_client_secret: None,
-        google_redirect_uri: None,
-    };
+    #[tokio::test]
+    async fn test_ephemeral_request_leaves_no_history_or_cookies() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response("200 OK", &[("Set-Cookie", "session=secret; Path=/")], "ok"),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let mut payload = get_payload(&format!("{}/login", server.url));
+        payload.ephemeral = true;
+        let sent = execute_request(&state, payload).await.unwrap();
+        assert_eq!(sent.status, 200);
+        assert!(state.db.get_history(None).await.unwrap().is_empty());
+
+        // The cookie set by the ephemeral response isn't sent with later requests
+        let payload = get_payload(&format!("{}/profile", server.url));
+        execute_request(&state, payload).await.unwrap();
+        assert!(!server.requests()[1].to_lowercase().contains("cookie:"));
+        assert_eq!(state.db.get_history(None).await.unwrap().len(), 1);
+
+        // An expired OAuth2 token is refreshed for the request but not stored
+        let server = MockServer::start(vec![
+            response(
+                "200 OK",
+                &[("Content-Type", "application/json")],
+                r#"{"access_token":"fresh-token","token_type":"bearer","expires_in":3600}"#,
+            ),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+        let mut collection = Collection::new("OAuth".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::OAuth2AuthCode,
+            basic: None,
+            bearer: None,
+            oauth2: Some(OAuth2AuthCode {
+                auth_url: "https://auth.example.com/authorize".to_string(),
+                token_url: format!("{}/token", server.url),
+                client_id: "geni-client".to_string(),
+                client_secret: None,
+                redirect_uri: "http://localhost:1420/callback".to_string(),
+                scopes: Vec::new(),
+                token: Some(OAuth2Token {
+                    access_token: "stale-token".to_string(),
+                    refresh_token: Some("refresh-me".to_string()),
+                    expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(5)),
+                }),
+            }),
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+
+        let mut payload = get_payload(&format!("{}/me", server.url));
+        payload.collection_id = Some(collection.id.to_string());
+        payload.ephemeral = true;
+        execute_request(&state, payload).await.unwrap();
+
+        assert!(server.requests()[1]
+            .to_lowercase()
+            .contains("authorization: bearer fresh-token"));
+        let collections = state.db.get_collections().await.unwrap();
+        let stored = collections.into_iter().find(|c| c.id == collection.id);
+        let token = stored.unwrap().auth.unwrap().oauth2.unwrap().token.unwrap();
+        assert_eq!(token.access_token, "stale-token");
+    }
 
-    let new_client = SyncClient::new(default_config).map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_binary_file_body_is_relative_to_the_workspace() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let name = format!("geni_upload_{}.bin", Uuid::new_v4());
+        std::fs::write(state.workspace_dir.join(&name), "file-bytes").unwrap();
+
+        let mut payload = get_payload(&format!("{}/upload", server.url));
+        payload.method = HttpMethod::PUT;
+        payload.body = Some(RequestBody::BinaryFile { path: name.clone() });
+        let response = execute_request(&state, payload).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert!(server.requests()[0].ends_with("\r\n\r\nfile-bytes"));
+        std::fs::remove_file(state.workspace_dir.join(&name)).unwrap();
+    }
 
-    let mut client = state.sync_client.lock().await;
-    *client = new_client;
+    #[tokio::test]
+    async fn test_raw_json_body_file_is_sent_as_written() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let name = format!("geni_raw_body_{}.json", Uuid::new_v4());
+        std::fs::write(state.workspace_dir.join(&name), r#"{"id": {{id}}}"#).unwrap();
+        let variables = HashMap::from([("id".to_string(), "42".to_string())]);
+        let environment = Environment::new("Local".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+
+        let mut payload = get_payload(&format!("{}/items", server.url));
+        payload.method = HttpMethod::POST;
+        payload.environment_id = Some(environment.id.to_string());
+        payload.body = Some(RequestBody::Json(serde_json::json!({})));
+        payload.body_file = Some(name.clone());
+        payload.body_file_raw = true;
+        let response = execute_request(&state, payload).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert!(server.requests()[0].ends_with("\r\n\r\n{\"id\": {{id}}}"));
+        std::fs::remove_file(state.workspace_dir.join(&name)).unwrap();
+    }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_validation_resolves_files_and_environment_like_send() {
+        let state = test_state().await;
+        let name = format!("geni_validate_{}.bin", Uuid::new_v4());
+        std::fs::write(state.workspace_dir.join(&name), "file-bytes").unwrap();
+        let variables = HashMap::from([(
+            "base_url".to_string(),
+            "https://api.example.com".to_string(),
+        )]);
+        let environment = Environment::new("Staging".to_string(), variables);
+        state.db.create_environment(&environment).await.unwrap();
+
+        let mut payload = get_payload("{{base_url}}/upload");
+        payload.method = HttpMethod::PUT;
+        payload.environment_id = Some(environment.id.to_string());
+        payload.body = Some(RequestBody::BinaryFile { path: name.clone() });
+        let issues = validate_with_state(&state, &payload).await.unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+
+        payload.body = Some(RequestBody::FormData(vec![(
+            "file".to_string(),
+            FormDataField::File { path: name.clone() },
+        )]));
+        let issues = validate_with_state(&state, &payload).await.unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+        std::fs::remove_file(state.workspace_dir.join(&name)).unwrap();
+    }
 
-// Supabase schema management
-#[tauri::command]
-pub async fn supabase_create_schema(state: State<'_, AppState>) -> Result<String, String> {
-    let client = state.sync_client.lock().await;
-    match client.ensure_schema().await {
-        Ok(_) => Ok("Schema created successfully or already exists".to_string()),
-        Err(e) => Err(format!("Failed to create schema: {}", e)),
+    #[tokio::test]
+    async fn test_form_data_file_is_relative_to_the_workspace() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let name = format!("geni_form_{}.txt", Uuid::new_v4());
+        std::fs::write(state.workspace_dir.join(&name), "form-file-bytes").unwrap();
+
+        let mut payload = get_payload(&format!("{}/upload", server.url));
+        payload.method = HttpMethod::POST;
+        payload.body = Some(RequestBody::FormData(vec![(
+            "file".to_string(),
+            FormDataField::File { path: name.clone() },
+        )]));
+        let response = execute_request(&state, payload).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert!(server.requests()[0].contains("form-file-bytes"));
+        std::fs::remove_file(state.workspace_dir.join(&name)).unwrap();
     }
-}
 
-#[tauri::command]
-pub async fn is_authenticated(state: State<'_, AppState>) -> Result<bool, String> {
-    let client = state.sync_client.lock().await;
-    Ok(client.is_authenticated())
-}
+    #[tokio::test]
+    async fn test_request_log_appends_redacted_line() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "ok")]).await;
+        let log_path =
+            std::env::temp_dir().join(format!("geni_request_log_{}.log", Uuid::new_v4()));
+        *state.request_log_path.write().unwrap() = Some(log_path.clone());
+
+        let payload = get_payload(&format!("{}/items?access_token=abc123&page=1", server.url));
+        execute_request(&state, payload).await.unwrap();
+
+        // The line is written off the request path, so give it a moment to land
+        let mut contents = String::new();
+        for _ in 0..50 {
+            contents = std::fs::read_to_string(&log_path).unwrap_or_default();
+            if !contents.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        std::fs::remove_file(&log_path).unwrap();
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let entry: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(entry["method"], "GET");
+        assert_eq!(entry["status"], 200);
+        assert_eq!(
+            entry["url"],
+            format!("{}/items?access_token=REDACTED&page=1", server.url)
+        );
+        assert!(!contents.contains("abc123"));
+    }
 
-#[tauri::command]
-pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<User>, String> {
-    let client = state.sync_client.lock().await;
-    Ok(client.get_current_user().await)
-}
+    #[test]
+    fn test_select_system_env_filters_by_prefix() {
+        std::env::set_var("GENI_TEST_IMPORT_API_KEY", "secret-key");
+        std::env::set_var("GENI_TEST_IMPORT_BASE_URL", "http://localhost:8080");
+        std::env::set_var("OTHER_TEST_IMPORT_VALUE", "ignored");
 
-#[tauri::command]
-pub async fn sync_push(state: State<'_, AppState>) -> Result<(), String> {
-    // Get unsynced items
-    let collections = state
-        .db
-        .get_unsynced_collections()
-        .await
-        .map_err(|e| e.to_string())?;
+        let vars = select_system_env(std::env::vars(), Some("GENI_TEST_IMPORT_"), None).unwrap();
 
-    let requests = state
-        .db
-        .get_unsynced_requests()
-        .await
-        .map_err(|e| e.to_string())?;
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars.get("API_KEY").unwrap(), "secret-key");
+        assert_eq!(vars.get("BASE_URL").unwrap(), "http://localhost:8080");
+        assert!(select_system_env(std::env::vars(), None, None).is_err());
+    }
 
-    let environments = state
-        .db
-        .get_unsynced_environments()
+    #[tokio::test]
+    async fn test_success_criteria_fails_error_body_with_200() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            r#"{"ok":false,"error":"quota exceeded"}"#,
+        )])
+        .await;
+
+        let mut payload = get_payload(&server.url);
+        payload.success_criteria = Some(SuccessCriteria::JsonPathEquals {
+            path: "$.ok".to_string(),
+            value: serde_json::json!(true),
+        });
+        let response = execute_request(&state, payload).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.is_success_by_criteria, Some(false));
+
+        let plain = execute_request(&state, get_payload(&server.url))
+            .await
+            .unwrap();
+        assert_eq!(plain.is_success_by_criteria, None);
+    }
+
+    #[tokio::test]
+    async fn test_markdown_export_of_nested_folder_with_examples() {
+        let state = test_state().await;
+        let root = Collection::new("Users API".to_string(), None);
+        let folder = Collection::new_with_parent("Admin".to_string(), None, Some(root.id));
+        state.db.create_collection(&root).await.unwrap();
+        state.db.create_collection(&folder).await.unwrap();
+
+        let mut request = HttpRequest::new(
+            "Get user".to_string(),
+            HttpMethod::GET,
+            "{{base_url}}/users/7".to_string(),
+        );
+        request.collection_id = Some(folder.id);
+        let request = state.db.save_request(&request).await.unwrap();
+        let example = SavedResponse {
+            name: "ok".to_string(),
+            status: 200,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: r#"{"id":7}"#.to_string(),
+            saved_at: chrono::Utc::now(),
+        };
+        state
+            .db
+            .set_request_examples(request.id.unwrap(), vec![example])
+            .await
+            .unwrap();
+        // A record that no longer parses is skipped, not fatal to the export
+        state
+            .db
+            .insert_raw_record("requests", "broken", b"{not json");
+
+        let markdown = collection_markdown(&state, &root.id.to_string())
+            .await
+            .unwrap();
+        assert!(markdown.starts_with("# Users API\n"));
+        assert!(markdown.contains("## Admin\n"));
+        assert!(markdown.contains("### Get user\n"));
+        assert!(markdown.contains("**Example: ok** (200)\n\n```json\n{\"id\":7}\n```"));
+        assert_eq!(state.db.get_corrupt_records().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_compare_to_example_ignores_volatile_fields() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            r#"{"id":7,"name":"Ada","generated_at":"2025-06-01T10:00:00Z"}"#,
+        )])
+        .await;
+
+        let request = HttpRequest::new(
+            "Get user".to_string(),
+            HttpMethod::GET,
+            format!("{}/users/7", server.url),
+        );
+        let request = state.db.save_request(&request).await.unwrap();
+        let example = SavedResponse {
+            name: "ok".to_string(),
+            status: 200,
+            headers: HashMap::from([("content-type".to_string(), "application/json".to_string())]),
+            body: r#"{"id":7,"name":"Ada","generated_at":"2024-01-01T00:00:00Z"}"#.to_string(),
+            saved_at: chrono::Utc::now(),
+        };
+        state
+            .db
+            .set_request_examples(request.id.unwrap(), vec![example])
+            .await
+            .unwrap();
+
+        let request_id = request.id.unwrap().to_string();
+        let content_type = vec!["Content-Type".to_string()];
+        let result = compare_request_to_example(
+            &state,
+            &request_id,
+            "ok",
+            &["$.generated_at".to_string()],
+            &content_type,
+        )
         .await
-        .map_err(|e| e.to_string())?;
+        .unwrap();
+        assert!(result.matches, "{:?}", result.differences);
 
-    if collections.is_empty() && requests.is_empty() && environments.is_empty() {
-        return Ok(()); // Nothing to sync
+        let strict = compare_request_to_example(&state, &request_id, "ok", &[], &content_type)
+            .await
+            .unwrap();
+        assert!(!strict.matches);
+        assert_eq!(strict.differences.len(), 1);
+        assert!(strict.differences[0].starts_with("$.generated_at"));
     }
 
-    let client = state.sync_client.lock().await;
+    fn oauth2_auth_code(token_url: String) -> AuthConfig {
+        AuthConfig {
+            auth_type: AuthType::OAuth2AuthCode,
+            basic: None,
+            bearer: None,
+            oauth2: Some(OAuth2AuthCode {
+                auth_url: "https://auth.example.com/authorize".to_string(),
+                token_url,
+                client_id: "geni-client".to_string(),
+                client_secret: None,
+                redirect_uri: "http://localhost:1420/callback".to_string(),
+                scopes: Vec::new(),
+                token: None,
+            }),
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        }
+    }
 
-    // Push to cloud
-    for collection in collections {
-        if let Some(cloud_id) = &collection.cloud_id {
-            // Update existing
-            client
-                .push_collection(&collection)
-                .await
-                .map_err(|e| e.to_string())?;
+    // Runs the authorization-code flow for an ID, answering with the pending CSRF state
+    async fn authorize_oauth2(state: &AppState, id: &str) {
+        oauth2_authorization_url(state, id).await.unwrap();
+        let csrf_state = state
+            .oauth2_pending
+            .lock()
+            .await
+            .values()
+            .next()
+            .unwrap()
+            .1
+            .clone();
+        oauth2_exchange(state, id, "code", &csrf_state)
+            .await
+            .unwrap();
+    }
 
-            state
-                .db
-                .mark_collection_synced(collection.id, cloud_id.clone(), collection.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        } else {
-            // Create new
-            let cloud_id = client
-                .push_collection(&collection)
-                .await
-                .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_oauth2_token_is_stored_where_the_auth_lives() {
+        let state = test_state().await;
+        let token_body = r#"{"access_token":"live-token","token_type":"bearer"}"#;
+        let server = MockServer::start(vec![response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            token_body,
+        )])
+        .await;
+        let token_url = format!("{}/token", server.url);
+
+        // A folder request inherits OAuth2 from the parent, which keeps the token
+        let mut parent = Collection::new("Provider".to_string(), None);
+        parent.auth = Some(oauth2_auth_code(token_url.clone()));
+        state.db.create_collection(&parent).await.unwrap();
+        let folder = Collection::new_with_parent("Users".to_string(), None, Some(parent.id));
+        state.db.create_collection(&folder).await.unwrap();
+        let mut inherited = HttpRequest::new(
+            "List".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/users".to_string(),
+        );
+        inherited.collection_id = Some(folder.id);
+        let inherited = state.db.save_request(&inherited).await.unwrap();
+        authorize_oauth2(&state, &inherited.id.unwrap().to_string()).await;
+
+        let collections = state.db.get_collections().await.unwrap();
+        let stored = collections.iter().find(|c| c.id == parent.id).unwrap();
+        let token = stored
+            .auth
+            .as_ref()
+            .and_then(|a| a.oauth2.as_ref()?.token.clone());
+        assert_eq!(token.unwrap().access_token, "live-token");
+
+        // A request with its own OAuth2 auth keeps its own token
+        let own = HttpRequest::new(
+            "Me".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/me".to_string(),
+        );
+        let own = state.db.save_request(&own).await.unwrap();
+        let id = own.id.unwrap();
+        state
+            .db
+            .set_request_auth(id, Some(oauth2_auth_code(token_url)))
+            .await
+            .unwrap();
+        authorize_oauth2(&state, &id.to_string()).await;
 
-            state
-                .db
-                .mark_collection_synced(collection.id, cloud_id, collection.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+        let stored = state.db.get_request(id).await.unwrap().unwrap();
+        let token = stored.auth.and_then(|a| a.oauth2?.token).unwrap();
+        assert_eq!(token.access_token, "live-token");
     }
 
-    for request in requests {
-        if let Some(ref cloud_id) = request.cloud_id {
-            client
-                .push_request(&request)
-                .await
-                .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_oauth2_auth_code_flow_with_pkce() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            r#"{"access_token":"live-token","token_type":"bearer","expires_in":3600,"refresh_token":"refresh-me"}"#,
+        )])
+        .await;
+
+        let mut collection = Collection::new("Provider".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::OAuth2AuthCode,
+            basic: None,
+            bearer: None,
+            oauth2: Some(OAuth2AuthCode {
+                auth_url: "https://auth.example.com/authorize".to_string(),
+                token_url: format!("{}/token", server.url),
+                client_id: "geni-client".to_string(),
+                client_secret: None,
+                redirect_uri: "http://localhost:1420/callback".to_string(),
+                scopes: vec!["read".to_string(), "write".to_string()],
+                token: None,
+            }),
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+        let id = collection.id.to_string();
+
+        let auth_url = oauth2_authorization_url(&state, &id).await.unwrap();
+        let url = reqwest::Url::parse(&auth_url).unwrap();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert!(auth_url.starts_with("https://auth.example.com/authorize?"));
+        assert_eq!(query["response_type"], "code");
+        assert_eq!(query["client_id"], "geni-client");
+        assert_eq!(query["redirect_uri"], "http://localhost:1420/callback");
+        assert_eq!(query["scope"], "read write");
+        assert_eq!(query["code_challenge_method"], "S256");
+        let (verifier, csrf_state) = state.oauth2_pending.lock().await[&collection.id].clone();
+        assert_eq!(query["state"], csrf_state);
+
+        let forged = oauth2_exchange(&state, &id, "auth-code-42", "forged").await;
+        assert!(forged.unwrap_err().contains("state doesn't match"));
+        let token = oauth2_exchange(&state, &id, "auth-code-42", &csrf_state)
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "live-token");
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh-me"));
+        assert!(token.expires_at.unwrap() > chrono::Utc::now());
+
+        let sent = server.requests()[0].clone();
+        assert!(sent.contains("code=auth-code-42"));
+        assert!(sent.contains(&format!("code_verifier={}", verifier)));
+
+        let stored = state.db.get_collections().await.unwrap()[0].auth.clone();
+        let headers = generate_auth_headers(&stored.unwrap());
+        assert_eq!(headers.get("Authorization").unwrap(), "Bearer live-token");
+
+        // The token stays on this device: it isn't pushed, and a pull keeps it
+        let upload = MockServer::start(vec![response("200 OK", &[], "{}")]).await;
+        let client = crate::sync::supabase::SupabaseClient::new(&upload.url, "anon-key")
+            .unwrap()
+            .with_storage_bucket(Some("geni".to_string()));
+        *state.sync_client.lock().await = SyncClient::Supabase(client);
+        push_unsynced(&state).await.unwrap();
+        assert!(upload.requests()[0].contains("geni-client"));
+        assert!(!upload.requests()[0].contains("live-token"));
+
+        let mut pulled = state.db.get_collections().await.unwrap().remove(0);
+        strip_oauth2_token(pulled.auth.as_mut());
+        pulled.version += 1;
+        state.db.merge_collection(pulled).await.unwrap();
+        let stored = state.db.get_collections().await.unwrap()[0].auth.clone();
+        let token = stored.unwrap().oauth2.unwrap().token.unwrap();
+        assert_eq!(token.access_token, "live-token");
+    }
 
-            state
-                .db
-                .mark_request_synced(request.id.unwrap(), cloud_id.clone(), request.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        } else {
-            let cloud_id = client
-                .push_request(&request)
-                .await
-                .map_err(|e| e.to_string())?;
+    fn blob_sync_client(url: &str) -> SyncClient {
+        let client = crate::sync::supabase::SupabaseClient::new(url, "anon-key")
+            .unwrap()
+            .with_storage_bucket(Some("geni".to_string()));
+        SyncClient::Supabase(client)
+    }
 
-            state
-                .db
-                .mark_request_synced(request.id.unwrap(), cloud_id, request.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+    // Pushes `source` to a Supabase Storage blob, then pulls that upload into `target`
+    async fn blob_push_then_pull(source: &AppState, target: &AppState) {
+        let upload = MockServer::start(vec![response("200 OK", &[], "{}")]).await;
+        *source.sync_client.lock().await = blob_sync_client(&upload.url);
+        push_unsynced(source).await.unwrap();
+        let sent = upload.requests().remove(0);
+        let (_, body) = sent.split_once("\r\n\r\n").unwrap();
+
+        let download = MockServer::start(vec![response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            body,
+        )])
+        .await;
+        *target.sync_client.lock().await = blob_sync_client(&download.url);
+        pull_remote(target).await.unwrap();
     }
 
-    for environment in environments {
-        if let Some(ref cloud_id) = environment.cloud_id {
-            client
-                .push_environment(&environment)
-                .await
-                .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_pulled_collection_defaults_follow_later_edits() {
+        let source = test_state().await;
+        let target = test_state().await;
+        let collection = Collection::new("API".to_string(), None);
+        source.db.create_collection(&collection).await.unwrap();
+        blob_push_then_pull(&source, &target).await;
 
-            state
-                .db
-                .mark_environment_synced(environment.id, cloud_id.clone(), environment.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        } else {
-            let cloud_id = client
-                .push_environment(&environment)
-                .await
-                .map_err(|e| e.to_string())?;
+        source
+            .db
+            .update_collection_defaults(collection.id, Some(45), Some(3))
+            .await
+            .unwrap();
+        blob_push_then_pull(&source, &target).await;
 
-            state
-                .db
-                .mark_environment_synced(environment.id, cloud_id, environment.version)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+        let pulled = target.db.get_collections().await.unwrap().remove(0);
+        assert_eq!(pulled.default_timeout, Some(45));
+        assert_eq!(pulled.default_retries, Some(3));
     }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_pulled_dependencies_point_at_local_requests() {
+        let source = test_state().await;
+        let target = test_state().await;
+        let request = |name: &str, depends_on: Vec<Uuid>| {
+            let mut request = HttpRequest::new(
+                name.to_string(),
+                HttpMethod::GET,
+                format!("https://api.example.com/{}", name),
+            );
+            request.depends_on = depends_on;
+            request
+        };
+        let login = source
+            .db
+            .save_request(&request("login", vec![]))
+            .await
+            .unwrap();
+        let fetch = request("fetch", vec![login.id.unwrap()]);
+        let fetch = source.db.save_request(&fetch).await.unwrap();
+        blob_push_then_pull(&source, &target).await;
+
+        let local_id = |requests: &[HttpRequest], name: &str| {
+            requests
+                .iter()
+                .find(|r| r.name == name)
+                .unwrap()
+                .id
+                .unwrap()
+        };
+        let pulled = target.db.get_all_requests().await.unwrap();
+        let pulled_fetch = pulled.iter().find(|r| r.name == "fetch").unwrap();
+        assert_ne!(local_id(&pulled, "login"), login.id.unwrap());
+        assert_eq!(pulled_fetch.depends_on, vec![local_id(&pulled, "login")]);
+
+        // A later change to the chain follows onto the pulled copy
+        let token = source
+            .db
+            .save_request(&request("token", vec![]))
+            .await
+            .unwrap();
+        source
+            .db
+            .set_request_dependencies(fetch.id.unwrap(), vec![token.id.unwrap()])
+            .await
+            .unwrap();
+        blob_push_then_pull(&source, &target).await;
 
-#[tauri::command]
-pub async fn sync_pull(state: State<'_, AppState>) -> Result<(), String> {
-    // Pull from cloud
-    let client = state.sync_client.lock().await;
-    let pull_response = client.pull_sync().await.map_err(|e| e.to_string())?;
+        let pulled = target.db.get_all_requests().await.unwrap();
+        let pulled_fetch = pulled.iter().find(|r| r.name == "fetch").unwrap();
+        assert_eq!(pulled_fetch.depends_on, vec![local_id(&pulled, "token")]);
+    }
 
-    drop(client); // Release lock before database operations
+    #[tokio::test]
+    async fn test_pulled_request_edits_reach_the_local_copy() {
+        let source = test_state().await;
+        let target = test_state().await;
+        let request = HttpRequest::new(
+            "Users".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/users".to_string(),
+        );
+        let request = source.db.save_request(&request).await.unwrap();
+        let id = request.id.unwrap();
+        blob_push_then_pull(&source, &target).await;
+
+        let example = SavedResponse {
+            name: "Empty list".to_string(),
+            status: 200,
+            headers: HashMap::new(),
+            body: "[]".to_string(),
+            saved_at: chrono::Utc::now(),
+        };
+        source
+            .db
+            .set_request_examples(id, vec![example])
+            .await
+            .unwrap();
+        let mut edited = source.db.get_request(id).await.unwrap().unwrap();
+        edited.success_criteria = Some(SuccessCriteria::StatusRange(200, 204));
+        source.db.save_request(&edited).await.unwrap();
+        let auth = AuthConfig {
+            auth_type: AuthType::Bearer,
+            basic: None,
+            bearer: Some(BearerAuth {
+                token: "{{token}}".to_string(),
+            }),
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        };
+        source.db.set_request_auth(id, Some(auth)).await.unwrap();
+        source
+            .db
+            .set_request_alias(id, Some("users".to_string()))
+            .await
+            .unwrap();
+        source
+            .db
+            .set_request_environment_exclusions(id, vec!["Production".to_string()])
+            .await
+            .unwrap();
+        let mut edited = source.db.get_request(id).await.unwrap().unwrap();
+        edited.query_params = vec![("page".to_string(), "2".to_string(), true)];
+        source.db.save_request(&edited).await.unwrap();
+        blob_push_then_pull(&source, &target).await;
+
+        let pulled = target.db.get_all_requests().await.unwrap().remove(0);
+        assert_eq!(pulled.examples.len(), 1);
+        assert_eq!(pulled.examples[0].name, "Empty list");
+        assert!(matches!(
+            pulled.success_criteria,
+            Some(SuccessCriteria::StatusRange(200, 204))
+        ));
+        let bearer = pulled.auth.and_then(|auth| auth.bearer).unwrap();
+        assert_eq!(bearer.token, "{{token}}");
+        assert_eq!(pulled.alias.as_deref(), Some("users"));
+        assert_eq!(pulled.disabled_in_environments, vec!["Production"]);
+        assert_eq!(
+            pulled.query_params,
+            vec![("page".to_string(), "2".to_string(), true)]
+        );
+    }
 
-    // Merge collections
-    for collection in pull_response.collections {
-        state
+    #[tokio::test]
+    async fn test_pulled_base_url_override_follows_later_edits() {
+        let source = test_state().await;
+        let target = test_state().await;
+        let environment = Environment::new("Staging".to_string(), HashMap::new());
+        source.db.create_environment(&environment).await.unwrap();
+        blob_push_then_pull(&source, &target).await;
+
+        source
             .db
-            .merge_collection(collection)
+            .set_environment_base_url_override(
+                environment.id,
+                Some("https://staging.example.com".to_string()),
+            )
             .await
-            .map_err(|e| e.to_string())?;
+            .unwrap();
+        blob_push_then_pull(&source, &target).await;
+
+        let pulled = target.db.get_environments().await.unwrap().remove(0);
+        assert_eq!(
+            pulled.base_url_override.as_deref(),
+            Some("https://staging.example.com")
+        );
     }
 
-    // Merge requests
-    for request in pull_response.requests {
+    #[tokio::test]
+    async fn test_blob_sync_uploads_the_whole_workspace() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![response("200 OK", &[], "{}")]).await;
+        let client = crate::sync::supabase::SupabaseClient::new(&server.url, "anon-key")
+            .unwrap()
+            .with_storage_bucket(Some("geni".to_string()));
+        *state.sync_client.lock().await = SyncClient::Supabase(client);
+
+        let synced = Collection::new("Already synced".to_string(), None);
+        state.db.create_collection(&synced).await.unwrap();
         state
             .db
-            .merge_request(request)
+            .mark_collection_synced(synced.id, "cloud-1".to_string(), synced.version)
             .await
-            .map_err(|e| e.to_string())?;
+            .unwrap();
+        let fresh = Collection::new("Fresh".to_string(), None);
+        state.db.create_collection(&fresh).await.unwrap();
+
+        push_unsynced(&state).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("\"Already synced\""));
+        assert!(requests[0].contains("\"Fresh\""));
+        let unsynced = state.db.get_unsynced_collections().await.unwrap();
+        assert!(unsynced.is_empty());
+        let collections = state.db.get_collections().await.unwrap();
+        let cloud_id = |id| {
+            collections
+                .iter()
+                .find(|c| c.id == id)
+                .and_then(|c| c.cloud_id.clone())
+        };
+        assert_eq!(cloud_id(synced.id).as_deref(), Some("cloud-1"));
+        assert_eq!(cloud_id(fresh.id), Some(fresh.id.to_string()));
     }
 
-    // Merge environments
-    for environment in pull_response.environments {
+    #[tokio::test]
+    async fn test_expired_request_oauth2_token_is_refreshed() {
+        let state = test_state().await;
+        let server = MockServer::start(vec![
+            response(
+                "200 OK",
+                &[("Content-Type", "application/json")],
+                r#"{"access_token":"fresh-token","token_type":"bearer","expires_in":3600}"#,
+            ),
+            response("200 OK", &[], "ok"),
+        ])
+        .await;
+
+        let mut request = HttpRequest::new(
+            "Me".to_string(),
+            HttpMethod::GET,
+            format!("{}/me", server.url),
+        );
+        request.auth = Some(AuthConfig {
+            auth_type: AuthType::OAuth2AuthCode,
+            basic: None,
+            bearer: None,
+            oauth2: Some(OAuth2AuthCode {
+                auth_url: "https://auth.example.com/authorize".to_string(),
+                token_url: format!("{}/token", server.url),
+                client_id: "geni-client".to_string(),
+                client_secret: None,
+                redirect_uri: "http://localhost:1420/callback".to_string(),
+                scopes: Vec::new(),
+                token: Some(OAuth2Token {
+                    access_token: "stale-token".to_string(),
+                    refresh_token: Some("refresh-me".to_string()),
+                    expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(5)),
+                }),
+            }),
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        let id = state.db.save_request(&request).await.unwrap().id.unwrap();
+
+        let mut payload = get_payload(&format!("{}/me", server.url));
+        payload.request_id = Some(id.to_string());
+        execute_request(&state, payload).await.unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0].contains("refresh_token=refresh-me"));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("authorization: bearer fresh-token"));
+        let stored = state.db.get_request(id).await.unwrap().unwrap();
+        let token = stored.auth.unwrap().oauth2.unwrap().token.unwrap();
+        assert_eq!(token.access_token, "fresh-token");
+        // The provider didn't rotate the refresh token, so the old one is kept
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh-me"));
+    }
+
+    async fn sample_workspace(state: &AppState) -> (Collection, HttpRequest) {
+        let mut collection = Collection::new("API".to_string(), None);
+        collection.auth = Some(AuthConfig {
+            auth_type: AuthType::Bearer,
+            basic: None,
+            bearer: Some(BearerAuth {
+                token: "secret-token".to_string(),
+            }),
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        });
+        state.db.create_collection(&collection).await.unwrap();
+
+        let mut request = HttpRequest::new(
+            "List".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/items".to_string(),
+        );
+        request.collection_id = Some(collection.id);
+        request.auth = Some(AuthConfig {
+            auth_type: AuthType::Basic,
+            basic: Some(BasicAuth {
+                username: "ada".to_string(),
+                password: "request-password".to_string(),
+            }),
+            ..collection.auth.clone().unwrap()
+        });
+        let request = state.db.save_request(&request).await.unwrap();
+
+        let variables = HashMap::from([
+            ("api_token".to_string(), "env-secret".to_string()),
+            ("host".to_string(), "api.example.com".to_string()),
+            (
+                "auth_url".to_string(),
+                "https://auth.example.com".to_string(),
+            ),
+            ("author_id".to_string(), "42".to_string()),
+        ]);
         state
             .db
-            .merge_environment(environment)
+            .create_environment(&Environment::new("Prod".to_string(), variables))
             .await
-            .map_err(|e| e.to_string())?;
+            .unwrap();
+        state
+            .db
+            .save_sync_config("api_server", r#"{"token":"session"}"#)
+            .await
+            .unwrap();
+        (collection, request)
     }
 
-    Ok(())
-}
-
-#[tauri::command]
-pub async fn sync_full(state: State<'_, AppState>) -> Result<(), String> {
-    // First push unsynced items
-    sync_push(state.clone()).await?;
+    #[tokio::test]
+    async fn test_import_workspace_replace_restores_bundle() {
+        let source = test_state().await;
+        let (collection, request) = sample_workspace(&source).await;
+        let bundle = build_workspace_bundle(&source, false).await.unwrap();
+
+        assert!(!bundle.config.contains_key("sync_provider_api_server"));
+        let auth = bundle.collections[0].auth.as_ref().unwrap();
+        assert!(auth.bearer.as_ref().unwrap().token.is_empty());
+        let auth = bundle.requests[0].auth.as_ref().unwrap();
+        assert!(auth.basic.as_ref().unwrap().password.is_empty());
+        assert!(auth.bearer.as_ref().unwrap().token.is_empty());
+        let variables = &bundle.environments[0].variables;
+        assert_eq!(variables["api_token"], "");
+        assert_eq!(variables["host"], "api.example.com");
+
+        let target = test_state().await;
+        let stale = Collection::new("Stale".to_string(), None);
+        target.db.create_collection(&stale).await.unwrap();
+        target
+            .db
+            .import_workspace(&bundle, ImportMode::Replace)
+            .await
+            .unwrap();
+
+        let collections = target.db.get_collections().await.unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(collections[0].id, collection.id);
+        let requests = target.db.get_all_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].id, request.id);
+        assert_eq!(requests[0].version, request.version);
+
+        // Names that only contain a secret word's letters keep their values
+        let variables = &target.db.get_environments().await.unwrap()[0].variables;
+        assert_eq!(variables["api_token"], "");
+        assert_eq!(variables["auth_url"], "https://auth.example.com");
+        assert_eq!(variables["author_id"], "42");
+    }
 
-    // Then pull updates
-    sync_pull(state).await?;
+    #[tokio::test]
+    async fn test_import_workspace_keeps_local_settings_and_drops_stale_drafts() {
+        let source = test_state().await;
+        sample_workspace(&source).await;
+        let mut bundle = build_workspace_bundle(&source, false).await.unwrap();
+        bundle.config.insert(
+            "request_log_path".to_string(),
+            "/elsewhere/geni.log".to_string(),
+        );
+        bundle.config.insert(
+            "active_environment_id".to_string(),
+            Uuid::new_v4().to_string(),
+        );
+
+        let target = test_state().await;
+        let local = target
+            .db
+            .save_request(&HttpRequest::new(
+                "Local".to_string(),
+                HttpMethod::GET,
+                "https://api.example.com/local".to_string(),
+            ))
+            .await
+            .unwrap();
+        let local_id = local.id.unwrap();
+        let draft = SaveRequestPayload {
+            id: Some(local_id.to_string()),
+            name: "Local".to_string(),
+            method: HttpMethod::GET,
+            url: "https://api.example.com/local?draft=1".to_string(),
+            headers: Vec::new(),
+            body: None,
+            path_params: HashMap::new(),
+            query_params: Vec::new(),
+            collection_id: None,
+            success_criteria: None,
+            draft_key: None,
+        };
+        target
+            .db
+            .save_draft(&local_id.to_string(), &draft)
+            .await
+            .unwrap();
+        target.db.save_view_state(local_id, "{}").await.unwrap();
+        target
+            .db
+            .set_request_log_path(Some("/local/geni.log"))
+            .await
+            .unwrap();
 
-    Ok(())
-}
+        target
+            .db
+            .import_workspace(&bundle, ImportMode::Replace)
+            .await
+            .unwrap();
+
+        let log_path = target.db.get_request_log_path().await.unwrap();
+        assert_eq!(log_path.as_deref(), Some("/local/geni.log"));
+        assert!(target.db.get_active_environment().await.unwrap().is_none());
+        let local_key = local_id.to_string();
+        assert!(target.db.get_draft(&local_key).await.unwrap().is_none());
+        assert!(target.db.get_view_state(local_id).await.unwrap().is_none());
+    }
 
-#[tauri::command]
-pub async fn get_sync_status(state: State<'_, AppState>) -> Result<SyncStatus, String> {
-    let unsynced_collections = state
-        .db
-        .get_unsynced_collections()
-        .await
-        .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_import_workspace_merge_skips_existing_ids() {
+        let source = test_state().await;
+        let (collection, _) = sample_workspace(&source).await;
+        let bundle = build_workspace_bundle(&source, true).await.unwrap();
+        assert!(bundle.config.contains_key("sync_provider_api_server"));
+
+        let target = test_state().await;
+        let mut renamed = collection.clone();
+        renamed.name = "Local name".to_string();
+        target.db.create_collection(&renamed).await.unwrap();
+        let local = Collection::new("Local only".to_string(), None);
+        target.db.create_collection(&local).await.unwrap();
+
+        for _ in 0..2 {
+            target
+                .db
+                .import_workspace(&bundle, ImportMode::Merge)
+                .await
+                .unwrap();
+        }
 
-    let unsynced_requests = state
-        .db
-        .get_unsynced_requests()
-        .await
-        .map_err(|e| e.to_string())?;
+        let collections = target.db.get_collections().await.unwrap();
+        assert_eq!(collections.len(), 2);
+        let merged = collections.iter().find(|c| c.id == collection.id).unwrap();
+        assert_eq!(merged.name, "Local name");
+        assert_eq!(target.db.get_all_requests().await.unwrap().len(), 1);
+    }
 
-    let unsynced_environments = state
-        .db
-        .get_unsynced_environments()
-        .await
-        .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_merge_import_is_idempotent() {
+        let state = test_state().await;
+        let target = Collection::new("Existing".to_string(), None);
+        state.db.create_collection(&target).await.unwrap();
+
+        let json = r#"{
+            "info": { "name": "Users" },
+            "item": [
+                {
+                    "name": "List users",
+                    "request": { "method": "GET", "header": [], "url": "{{base_url}}/users" }
+                },
+                {
+                    "name": "Admin",
+                    "item": [
+                        {
+                            "name": "Delete user",
+                            "request": { "method": "DELETE", "header": [], "url": "{{base_url}}/users/:id" }
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        let parse = || serde_json::from_str::<crate::postman::PostmanCollection>(json).unwrap();
 
-    let client = state.sync_client.lock().await;
-    let is_authenticated = client.is_authenticated();
+        let first = merge_postman_import(&state, parse(), target.id)
+            .await
+            .unwrap();
+        assert_eq!((first.created, first.updated), (2, 0));
 
-    Ok(SyncStatus {
-        is_authenticated,
-        unsynced_collections_count: unsynced_collections.len(),
-        unsynced_requests_count: unsynced_requests.len(),
-        unsynced_environments_count: unsynced_environments.len(),
-        last_sync: None, // You can store this in a separate table if needed
-    })
-}
+        let second = merge_postman_import(&state, parse(), target.id)
+            .await
+            .unwrap();
+        assert_eq!((second.created, second.updated), (0, 2));
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct SyncStatus {
-    pub is_authenticated: bool,
-    pub unsynced_collections_count: usize,
-    pub unsynced_requests_count: usize,
-    pub unsynced_environments_count: usize,
-    pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
-}
+        assert_eq!(state.db.get_all_requests().await.unwrap().len(), 2);
+        assert_eq!(state.db.get_collections().await.unwrap().len(), 2);
+    }
 
-#[tauri::command]
-pub async fn extract_path_params(url: String) -> Result<Vec<String>, String> {
-    Ok(crate::http::extract_path_parameters(&url))
+    #[tokio::test]
+    async fn test_merge_import_only_matches_requests_in_the_same_folder() {
+        let state = test_state().await;
+        let target = Collection::new("Existing".to_string(), None);
+        state.db.create_collection(&target).await.unwrap();
+        let billing = Collection::new_with_parent("Billing".to_string(), None, Some(target.id));
+        state.db.create_collection(&billing).await.unwrap();
+        let mut local = HttpRequest::new(
+            "Billing users".to_string(),
+            HttpMethod::GET,
+            "{{base_url}}/users".to_string(),
+        );
+        local.collection_id = Some(billing.id);
+        let local = state.db.save_request(&local).await.unwrap();
+
+        let json = r#"{
+            "info": { "name": "Users" },
+            "item": [
+                {
+                    "name": "Admin",
+                    "item": [
+                        {
+                            "name": "Admin users",
+                            "request": { "method": "GET", "header": [], "url": "{{base_url}}/users" }
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        let postman = serde_json::from_str::<crate::postman::PostmanCollection>(json).unwrap();
+        let result = merge_postman_import(&state, postman, target.id)
+            .await
+            .unwrap();
+        assert_eq!((result.created, result.updated), (1, 0));
+
+        let stored = state.db.get_request(local.id.unwrap()).await.unwrap();
+        assert_eq!(stored.unwrap().name, "Billing users");
+        let requests = state.db.get_all_requests().await.unwrap();
+        let admin = requests.iter().find(|r| r.name == "Admin users").unwrap();
+        assert_ne!(admin.collection_id, Some(billing.id));
+    }
 }
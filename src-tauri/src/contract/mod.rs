@@ -0,0 +1,444 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::models::{ContractResult, ResponseExtractor, SavedResponse, SuccessCriteria};
+
+// One step of a JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSelector {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+// One step of a concrete location inside a JSON document
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+// Parses the JSONPath subset used for ignore paths: `$`, `.key`, `['key']`, `[0]`,
+// `.*` and `[*]`
+pub fn parse_json_path(path: &str) -> Result<Vec<PathSelector>> {
+    let rest = path
+        .trim()
+        .strip_prefix('$')
+        .ok_or_else(|| anyhow::anyhow!("JSONPath '{}' must start with '$'", path))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut selectors = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                let key: String = chars[start..end].iter().collect();
+                if key.is_empty() {
+                    return Err(anyhow::anyhow!("Empty key in JSONPath '{}'", path));
+                }
+                selectors.push(if key == "*" {
+                    PathSelector::Wildcard
+                } else {
+                    PathSelector::Key(key)
+                });
+                i = end;
+            }
+            '[' => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|offset| i + offset)
+                    .ok_or_else(|| anyhow::anyhow!("Unclosed '[' in JSONPath '{}'", path))?;
+                let inner: String = chars[i + 1..end].iter().collect();
+                let inner = inner.trim();
+                selectors.push(if inner == "*" {
+                    PathSelector::Wildcard
+                } else if let Ok(index) = inner.parse::<usize>() {
+                    PathSelector::Index(index)
+                } else {
+                    let key = inner.trim_matches(|c| c == '\'' || c == '"');
+                    PathSelector::Key(key.to_string())
+                });
+                i = end + 1;
+            }
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unexpected '{}' in JSONPath '{}'",
+                    other,
+                    path
+                ))
+            }
+        }
+    }
+
+    Ok(selectors)
+}
+
+// Every value the parsed path selects in the document
+pub fn select_json_path<'a>(value: &'a Value, selectors: &[PathSelector]) -> Vec<&'a Value> {
+    let mut current = vec![value];
+
+    for selector in selectors {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (selector, value) {
+                    (PathSelector::Key(key), Value::Object(map)) => {
+                        map.get(key).into_iter().collect()
+                    }
+                    (PathSelector::Index(index), Value::Array(items)) => {
+                        items.get(*index).into_iter().collect()
+                    }
+                    (PathSelector::Wildcard, Value::Object(map)) => map.values().collect(),
+                    (PathSelector::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+
+    current
+}
+
+// Overwrites every value the parsed path selects with `replacement`
+pub fn replace_json_path(value: &mut Value, selectors: &[PathSelector], replacement: &Value) {
+    let Some((selector, rest)) = selectors.split_first() else {
+        *value = replacement.clone();
+        return;
+    };
+
+    match (selector, value) {
+        (PathSelector::Key(key), Value::Object(map)) => {
+            if let Some(child) = map.get_mut(key) {
+                replace_json_path(child, rest, replacement);
+            }
+        }
+        (PathSelector::Index(index), Value::Array(items)) => {
+            if let Some(child) = items.get_mut(*index) {
+                replace_json_path(child, rest, replacement);
+            }
+        }
+        (PathSelector::Wildcard, Value::Object(map)) => {
+            for child in map.values_mut() {
+                replace_json_path(child, rest, replacement);
+            }
+        }
+        (PathSelector::Wildcard, Value::Array(items)) => {
+            for child in items.iter_mut() {
+                replace_json_path(child, rest, replacement);
+            }
+        }
+        _ => {}
+    }
+}
+
+// A JSONPath criterion passes when the path selects at least one value and all of them
+// equal the expected value
+pub fn meets_success_criteria(criteria: &SuccessCriteria, status: u16, body: &str) -> bool {
+    match criteria {
+        SuccessCriteria::StatusRange(min, max) => (*min..=*max).contains(&status),
+        SuccessCriteria::JsonPathEquals { path, value } => {
+            let (Ok(selectors), Ok(document)) =
+                (parse_json_path(path), serde_json::from_str::<Value>(body))
+            else {
+                return false;
+            };
+            let selected = select_json_path(&document, &selectors);
+            !selected.is_empty() && selected.iter().all(|selected| *selected == value)
+        }
+    }
+}
+
+fn selector_matches(selector: &PathSelector, segment: &PathSegment) -> bool {
+    match (selector, segment) {
+        (PathSelector::Wildcard, _) => true,
+        (PathSelector::Key(a), PathSegment::Key(b)) => a == b,
+        (PathSelector::Index(a), PathSegment::Index(b)) => a == b,
+        _ => false,
+    }
+}
+
+// True when the location is the path itself or somewhere beneath it
+pub fn path_is_covered(pattern: &[PathSelector], location: &[PathSegment]) -> bool {
+    pattern.len() <= location.len()
+        && pattern
+            .iter()
+            .zip(location)
+            .all(|(selector, segment)| selector_matches(selector, segment))
+}
+
+pub fn format_path(location: &[PathSegment]) -> String {
+    let mut path = "$".to_string();
+    for segment in location {
+        match segment {
+            PathSegment::Key(key) => {
+                path.push('.');
+                path.push_str(key);
+            }
+            PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+// Describes every difference between two JSON values, skipping ignored locations
+pub fn diff_json(expected: &Value, actual: &Value, ignore: &[Vec<PathSelector>]) -> Vec<String> {
+    let mut differences = Vec::new();
+    diff_at(expected, actual, &mut Vec::new(), ignore, &mut differences);
+    differences
+}
+
+fn diff_at(
+    expected: &Value,
+    actual: &Value,
+    location: &mut Vec<PathSegment>,
+    ignore: &[Vec<PathSelector>],
+    differences: &mut Vec<String>,
+) {
+    if is_ignored(location, ignore) {
+        return;
+    }
+
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected {
+                location.push(PathSegment::Key(key.clone()));
+                match actual.get(key) {
+                    Some(actual_value) => {
+                        diff_at(expected_value, actual_value, location, ignore, differences)
+                    }
+                    None if !is_ignored(location, ignore) => {
+                        differences.push(format!("{}: missing", format_path(location)))
+                    }
+                    None => {}
+                }
+                location.pop();
+            }
+            for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                location.push(PathSegment::Key(key.clone()));
+                if !is_ignored(location, ignore) {
+                    differences.push(format!("{}: unexpected field", format_path(location)));
+                }
+                location.pop();
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                differences.push(format!(
+                    "{}: expected {} items, got {}",
+                    format_path(location),
+                    expected.len(),
+                    actual.len()
+                ));
+            }
+            for (index, (expected_item, actual_item)) in expected.iter().zip(actual).enumerate() {
+                location.push(PathSegment::Index(index));
+                diff_at(expected_item, actual_item, location, ignore, differences);
+                location.pop();
+            }
+        }
+        _ if expected != actual => differences.push(format!(
+            "{}: expected {}, got {}",
+            format_path(location),
+            expected,
+            actual
+        )),
+        _ => {}
+    }
+}
+
+fn is_ignored(location: &[PathSegment], ignore: &[Vec<PathSelector>]) -> bool {
+    ignore
+        .iter()
+        .any(|pattern| path_is_covered(pattern, location))
+}
+
+// Keys whose string values are usually worth reusing in later requests
+fn is_capturable_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    key.contains("token") || key.contains("session") || key.ends_with("id")
+}
+
+// snake_case variable name for a key; a bare `id` borrows its parent's name
+fn variable_name(key: &str, parent: Option<&str>) -> String {
+    let mut name = String::new();
+    for (i, c) in key.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(if c.is_ascii_alphanumeric() {
+            c.to_ascii_lowercase()
+        } else {
+            '_'
+        });
+    }
+
+    match parent {
+        Some(parent) if name == "id" => format!("{}_id", variable_name(parent, None)),
+        _ => name,
+    }
+}
+
+// Suggests extractors for string leaves under id-, token- or session-like keys
+pub fn suggest_extractors(document: &Value) -> Vec<ResponseExtractor> {
+    let mut suggestions = Vec::new();
+    collect_suggestions(document, &mut Vec::new(), &mut suggestions);
+    suggestions
+}
+
+fn collect_suggestions(
+    value: &Value,
+    location: &mut Vec<PathSegment>,
+    suggestions: &mut Vec<ResponseExtractor>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                location.push(PathSegment::Key(key.clone()));
+                if child.is_string() && is_capturable_key(key) {
+                    let parent = location
+                        .iter()
+                        .rev()
+                        .skip(1)
+                        .find_map(|segment| match segment {
+                            PathSegment::Key(key) => Some(key.as_str()),
+                            PathSegment::Index(_) => None,
+                        });
+                    let base = variable_name(key, parent);
+                    let mut variable = base.clone();
+                    let mut n = 2;
+                    while suggestions.iter().any(|s| s.variable == variable) {
+                        variable = format!("{}_{}", base, n);
+                        n += 1;
+                    }
+                    suggestions.push(ResponseExtractor {
+                        variable,
+                        path: format_path(location),
+                    });
+                } else {
+                    collect_suggestions(child, location, suggestions);
+                }
+                location.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                location.push(PathSegment::Index(index));
+                collect_suggestions(item, location, suggestions);
+                location.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn header_value<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+// Compares a live response with a saved example: status, the selected headers and the body
+// (structurally when both sides are JSON, verbatim otherwise)
+pub fn compare_to_example(
+    example: &SavedResponse,
+    status: u16,
+    headers: &HashMap<String, String>,
+    body: &str,
+    compare_headers: &[String],
+    ignore_paths: &[String],
+) -> Result<ContractResult> {
+    let ignore = ignore_paths
+        .iter()
+        .map(|path| parse_json_path(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut differences = Vec::new();
+    if example.status != status {
+        differences.push(format!(
+            "status: expected {}, got {}",
+            example.status, status
+        ));
+    }
+
+    for name in compare_headers {
+        let expected = header_value(&example.headers, name);
+        let actual = header_value(headers, name);
+        if expected != actual {
+            differences.push(format!(
+                "header {}: expected {:?}, got {:?}",
+                name, expected, actual
+            ));
+        }
+    }
+
+    match (
+        serde_json::from_str::<Value>(&example.body),
+        serde_json::from_str::<Value>(body),
+    ) {
+        (Ok(expected), Ok(actual)) => differences.extend(diff_json(&expected, &actual, &ignore)),
+        (Ok(_), Err(_)) => differences.push("body: expected JSON".to_string()),
+        _ if example.body != body => differences.push("body: content differs".to_string()),
+        _ => {}
+    }
+
+    Ok(ContractResult {
+        matches: differences.is_empty(),
+        differences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_json_ignores_wildcard_paths() {
+        let expected = json!({
+            "items": [{ "id": 1, "updated_at": "2024-01-01" }, { "id": 2, "updated_at": "2024-01-02" }],
+            "total": 2
+        });
+        let actual = json!({
+            "items": [{ "id": 1, "updated_at": "2025-06-01" }, { "id": 3, "updated_at": "2025-06-02" }],
+            "total": 2
+        });
+        let ignore = vec![parse_json_path("$.items[*].updated_at").unwrap()];
+
+        assert_eq!(
+            diff_json(&expected, &actual, &ignore),
+            vec!["$.items[1].id: expected 2, got 3".to_string()]
+        );
+        assert!(parse_json_path("items.id").is_err());
+    }
+
+    #[test]
+    fn test_suggest_extractors_for_login_response() {
+        let response = json!({
+            "access_token": "eyJhbGciOi...",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "user": { "id": "u_123", "email": "dev@example.com" }
+        });
+
+        let suggestions = suggest_extractors(&response);
+
+        assert!(suggestions.contains(&ResponseExtractor {
+            variable: "access_token".to_string(),
+            path: "$.access_token".to_string(),
+        }));
+        assert!(suggestions.contains(&ResponseExtractor {
+            variable: "user_id".to_string(),
+            path: "$.user.id".to_string(),
+        }));
+        assert!(!suggestions.iter().any(|s| s.path == "$.user.email"));
+        assert!(!suggestions.iter().any(|s| s.path == "$.expires_in"));
+    }
+}
@@ -1,10 +1,68 @@
 use anyhow::Result;
 use chrono::Utc;
+use serde::de::DeserializeOwned;
+use sled::transaction::{TransactionError, Transactional};
 use sled::{Db, Tree};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::models::*;
 
+// The active environment is tracked in the config tree instead of a flag on every record
+const ACTIVE_ENVIRONMENT_KEY: &str = "active_environment_id";
+const ACTIVE_ENVIRONMENT_MIGRATED_KEY: &str = "active_environment_migrated";
+const TIMEOUT_PRESETS_KEY: &str = "timeout_presets";
+const FORMAT_PREFERENCES_KEY: &str = "format_preferences";
+// Cached number of requests directly in a collection, followed by the collection id
+const REQUEST_COUNT_PREFIX: &str = "request_count:";
+// Saved cookie jars, keyed by scope
+const COOKIE_JAR_PREFIX: &str = "cookie_jar:";
+// Config entries tied to this machine or cached in AppState at startup, which a workspace
+// import must not overwrite
+const MACHINE_LOCAL_CONFIG_KEYS: &[&str] = &[
+    ACTIVE_ENVIRONMENT_KEY,
+    ACTIVE_ENVIRONMENT_MIGRATED_KEY,
+    "custom_ca_bundle_path",
+    "request_log_path",
+    "proxy_settings",
+];
+
+fn quarantine_key(tree: &[u8], key: &[u8]) -> String {
+    format!(
+        "{}/{}",
+        String::from_utf8_lossy(tree),
+        String::from_utf8_lossy(key)
+    )
+}
+
+// Whether a stored value still fails to parse as the record type its tree holds
+fn is_corrupt_record(tree: &str, value: &[u8]) -> Result<bool> {
+    Ok(match tree {
+        "collections" => serde_json::from_slice::<Collection>(value).is_err(),
+        "requests" => serde_json::from_slice::<HttpRequest>(value).is_err(),
+        "environments" => serde_json::from_slice::<Environment>(value).is_err(),
+        "auth_presets" => serde_json::from_slice::<AuthPreset>(value).is_err(),
+        "history" => serde_json::from_slice::<RequestHistory>(value).is_err(),
+        _ => return Err(anyhow::anyhow!("Unknown tree '{}'", tree)),
+    })
+}
+
+fn is_machine_local_config(key: &str) -> bool {
+    MACHINE_LOCAL_CONFIG_KEYS.contains(&key) || key.starts_with(COOKIE_JAR_PREFIX)
+}
+
+// Synced auth carries no OAuth2 token, so this device's token is kept
+fn merge_pulled_auth(local: Option<AuthConfig>, pulled: Option<AuthConfig>) -> Option<AuthConfig> {
+    let local_token = local
+        .and_then(|auth| auth.oauth2)
+        .and_then(|config| config.token);
+    let mut auth = pulled;
+    if let Some(config) = auth.as_mut().and_then(|auth| auth.oauth2.as_mut()) {
+        config.token = config.token.take().or(local_token);
+    }
+    auth
+}
+
 pub struct Database {
     db: Db,
     collections: Tree,
@@ -12,6 +70,10 @@ pub struct Database {
     environments: Tree,
     history: Tree,
     config: Tree,
+    quarantine: Tree,   // keys of records that failed to deserialize
+    auth_presets: Tree, // local only, never synced
+    drafts: Tree,       // unsaved editor state, keyed by request id; never synced
+    view_state: Tree,   // frontend view state (tabs, filters), keyed by request id; never synced
 }
 
 impl Database {
@@ -23,6 +85,10 @@ impl Database {
         let environments = db.open_tree("environments")?;
         let history = db.open_tree("history")?;
         let config = db.open_tree("config")?;
+        let quarantine = db.open_tree("quarantine")?;
+        let auth_presets = db.open_tree("auth_presets")?;
+        let drafts = db.open_tree("drafts")?;
+        let view_state = db.open_tree("view_state")?;
 
         Ok(Self {
             db,
@@ -31,6 +97,10 @@ impl Database {
             environments,
             history,
             config,
+            quarantine,
+            auth_presets,
+            drafts,
+            view_state,
         })
     }
 
@@ -42,6 +112,10 @@ impl Database {
         let environments = db.open_tree("environments")?;
         let history = db.open_tree("history")?;
         let config = db.open_tree("config")?;
+        let quarantine = db.open_tree("quarantine")?;
+        let auth_presets = db.open_tree("auth_presets")?;
+        let drafts = db.open_tree("drafts")?;
+        let view_state = db.open_tree("view_state")?;
 
         Ok(Self {
             db,
@@ -50,6 +124,10 @@ impl Database {
             environments,
             history,
             config,
+            quarantine,
+            auth_presets,
+            drafts,
+            view_state,
         })
     }
 
@@ -62,6 +140,10 @@ impl Database {
         let environments = db.open_tree("environments")?;
         let history = db.open_tree("history")?;
         let config = db.open_tree("config")?;
+        let quarantine = db.open_tree("quarantine")?;
+        let auth_presets = db.open_tree("auth_presets")?;
+        let drafts = db.open_tree("drafts")?;
+        let view_state = db.open_tree("view_state")?;
 
         Ok(Self {
             db,
@@ -70,26 +152,109 @@ impl Database {
             environments,
             history,
             config,
+            quarantine,
+            auth_presets,
+            drafts,
+            view_state,
         })
     }
 
+    // Deserializes every record in a tree. Records that fail to parse are skipped and
+    // quarantined rather than failing the whole list.
+    fn load_records<T: DeserializeOwned>(&self, tree: &Tree) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+
+        for item in tree.iter() {
+            let (key, value) = item?;
+            match serde_json::from_slice(&value) {
+                Ok(record) => records.push(record),
+                Err(e) => self.quarantine_record(tree, &key, &e.to_string())?,
+            }
+        }
+
+        Ok(records)
+    }
+
+    // Writes raw bytes into a tree, bypassing serialization
+    #[cfg(test)]
+    pub(crate) fn insert_raw_record(&self, tree: &str, key: &str, value: &[u8]) {
+        self.db.open_tree(tree).unwrap().insert(key, value).unwrap();
+    }
+
+    fn quarantine_record(&self, tree: &Tree, key: &[u8], error: &str) -> Result<()> {
+        let record = CorruptRecord {
+            tree: String::from_utf8_lossy(&tree.name()).to_string(),
+            key: String::from_utf8_lossy(key).to_string(),
+            error: error.to_string(),
+        };
+        let quarantine_key = quarantine_key(&tree.name(), key);
+
+        if !self.quarantine.contains_key(&quarantine_key)? {
+            eprintln!(
+                "Skipping corrupt record {} in '{}': {}",
+                record.key, record.tree, record.error
+            );
+            self.quarantine
+                .insert(quarantine_key, serde_json::to_vec(&record)?)?;
+        }
+        Ok(())
+    }
+
+    // Writes a record. A key written with a valid value is no longer corrupt, so any
+    // quarantine entry for it goes too.
+    fn put_record(
+        &self,
+        tree: &Tree,
+        key: impl AsRef<[u8]>,
+        value: impl Into<sled::IVec>,
+    ) -> Result<()> {
+        let key = key.as_ref();
+        tree.insert(key, value)?;
+        self.quarantine.remove(quarantine_key(&tree.name(), key))?;
+        Ok(())
+    }
+
+    pub async fn get_corrupt_records(&self) -> Result<Vec<CorruptRecord>> {
+        let mut records = Vec::new();
+        for item in self.quarantine.iter() {
+            let (_, value) = item?;
+            records.push(serde_json::from_slice(&value)?);
+        }
+        Ok(records)
+    }
+
+    // Removes a quarantined record from both its tree and the quarantine. A record that
+    // parses again (e.g. rewritten outside the app) is kept; only its entry is dropped.
+    pub async fn delete_corrupt_record(&self, tree: &str, key: &str) -> Result<()> {
+        let quarantine_key = quarantine_key(tree.as_bytes(), key.as_bytes());
+        if !self.quarantine.contains_key(&quarantine_key)? {
+            return Err(anyhow::anyhow!("Record is not quarantined"));
+        }
+
+        let records = self.db.open_tree(tree)?;
+        if let Some(value) = records.get(key)? {
+            if is_corrupt_record(tree, &value)? {
+                records.remove(key)?;
+            } else {
+                eprintln!("Record {} in '{}' parses again, keeping it", key, tree);
+            }
+        }
+        self.quarantine.remove(quarantine_key)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
     // Collection operations
     pub async fn create_collection(&self, collection: &Collection) -> Result<()> {
         let key = collection.id.to_string();
         let value = serde_json::to_vec(collection)?;
-        self.collections.insert(key, value)?;
+        self.put_record(&self.collections, key, value)?;
         self.db.flush()?;
         Ok(())
     }
 
     pub async fn get_collections(&self) -> Result<Vec<Collection>> {
-        let mut collections = Vec::new();
-
-        for item in self.collections.iter() {
-            let (_, value) = item?;
-            let collection: Collection = serde_json::from_slice(&value)?;
-            collections.push(collection);
-        }
+        let mut collections: Vec<Collection> = self.load_records(&self.collections)?;
 
         // Sort by created_at desc
         collections.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -134,12 +299,16 @@ impl Database {
             }
 
             for key in request_keys_to_remove {
+                self.drafts.remove(&key)?;
+                self.view_state.remove(&key)?;
                 self.requests.remove(key)?;
             }
 
             // Finally, delete this collection itself
             let key = id.to_string();
             self.collections.remove(key)?;
+            self.config
+                .remove(format!("{}{}", REQUEST_COUNT_PREFIX, id))?;
 
             Ok(())
         })
@@ -161,7 +330,7 @@ impl Database {
 
             // Save back to database
             let updated_value = serde_json::to_vec(&collection)?;
-            self.collections.insert(key, updated_value)?;
+            self.put_record(&self.collections, key, updated_value)?;
             self.db.flush()?;
         }
 
@@ -183,13 +352,57 @@ impl Database {
 
             // Save back to database
             let updated_value = serde_json::to_vec(&collection)?;
-            self.collections.insert(key, updated_value)?;
+            self.put_record(&self.collections, key, updated_value)?;
             self.db.flush()?;
         }
 
         Ok(())
     }
 
+    // Stores an OAuth2 token on the collection's auth. Tokens don't sync, so the collection
+    // isn't marked as changed.
+    pub async fn set_collection_oauth2_token(&self, id: Uuid, token: OAuth2Token) -> Result<()> {
+        let key = id.to_string();
+        let Some(value) = self.collections.get(&key)? else {
+            return Err(anyhow::anyhow!("Collection not found"));
+        };
+        let mut collection: Collection = serde_json::from_slice(&value)?;
+        if let Some(config) = collection
+            .auth
+            .as_mut()
+            .and_then(|auth| auth.oauth2.as_mut())
+        {
+            config.token = Some(token);
+        }
+
+        self.put_record(&self.collections, key, serde_json::to_vec(&collection)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn update_collection_defaults(
+        &self,
+        collection_id: Uuid,
+        default_timeout: Option<u64>,
+        default_retries: Option<u32>,
+    ) -> Result<()> {
+        let key = collection_id.to_string();
+        let Some(value) = self.collections.get(&key)? else {
+            return Err(anyhow::anyhow!("Collection not found"));
+        };
+        let mut collection: Collection = serde_json::from_slice(&value)?;
+
+        collection.default_timeout = default_timeout;
+        collection.default_retries = default_retries;
+        collection.updated_at = Utc::now();
+        collection.version += 1;
+        collection.synced = false;
+
+        self.put_record(&self.collections, key, serde_json::to_vec(&collection)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
     pub async fn update_collection_name(&self, collection_id: Uuid, name: String) -> Result<()> {
         let key = collection_id.to_string();
         if let Some(value) = self.collections.get(&key)? {
@@ -201,7 +414,7 @@ impl Database {
 
             // Save back to database
             let updated_value = serde_json::to_vec(&collection)?;
-            self.collections.insert(key, updated_value)?;
+            self.put_record(&self.collections, key, updated_value)?;
             self.db.flush()?;
         }
 
@@ -212,7 +425,50 @@ impl Database {
     pub async fn save_request(&self, request: &HttpRequest) -> Result<HttpRequest> {
         let id = request.id.unwrap_or_else(Uuid::new_v4);
         let now = Utc::now();
-        let created_at = request.created_at.unwrap_or(now);
+
+        // Keep the last-run summary when the editor saves without it
+        let existing = self.get_request(id).await?;
+        // Callers send a fresh timestamp on every save, so the stored one wins
+        let created_at = existing
+            .as_ref()
+            .and_then(|e| e.created_at)
+            .or(request.created_at)
+            .unwrap_or(now);
+        let previous_collection = existing.as_ref().map(|e| e.collection_id);
+        let existing_run = match &existing {
+            Some(existing) => (
+                existing.last_status,
+                existing.last_response_time,
+                existing.last_run_at,
+            ),
+            None => (None, None, None),
+        };
+
+        // Examples, auth, alias, dependencies and environment exclusions are edited through
+        // their own commands, so keep the stored ones
+        let auth = request
+            .auth
+            .clone()
+            .or_else(|| existing.as_ref().and_then(|e| e.auth.clone()));
+        let alias = request
+            .alias
+            .clone()
+            .or_else(|| existing.as_ref().and_then(|e| e.alias.clone()));
+        let depends_on = match &existing {
+            Some(existing) if request.depends_on.is_empty() => existing.depends_on.clone(),
+            _ => request.depends_on.clone(),
+        };
+        let disabled_in_environments = match &existing {
+            Some(existing) if request.disabled_in_environments.is_empty() => {
+                existing.disabled_in_environments.clone()
+            }
+            _ => request.disabled_in_environments.clone(),
+        };
+        let examples = if request.examples.is_empty() {
+            existing.map(|e| e.examples).unwrap_or_default()
+        } else {
+            request.examples.clone()
+        };
 
         let saved_request = HttpRequest {
             id: Some(id),
@@ -222,29 +478,293 @@ impl Database {
             headers: request.headers.clone(),
             body: request.body.clone(),
             path_params: request.path_params.clone(),
+            query_params: request.query_params.clone(),
             collection_id: request.collection_id,
             created_at: Some(created_at),
             updated_at: Some(now),
             synced: false,
             version: request.version + 1,
             cloud_id: request.cloud_id.clone(),
+            last_status: request.last_status.or(existing_run.0),
+            last_response_time: request.last_response_time.or(existing_run.1),
+            last_run_at: request.last_run_at.or(existing_run.2),
+            examples,
+            success_criteria: request.success_criteria.clone(),
+            auth,
+            alias,
+            depends_on,
+            depends_on_cloud_ids: Vec::new(),
+            disabled_in_environments,
         };
 
         let key = id.to_string();
         let value = serde_json::to_vec(&saved_request)?;
-        self.requests.insert(key, value)?;
+        self.put_record(&self.requests, key, value)?;
+        match previous_collection {
+            Some(previous) => self.move_request_count(previous, request.collection_id)?,
+            None => self.adjust_request_count(request.collection_id, 1)?,
+        }
         self.db.flush()?;
 
         Ok(saved_request)
     }
 
+    // Counts are only adjusted when cached; missing ones are rebuilt on the next read
+    fn adjust_request_count(&self, collection_id: Option<Uuid>, delta: i64) -> Result<()> {
+        let Some(collection_id) = collection_id else {
+            return Ok(());
+        };
+        let key = format!("{}{}", REQUEST_COUNT_PREFIX, collection_id);
+        let cached = self
+            .config
+            .get(&key)?
+            .and_then(|value| String::from_utf8(value.to_vec()).ok())
+            .and_then(|value| value.parse::<i64>().ok());
+        match cached {
+            Some(count) => {
+                let count = (count + delta).max(0);
+                self.config.insert(key, count.to_string().as_bytes())?;
+            }
+            None => {
+                self.config.remove(key)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn move_request_count(&self, from: Option<Uuid>, to: Option<Uuid>) -> Result<()> {
+        if from != to {
+            self.adjust_request_count(from, -1)?;
+            self.adjust_request_count(to, 1)?;
+        }
+        Ok(())
+    }
+
+    // Number of requests directly inside each collection, served from the cache and
+    // rebuilt with a single scan when any collection's count is missing
+    pub async fn get_collection_request_counts(&self) -> Result<HashMap<Uuid, usize>> {
+        let collections = self.get_collections().await?;
+        let mut counts = HashMap::new();
+        for collection in &collections {
+            let key = format!("{}{}", REQUEST_COUNT_PREFIX, collection.id);
+            let cached = self
+                .config
+                .get(&key)?
+                .and_then(|value| String::from_utf8(value.to_vec()).ok())
+                .and_then(|value| value.parse::<usize>().ok());
+            match cached {
+                Some(count) => {
+                    counts.insert(collection.id, count);
+                }
+                None => break,
+            }
+        }
+        if counts.len() == collections.len() {
+            return Ok(counts);
+        }
+
+        let mut counts: HashMap<Uuid, usize> = collections.iter().map(|c| (c.id, 0)).collect();
+        for request in self.get_all_requests().await? {
+            if let Some(count) = request
+                .collection_id
+                .and_then(|collection_id| counts.get_mut(&collection_id))
+            {
+                *count += 1;
+            }
+        }
+        for (collection_id, count) in &counts {
+            self.config.insert(
+                format!("{}{}", REQUEST_COUNT_PREFIX, collection_id),
+                count.to_string().as_bytes(),
+            )?;
+        }
+        self.db.flush()?;
+        Ok(counts)
+    }
+
+    // Run metadata only: version and synced are left alone so it never triggers a sync
+    pub async fn record_request_run(
+        &self,
+        id: Uuid,
+        status: u16,
+        response_time: u64,
+    ) -> Result<()> {
+        let key = id.to_string();
+        if let Some(value) = self.requests.get(&key)? {
+            let mut request: HttpRequest = serde_json::from_slice(&value)?;
+            request.last_status = Some(status);
+            request.last_response_time = Some(response_time);
+            request.last_run_at = Some(Utc::now());
+
+            let updated_value = serde_json::to_vec(&request)?;
+            self.put_record(&self.requests, key, updated_value)?;
+            self.db.flush()?;
+        }
+        Ok(())
+    }
+
+    pub async fn set_request_examples(
+        &self,
+        id: Uuid,
+        examples: Vec<SavedResponse>,
+    ) -> Result<HttpRequest> {
+        let key = id.to_string();
+        let mut request: HttpRequest = match self.requests.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Request not found")),
+        };
+
+        request.examples = examples;
+        request.updated_at = Some(Utc::now());
+        request.version += 1;
+        request.synced = false;
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(request)
+    }
+
+    pub async fn set_request_auth(&self, id: Uuid, auth: Option<AuthConfig>) -> Result<()> {
+        let key = id.to_string();
+        let mut request: HttpRequest = match self.requests.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Request not found")),
+        };
+
+        request.auth = auth;
+        request.updated_at = Some(Utc::now());
+        request.version += 1;
+        request.synced = false;
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Like set_collection_oauth2_token, for a request's own auth
+    pub async fn set_request_oauth2_token(&self, id: Uuid, token: OAuth2Token) -> Result<()> {
+        let key = id.to_string();
+        let Some(value) = self.requests.get(&key)? else {
+            return Err(anyhow::anyhow!("Request not found"));
+        };
+        let mut request: HttpRequest = serde_json::from_slice(&value)?;
+        if let Some(config) = request.auth.as_mut().and_then(|auth| auth.oauth2.as_mut()) {
+            config.token = Some(token);
+        }
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Aliases are compared case-insensitively; `None` clears the request's alias
+    pub async fn set_request_alias(&self, id: Uuid, alias: Option<String>) -> Result<()> {
+        let key = id.to_string();
+        let mut request: HttpRequest = match self.requests.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Request not found")),
+        };
+
+        if let Some(alias) = &alias {
+            if let Some(conflict) = self.find_request_by_alias(alias).await? {
+                if conflict.id != Some(id) {
+                    return Err(anyhow::anyhow!(
+                        "Alias '{}' is already used by request '{}' ({})",
+                        alias,
+                        conflict.name,
+                        conflict.id.map(|id| id.to_string()).unwrap_or_default()
+                    ));
+                }
+            }
+        }
+
+        request.alias = alias;
+        request.updated_at = Some(Utc::now());
+        request.version += 1;
+        request.synced = false;
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn find_request_by_alias(&self, alias: &str) -> Result<Option<HttpRequest>> {
+        Ok(self.get_all_requests().await?.into_iter().find(|request| {
+            request
+                .alias
+                .as_deref()
+                .is_some_and(|existing| existing.eq_ignore_ascii_case(alias))
+        }))
+    }
+
+    // Cycles are only reported when the request is run, since syncs can introduce them too
+    pub async fn set_request_dependencies(&self, id: Uuid, depends_on: Vec<Uuid>) -> Result<()> {
+        let key = id.to_string();
+        let mut request: HttpRequest = match self.requests.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Request not found")),
+        };
+
+        for dependency in &depends_on {
+            if *dependency == id {
+                return Err(anyhow::anyhow!("A request cannot depend on itself"));
+            }
+            if !self.requests.contains_key(dependency.to_string())? {
+                return Err(anyhow::anyhow!("Dependency {} not found", dependency));
+            }
+        }
+
+        request.depends_on = depends_on;
+        request.updated_at = Some(Utc::now());
+        request.version += 1;
+        request.synced = false;
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn set_request_environment_exclusions(
+        &self,
+        id: Uuid,
+        environments: Vec<String>,
+    ) -> Result<()> {
+        let key = id.to_string();
+        let mut request: HttpRequest = match self.requests.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Request not found")),
+        };
+
+        request.disabled_in_environments = environments
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        request.updated_at = Some(Utc::now());
+        request.version += 1;
+        request.synced = false;
+
+        self.put_record(&self.requests, key, serde_json::to_vec(&request)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_request(&self, id: Uuid) -> Result<Option<HttpRequest>> {
+        match self.requests.get(id.to_string())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Every request regardless of collection (get_requests(None) only returns unfiled ones)
+    pub async fn get_all_requests(&self) -> Result<Vec<HttpRequest>> {
+        self.load_records(&self.requests)
+    }
+
     pub async fn get_requests(&self, collection_id: Option<Uuid>) -> Result<Vec<HttpRequest>> {
         let mut requests = Vec::new();
 
-        for item in self.requests.iter() {
-            let (_, value) = item?;
-            let request: HttpRequest = serde_json::from_slice(&value)?;
-
+        for request in self.load_records::<HttpRequest>(&self.requests)? {
             match collection_id {
                 Some(id) => {
                     if request.collection_id == Some(id) {
@@ -275,7 +795,12 @@ impl Database {
 
     pub async fn delete_request(&self, id: Uuid) -> Result<()> {
         let key = id.to_string();
-        self.requests.remove(key)?;
+        self.view_state.remove(&key)?;
+        self.drafts.remove(&key)?;
+        if let Some(value) = self.requests.remove(key)? {
+            let request: HttpRequest = serde_json::from_slice(&value)?;
+            self.adjust_request_count(request.collection_id, -1)?;
+        }
         self.db.flush()?;
         Ok(())
     }
@@ -287,12 +812,13 @@ impl Database {
             let mut request: HttpRequest = serde_json::from_slice(&value)?;
 
             // Update the collection_id
+            self.move_request_count(request.collection_id, Some(new_collection_id))?;
             request.collection_id = Some(new_collection_id);
             request.updated_at = Some(Utc::now());
 
             // Save back to database
             let updated_value = serde_json::to_vec(&request)?;
-            self.requests.insert(key, updated_value)?;
+            self.put_record(&self.requests, key, updated_value)?;
             self.db.flush()?;
         }
 
@@ -310,12 +836,13 @@ impl Database {
             let mut request: HttpRequest = serde_json::from_slice(&value)?;
 
             // Update the collection_id (can be None for root)
+            self.move_request_count(request.collection_id, new_collection_id)?;
             request.collection_id = new_collection_id;
             request.updated_at = Some(Utc::now());
 
             // Save back to database
             let updated_value = serde_json::to_vec(&request)?;
-            self.requests.insert(key, updated_value)?;
+            self.put_record(&self.requests, key, updated_value)?;
             self.db.flush()?;
         }
 
@@ -333,7 +860,7 @@ impl Database {
 
             // Save back to database
             let updated_value = serde_json::to_vec(&request)?;
-            self.requests.insert(key, updated_value)?;
+            self.put_record(&self.requests, key, updated_value)?;
             self.db.flush()?;
         }
 
@@ -344,18 +871,16 @@ impl Database {
     pub async fn create_environment(&self, environment: &Environment) -> Result<()> {
         let key = environment.id.to_string();
         let value = serde_json::to_vec(environment)?;
-        self.environments.insert(key, value)?;
+        self.put_record(&self.environments, key, value)?;
         self.db.flush()?;
         Ok(())
     }
 
     pub async fn get_environments(&self) -> Result<Vec<Environment>> {
-        let mut environments = Vec::new();
-
-        for item in self.environments.iter() {
-            let (_, value) = item?;
-            let environment: Environment = serde_json::from_slice(&value)?;
-            environments.push(environment);
+        let active_id = self.active_environment_id()?;
+        let mut environments: Vec<Environment> = self.load_records(&self.environments)?;
+        for environment in &mut environments {
+            environment.is_active = Some(environment.id) == active_id;
         }
 
         // Sort by created_at desc
@@ -363,40 +888,63 @@ impl Database {
         Ok(environments)
     }
 
-    pub async fn set_active_environment(&self, id: Option<Uuid>) -> Result<()> {
-        // First, deactivate all environments
-        for item in self.environments.iter() {
-            let (key, value) = item?;
-            let mut environment: Environment = serde_json::from_slice(&value)?;
-            environment.is_active = false;
-            let updated_value = serde_json::to_vec(&environment)?;
-            self.environments.insert(key, updated_value)?;
+    // Reads the active environment id, first migrating databases that stored an
+    // `is_active` flag on each record
+    fn active_environment_id(&self) -> Result<Option<Uuid>> {
+        if !self.config.contains_key(ACTIVE_ENVIRONMENT_MIGRATED_KEY)? {
+            let legacy_active = self
+                .load_records::<Environment>(&self.environments)?
+                .into_iter()
+                .find(|environment| environment.is_active);
+            if let Some(environment) = legacy_active {
+                self.config.insert(
+                    ACTIVE_ENVIRONMENT_KEY,
+                    environment.id.to_string().as_bytes(),
+                )?;
+            }
+            self.config
+                .insert(ACTIVE_ENVIRONMENT_MIGRATED_KEY, "true")?;
         }
 
-        // Then activate the selected one if provided
-        if let Some(env_id) = id {
-            let key = env_id.to_string();
-            if let Some(value) = self.environments.get(&key)? {
-                let mut environment: Environment = serde_json::from_slice(&value)?;
-                environment.is_active = true;
-                let updated_value = serde_json::to_vec(&environment)?;
-                self.environments.insert(key, updated_value)?;
-            }
+        match self.config.get(ACTIVE_ENVIRONMENT_KEY)? {
+            Some(bytes) => Ok(Uuid::parse_str(std::str::from_utf8(&bytes)?).ok()),
+            None => Ok(None),
         }
+    }
+
+    pub async fn set_active_environment(&self, id: Option<Uuid>) -> Result<()> {
+        // Only an existing environment can be activated; anything else clears the selection
+        let id = match id {
+            Some(id) if self.environments.contains_key(id.to_string())? => Some(id),
+            _ => None,
+        };
+
+        match id {
+            Some(id) => self
+                .config
+                .insert(ACTIVE_ENVIRONMENT_KEY, id.to_string().as_bytes())?,
+            None => self.config.remove(ACTIVE_ENVIRONMENT_KEY)?,
+        };
+        self.config
+            .insert(ACTIVE_ENVIRONMENT_MIGRATED_KEY, "true")?;
 
         self.db.flush()?;
         Ok(())
     }
 
     pub async fn get_active_environment(&self) -> Result<Option<Environment>> {
-        for item in self.environments.iter() {
-            let (_, value) = item?;
-            let environment: Environment = serde_json::from_slice(&value)?;
-            if environment.is_active {
-                return Ok(Some(environment));
+        let Some(id) = self.active_environment_id()? else {
+            return Ok(None);
+        };
+
+        match self.environments.get(id.to_string())? {
+            Some(value) => {
+                let mut environment: Environment = serde_json::from_slice(&value)?;
+                environment.is_active = true;
+                Ok(Some(environment))
             }
+            None => Ok(None),
         }
-        Ok(None)
     }
 
     pub async fn update_environment(
@@ -413,10 +961,11 @@ impl Database {
             environment.name = name;
             environment.variables = variables;
             environment.updated_at = Utc::now();
+            environment.is_active = self.active_environment_id()? == Some(id);
 
             // Save back to database
             let updated_value = serde_json::to_vec(&environment)?;
-            self.environments.insert(key, updated_value)?;
+            self.put_record(&self.environments, key, updated_value)?;
             self.db.flush()?;
 
             Ok(environment)
@@ -425,6 +974,28 @@ impl Database {
         }
     }
 
+    pub async fn set_environment_base_url_override(
+        &self,
+        id: Uuid,
+        base_url_override: Option<String>,
+    ) -> Result<Environment> {
+        let key = id.to_string();
+        let mut environment: Environment = match self.environments.get(&key)? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Err(anyhow::anyhow!("Environment not found")),
+        };
+
+        environment.base_url_override = base_url_override;
+        environment.updated_at = Utc::now();
+        environment.version += 1;
+        environment.synced = false;
+        environment.is_active = self.active_environment_id()? == Some(id);
+
+        self.put_record(&self.environments, key, serde_json::to_vec(&environment)?)?;
+        self.db.flush()?;
+        Ok(environment)
+    }
+
     pub async fn delete_environment(&self, id: Uuid) -> Result<()> {
         let key = id.to_string();
         self.environments.remove(key)?;
@@ -432,6 +1003,64 @@ impl Database {
         Ok(())
     }
 
+    // Auth preset operations
+    pub async fn save_auth_preset(&self, preset: &AuthPreset) -> Result<()> {
+        let key = preset.id.to_string();
+        let value = serde_json::to_vec(preset)?;
+        self.put_record(&self.auth_presets, key, value)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_auth_presets(&self) -> Result<Vec<AuthPreset>> {
+        let mut presets: Vec<AuthPreset> = self.load_records(&self.auth_presets)?;
+        presets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        Ok(presets)
+    }
+
+    pub async fn get_auth_preset(&self, id: Uuid) -> Result<Option<AuthPreset>> {
+        match self.auth_presets.get(id.to_string())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Draft operations
+    pub async fn save_draft(&self, key: &str, draft: &SaveRequestPayload) -> Result<()> {
+        let value = serde_json::to_vec(draft)?;
+        self.drafts.insert(key, value)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_draft(&self, key: &str) -> Result<Option<SaveRequestPayload>> {
+        match self.drafts.get(key)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn discard_draft(&self, key: &str) -> Result<()> {
+        self.drafts.remove(key)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // View state operations; the state is opaque JSON owned by the frontend
+    pub async fn save_view_state(&self, request_id: Uuid, state: &str) -> Result<()> {
+        self.view_state
+            .insert(request_id.to_string(), state.as_bytes())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_view_state(&self, request_id: Uuid) -> Result<Option<String>> {
+        match self.view_state.get(request_id.to_string())? {
+            Some(value) => Ok(Some(String::from_utf8(value.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
     // History operations
     pub async fn save_to_history(&self, history: &RequestHistory) -> Result<()> {
         let key = history.id.to_string();
@@ -442,13 +1071,7 @@ impl Database {
     }
 
     pub async fn get_history(&self, limit: Option<i32>) -> Result<Vec<RequestHistory>> {
-        let mut history = Vec::new();
-
-        for item in self.history.iter() {
-            let (_, value) = item?;
-            let entry: RequestHistory = serde_json::from_slice(&value)?;
-            history.push(entry);
-        }
+        let mut history: Vec<RequestHistory> = self.load_records(&self.history)?;
 
         // Sort by timestamp desc
         history.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
@@ -461,6 +1084,13 @@ impl Database {
         Ok(history)
     }
 
+    pub async fn get_history_entry(&self, id: Uuid) -> Result<Option<RequestHistory>> {
+        match self.history.get(id.to_string())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
     pub async fn clear_history(&self) -> Result<()> {
         self.history.clear()?;
         self.db.flush()?;
@@ -482,7 +1112,7 @@ impl Database {
             collection.version = version;
 
             let updated_value = serde_json::to_vec(&collection)?;
-            self.collections.insert(key, updated_value)?;
+            self.put_record(&self.collections, key, updated_value)?;
             self.db.flush()?;
         }
         Ok(())
@@ -502,7 +1132,7 @@ impl Database {
             request.version = version;
 
             let updated_value = serde_json::to_vec(&request)?;
-            self.requests.insert(key, updated_value)?;
+            self.put_record(&self.requests, key, updated_value)?;
             self.db.flush()?;
         }
         Ok(())
@@ -522,52 +1152,79 @@ impl Database {
             environment.version = version;
 
             let updated_value = serde_json::to_vec(&environment)?;
-            self.environments.insert(key, updated_value)?;
+            self.put_record(&self.environments, key, updated_value)?;
             self.db.flush()?;
         }
         Ok(())
     }
 
-    pub async fn get_unsynced_collections(&self) -> Result<Vec<Collection>> {
-        let mut collections = Vec::new();
-
-        for item in self.collections.iter() {
-            let (_, value) = item?;
-            let collection: Collection = serde_json::from_slice(&value)?;
-            if !collection.synced {
-                collections.push(collection);
-            }
+    // Marks every record unsynced with no cloud id, so the next push sends everything as new.
+    // Returns the number of records reset.
+    pub async fn reset_sync_state(&self) -> Result<usize> {
+        let mut count = 0;
+
+        for mut collection in self.load_records::<Collection>(&self.collections)? {
+            collection.synced = false;
+            collection.cloud_id = None;
+            self.put_record(
+                &self.collections,
+                collection.id.to_string(),
+                serde_json::to_vec(&collection)?,
+            )?;
+            count += 1;
         }
 
-        Ok(collections)
-    }
-
-    pub async fn get_unsynced_requests(&self) -> Result<Vec<HttpRequest>> {
-        let mut requests = Vec::new();
+        for mut request in self.load_records::<HttpRequest>(&self.requests)? {
+            let Some(id) = request.id else {
+                continue;
+            };
+            request.synced = false;
+            request.cloud_id = None;
+            self.put_record(
+                &self.requests,
+                id.to_string(),
+                serde_json::to_vec(&request)?,
+            )?;
+            count += 1;
+        }
 
-        for item in self.requests.iter() {
-            let (_, value) = item?;
-            let request: HttpRequest = serde_json::from_slice(&value)?;
-            if !request.synced {
-                requests.push(request);
-            }
+        for mut environment in self.load_records::<Environment>(&self.environments)? {
+            environment.synced = false;
+            environment.cloud_id = None;
+            self.put_record(
+                &self.environments,
+                environment.id.to_string(),
+                serde_json::to_vec(&environment)?,
+            )?;
+            count += 1;
         }
 
-        Ok(requests)
+        self.db.flush()?;
+        Ok(count)
     }
 
-    pub async fn get_unsynced_environments(&self) -> Result<Vec<Environment>> {
-        let mut environments = Vec::new();
+    pub async fn get_unsynced_collections(&self) -> Result<Vec<Collection>> {
+        let collections: Vec<Collection> = self.load_records(&self.collections)?;
+        Ok(collections
+            .into_iter()
+            .filter(|collection| !collection.synced)
+            .collect())
+    }
 
-        for item in self.environments.iter() {
-            let (_, value) = item?;
-            let environment: Environment = serde_json::from_slice(&value)?;
-            if !environment.synced {
-                environments.push(environment);
-            }
-        }
+    pub async fn get_unsynced_requests(&self) -> Result<Vec<HttpRequest>> {
+        let requests: Vec<HttpRequest> = self.load_records(&self.requests)?;
+        Ok(requests
+            .into_iter()
+            .filter(|request| !request.synced)
+            .collect())
+    }
 
-        Ok(environments)
+    pub async fn get_unsynced_environments(&self) -> Result<Vec<Environment>> {
+        let environments: Vec<Environment> = self.load_records(&self.environments)?;
+        Ok(environments
+            .into_iter()
+            .filter(|environment| !environment.synced)
+            .collect())
     }
 
     pub async fn merge_collection(&self, cloud_collection: Collection) -> Result<()> {
@@ -592,14 +1249,16 @@ impl Database {
                 local.name = cloud_collection.name;
                 local.description = cloud_collection.description;
                 local.parent_id = cloud_collection.parent_id;
-                local.auth = cloud_collection.auth;
+                local.auth = merge_pulled_auth(local.auth.take(), cloud_collection.auth);
+                local.default_timeout = cloud_collection.default_timeout;
+                local.default_retries = cloud_collection.default_retries;
                 local.updated_at = cloud_collection.updated_at;
                 local.version = cloud_collection.version;
                 local.synced = true;
                 local.cloud_id = cloud_collection.cloud_id;
 
                 let updated_value = serde_json::to_vec(&local)?;
-                self.collections.insert(key, updated_value)?;
+                self.put_record(&self.collections, key, updated_value)?;
             }
         } else {
             // New collection from cloud
@@ -609,7 +1268,7 @@ impl Database {
 
             let key = new_collection.id.to_string();
             let value = serde_json::to_vec(&new_collection)?;
-            self.collections.insert(key, value)?;
+            self.put_record(&self.collections, key, value)?;
         }
 
         self.db.flush()?;
@@ -642,23 +1301,78 @@ impl Database {
                 local.url = cloud_request.url;
                 local.headers = cloud_request.headers;
                 local.body = cloud_request.body;
+                self.move_request_count(local.collection_id, cloud_request.collection_id)?;
                 local.collection_id = cloud_request.collection_id;
+                // Relinked from the cloud ids once the whole pull is merged
+                local.depends_on = Vec::new();
+                local.depends_on_cloud_ids = cloud_request.depends_on_cloud_ids;
+                local.examples = cloud_request.examples;
+                local.success_criteria = cloud_request.success_criteria;
+                local.auth = merge_pulled_auth(local.auth.take(), cloud_request.auth);
+                local.alias = cloud_request.alias;
+                local.disabled_in_environments = cloud_request.disabled_in_environments;
+                local.query_params = cloud_request.query_params;
                 local.updated_at = cloud_request.updated_at;
                 local.version = cloud_request.version;
                 local.synced = true;
                 local.cloud_id = cloud_request.cloud_id;
 
                 let updated_value = serde_json::to_vec(&local)?;
-                self.requests.insert(key, updated_value)?;
+                self.put_record(&self.requests, key, updated_value)?;
             }
         } else {
             let mut new_request = cloud_request;
             new_request.id = Some(Uuid::new_v4());
             new_request.synced = true;
+            new_request.depends_on = Vec::new(); // the sender's local ids
 
             let key = new_request.id.unwrap().to_string();
             let value = serde_json::to_vec(&new_request)?;
-            self.requests.insert(key, value)?;
+            self.put_record(&self.requests, key, value)?;
+            self.adjust_request_count(new_request.collection_id, 1)?;
+        }
+
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Pulled dependencies are named by cloud id, since local ids differ per device. Runs once
+    // a pull's requests are merged, so a dependency later in the batch still resolves.
+    pub async fn relink_request_dependencies(&self, pulled: &[HttpRequest]) -> Result<()> {
+        let requests: Vec<HttpRequest> = self.load_records(&self.requests)?;
+        let local_ids: HashMap<&str, Uuid> = requests
+            .iter()
+            .filter_map(|request| Some((request.cloud_id.as_deref()?, request.id?)))
+            .collect();
+
+        for cloud_request in pulled {
+            let Some(local) = cloud_request.cloud_id.as_deref().and_then(|cloud_id| {
+                requests
+                    .iter()
+                    .find(|request| request.cloud_id.as_deref() == Some(cloud_id))
+            }) else {
+                continue;
+            };
+            // A local edit newer than the pulled copy keeps its own dependencies
+            if !local.synced || local.version != cloud_request.version {
+                continue;
+            }
+
+            let depends_on: Vec<Uuid> = cloud_request
+                .depends_on_cloud_ids
+                .iter()
+                .filter_map(|cloud_id| local_ids.get(cloud_id.as_str()).copied())
+                .collect();
+            let Some(id) = local.id else { continue };
+            if depends_on != local.depends_on {
+                let mut relinked = local.clone();
+                relinked.depends_on = depends_on;
+                self.put_record(
+                    &self.requests,
+                    id.to_string(),
+                    serde_json::to_vec(&relinked)?,
+                )?;
+            }
         }
 
         self.db.flush()?;
@@ -683,13 +1397,14 @@ impl Database {
             {
                 local.name = cloud_environment.name;
                 local.variables = cloud_environment.variables;
+                local.base_url_override = cloud_environment.base_url_override;
                 local.updated_at = cloud_environment.updated_at;
                 local.version = cloud_environment.version;
                 local.synced = true;
                 local.cloud_id = cloud_environment.cloud_id;
 
                 let updated_value = serde_json::to_vec(&local)?;
-                self.environments.insert(key, updated_value)?;
+                self.put_record(&self.environments, key, updated_value)?;
             }
         } else {
             let mut new_environment = cloud_environment;
@@ -699,7 +1414,7 @@ impl Database {
 
             let key = new_environment.id.to_string();
             let value = serde_json::to_vec(&new_environment)?;
-            self.environments.insert(key, value)?;
+            self.put_record(&self.environments, key, value)?;
         }
 
         self.db.flush()?;
@@ -756,4 +1471,640 @@ impl Database {
         self.db.flush()?;
         Ok(())
     }
+
+    pub async fn get_config_flag(&self, key: &str) -> Result<bool> {
+        Ok(self
+            .config
+            .get(key)?
+            .is_some_and(|bytes| bytes.as_ref() == b"true"))
+    }
+
+    pub async fn set_config_flag(&self, key: &str, enabled: bool) -> Result<()> {
+        self.config
+            .insert(key, if enabled { "true" } else { "false" }.as_bytes())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_custom_ca_bundle_path(&self) -> Result<Option<String>> {
+        match self.config.get("custom_ca_bundle_path")? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_custom_ca_bundle_path(&self, path: Option<&str>) -> Result<()> {
+        if let Some(path) = path {
+            self.config
+                .insert("custom_ca_bundle_path", path.as_bytes())?;
+        } else {
+            self.config.remove("custom_ca_bundle_path")?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_proxy_settings(&self) -> Result<Option<ProxySettings>> {
+        match self.config.get("proxy_settings")? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_proxy_settings(&self, settings: Option<&ProxySettings>) -> Result<()> {
+        if let Some(settings) = settings {
+            self.config
+                .insert("proxy_settings", serde_json::to_vec(settings)?)?;
+        } else {
+            self.config.remove("proxy_settings")?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Every saved cookie jar as (scope, JSON)
+    pub async fn get_cookie_jars(&self) -> Result<Vec<(String, String)>> {
+        let mut jars = Vec::new();
+        for item in self.config.scan_prefix(COOKIE_JAR_PREFIX) {
+            let (key, value) = item?;
+            let key = String::from_utf8(key.to_vec())?;
+            jars.push((
+                key[COOKIE_JAR_PREFIX.len()..].to_string(),
+                String::from_utf8(value.to_vec())?,
+            ));
+        }
+        Ok(jars)
+    }
+
+    pub async fn save_cookie_jar(&self, scope: &str, json: &str) -> Result<()> {
+        self.config
+            .insert(format!("{}{}", COOKIE_JAR_PREFIX, scope), json.as_bytes())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub async fn get_request_log_path(&self) -> Result<Option<String>> {
+        match self.config.get("request_log_path")? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_request_log_path(&self, path: Option<&str>) -> Result<()> {
+        if let Some(path) = path {
+            self.config.insert("request_log_path", path.as_bytes())?;
+        } else {
+            self.config.remove("request_log_path")?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Named timeouts, falling back to the built-in set until one is saved
+    pub async fn get_timeout_presets(&self) -> Result<Vec<TimeoutPreset>> {
+        match self.config.get(TIMEOUT_PRESETS_KEY)? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(TimeoutPreset::defaults()),
+        }
+    }
+
+    // Adds the preset, or updates the seconds of an existing one with the same name
+    pub async fn save_timeout_preset(&self, preset: TimeoutPreset) -> Result<Vec<TimeoutPreset>> {
+        let mut presets = self.get_timeout_presets().await?;
+        match presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => existing.seconds = preset.seconds,
+            None => presets.push(preset),
+        }
+
+        self.config
+            .insert(TIMEOUT_PRESETS_KEY, serde_json::to_vec(&presets)?)?;
+        self.db.flush()?;
+        Ok(presets)
+    }
+
+    // Display mode per media type, e.g. "text/html" -> Raw
+    pub async fn get_format_preferences(&self) -> Result<HashMap<String, FormatMode>> {
+        match self.config.get(FORMAT_PREFERENCES_KEY)? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    pub async fn set_format_preference(
+        &self,
+        media_type: &str,
+        mode: FormatMode,
+    ) -> Result<HashMap<String, FormatMode>> {
+        let mut preferences = self.get_format_preferences().await?;
+        preferences.insert(media_type.to_string(), mode);
+
+        self.config
+            .insert(FORMAT_PREFERENCES_KEY, serde_json::to_vec(&preferences)?)?;
+        self.db.flush()?;
+        Ok(preferences)
+    }
+
+    // Config entries with UTF-8 values (flags, paths, sync provider settings). Cached
+    // request counts are left out since they only describe this database.
+    pub async fn get_config_entries(&self) -> Result<HashMap<String, String>> {
+        let mut entries = HashMap::new();
+        for item in self.config.iter() {
+            let (key, value) = item?;
+            if let (Ok(key), Ok(value)) = (
+                String::from_utf8(key.to_vec()),
+                String::from_utf8(value.to_vec()),
+            ) {
+                if key.starts_with(REQUEST_COUNT_PREFIX) {
+                    continue;
+                }
+                entries.insert(key, value);
+            }
+        }
+        Ok(entries)
+    }
+
+    // Restores a workspace bundle. Records are written as-is so ids, versions and sync
+    // state survive the move. Config entries missing from the bundle are kept, and
+    // machine-local ones in it are ignored. Everything is serialized first and written in
+    // one transaction, so a failed import leaves the workspace untouched.
+    pub async fn import_workspace(&self, bundle: &WorkspaceBundle, mode: ImportMode) -> Result<()> {
+        let replace = matches!(mode, ImportMode::Replace);
+
+        let collections = bundle
+            .collections
+            .iter()
+            .map(|collection| Ok((collection.id.to_string(), serde_json::to_vec(collection)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let requests = bundle
+            .requests
+            .iter()
+            .map(|request| {
+                let key = request.id.unwrap_or_else(Uuid::new_v4).to_string();
+                Ok((key, serde_json::to_vec(request)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let environments = bundle
+            .environments
+            .iter()
+            .map(|environment| Ok((environment.id.to_string(), serde_json::to_vec(environment)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let config: Vec<_> = bundle
+            .config
+            .iter()
+            .filter(|(key, _)| !is_machine_local_config(key))
+            .collect();
+
+        // Replacing drops every existing record, along with the drafts and view state of
+        // the replaced requests (their aliases live on the records themselves)
+        let stale = |tree: &Tree| -> Result<Vec<sled::IVec>> {
+            if !replace {
+                return Ok(Vec::new());
+            }
+            Ok(tree.iter().keys().collect::<std::result::Result<_, _>>()?)
+        };
+        let stale_collections = stale(&self.collections)?;
+        let stale_requests = stale(&self.requests)?;
+        let stale_environments = stale(&self.environments)?;
+        // Requests are written directly, so the cached counts are dropped to rebuild them
+        let cached_counts = self
+            .config
+            .scan_prefix(REQUEST_COUNT_PREFIX)
+            .keys()
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let trees = [
+            &self.collections,
+            &self.requests,
+            &self.environments,
+            &self.config,
+            &self.drafts,
+            &self.view_state,
+            &self.quarantine,
+        ];
+        trees
+            .transaction(|trees| {
+                let (collections_tx, requests_tx, environments_tx) =
+                    (&trees[0], &trees[1], &trees[2]);
+                let (config_tx, drafts_tx, view_state_tx) = (&trees[3], &trees[4], &trees[5]);
+                let quarantine_tx = &trees[6];
+
+                for key in &stale_collections {
+                    collections_tx.remove(key)?;
+                }
+                for key in &stale_requests {
+                    requests_tx.remove(key)?;
+                    drafts_tx.remove(key)?;
+                    view_state_tx.remove(key)?;
+                }
+                for key in &stale_environments {
+                    environments_tx.remove(key)?;
+                }
+
+                for (tree, name, records) in [
+                    (collections_tx, "collections", &collections),
+                    (requests_tx, "requests", &requests),
+                    (environments_tx, "environments", &environments),
+                ] {
+                    for (key, value) in records {
+                        if replace || tree.get(key.as_bytes())?.is_none() {
+                            tree.insert(key.as_bytes(), value.as_slice())?;
+                            quarantine_tx.remove(
+                                quarantine_key(name.as_bytes(), key.as_bytes()).as_bytes(),
+                            )?;
+                        }
+                    }
+                }
+                for (key, value) in &config {
+                    if replace || config_tx.get(key.as_bytes())?.is_none() {
+                        config_tx.insert(key.as_bytes(), value.as_bytes())?;
+                    }
+                }
+                for key in &cached_counts {
+                    config_tx.remove(key)?;
+                }
+                Ok(())
+            })
+            .map_err(|e: TransactionError<()>| {
+                anyhow::anyhow!("Failed to import workspace: {:?}", e)
+            })?;
+
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Maintenance operations
+    pub async fn get_stats(&self) -> Result<DatabaseStats> {
+        Ok(DatabaseStats {
+            collections_count: self.collections.len(),
+            requests_count: self.requests.len(),
+            environments_count: self.environments.len(),
+            history_count: self.history.len(),
+            on_disk_bytes: self.db.size_on_disk()?,
+        })
+    }
+
+    // sled has no explicit compaction, so this only flushes pending writes to disk. Space
+    // freed by deletions is reused by later writes; `on_disk_bytes` may not go down.
+    pub async fn compact(&self) -> Result<()> {
+        self.db.flush_async().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_updating_request_keeps_created_at() {
+        let db = Database::new_embedded().await.unwrap();
+        let request = HttpRequest::new(
+            "List users".to_string(),
+            HttpMethod::GET,
+            "https://example.com/users".to_string(),
+        );
+        let first = db.save_request(&request).await.unwrap();
+
+        let mut updated = first.clone();
+        for url in [
+            "https://example.com/v2/users",
+            "https://example.com/v3/users",
+        ] {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            updated.url = url.to_string();
+            updated.created_at = Some(Utc::now()); // as the editor sends it
+            updated = db.save_request(&updated).await.unwrap();
+        }
+
+        let stored = db.get_request(first.id.unwrap()).await.unwrap().unwrap();
+        assert_eq!(stored.created_at, first.created_at);
+        assert!(stored.updated_at > first.updated_at);
+        assert_eq!(stored.url, "https://example.com/v3/users");
+    }
+
+    #[tokio::test]
+    async fn test_request_auth_survives_saves_until_cleared() {
+        let db = Database::new_embedded().await.unwrap();
+        let request = HttpRequest::new(
+            "Me".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/me".to_string(),
+        );
+        let saved = db.save_request(&request).await.unwrap();
+        let id = saved.id.unwrap();
+        let auth = AuthConfig {
+            auth_type: AuthType::Bearer,
+            basic: None,
+            bearer: Some(BearerAuth {
+                token: "request-token".to_string(),
+            }),
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
+        };
+        db.set_request_auth(id, Some(auth)).await.unwrap();
+
+        // The editor saves without auth, which leaves the stored auth alone
+        db.save_request(&saved).await.unwrap();
+        assert!(db.get_request(id).await.unwrap().unwrap().auth.is_some());
+
+        db.set_request_auth(id, None).await.unwrap();
+        db.save_request(&saved).await.unwrap();
+        assert!(db.get_request(id).await.unwrap().unwrap().auth.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_view_state_round_trips_and_is_removed_with_request() {
+        let db = Database::new_embedded().await.unwrap();
+        let request = HttpRequest::new(
+            "Users".to_string(),
+            HttpMethod::GET,
+            "https://api.example.com/users".to_string(),
+        );
+        let id = db.save_request(&request).await.unwrap().id.unwrap();
+        let state = r#"{"tab":"headers","filter":"$.items","wrap":true}"#;
+
+        db.save_view_state(id, state).await.unwrap();
+        assert_eq!(db.get_view_state(id).await.unwrap().as_deref(), Some(state));
+
+        db.delete_request(id).await.unwrap();
+        assert_eq!(db.get_view_state(id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_stored_request_headers_keep_their_order() {
+        let db = Database::new_embedded().await.unwrap();
+        let id = Uuid::new_v4();
+        let stored = format!(
+            r#"{{"id":"{}","name":"Old","method":"GET","url":"https://example.com",
+            "headers":{{"X-Second":"2","Accept":"text/plain"}},"body":null,
+            "collection_id":null,"created_at":null,"updated_at":null,"cloud_id":null}}"#,
+            id
+        );
+        db.requests
+            .insert(id.to_string(), stored.as_bytes())
+            .unwrap();
+
+        let request = db.get_request(id).await.unwrap().unwrap();
+        assert_eq!(
+            request.headers,
+            vec![
+                ("X-Second".to_string(), "2".to_string()),
+                ("Accept".to_string(), "text/plain".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_headers_keep_their_order_through_ipc() {
+        // Tauri hands command arguments over as a serde_json::Value
+        let args: serde_json::Value = serde_json::from_str(
+            r#"{"method":"GET","url":"https://example.com",
+            "headers":{"X-Second":"2","Accept":"text/plain"},"body":null}"#,
+        )
+        .unwrap();
+        let payload: SendRequestPayload = serde_json::from_value(args).unwrap();
+
+        let names: Vec<_> = payload
+            .headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["X-Second", "Accept"]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_record_is_skipped_and_quarantined() {
+        let db = Database::new_embedded().await.unwrap();
+
+        let good = Collection::new("Good".to_string(), None);
+        db.create_collection(&good).await.unwrap();
+        db.collections.insert("broken", &b"{not json"[..]).unwrap();
+
+        let collections = db.get_collections().await.unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(collections[0].id, good.id);
+
+        let corrupt = db.get_corrupt_records().await.unwrap();
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(
+            (corrupt[0].tree.as_str(), corrupt[0].key.as_str()),
+            ("collections", "broken")
+        );
+
+        db.delete_corrupt_record("collections", "broken")
+            .await
+            .unwrap();
+        assert!(db.get_corrupt_records().await.unwrap().is_empty());
+        assert_eq!(db.collections.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rewritten_record_leaves_quarantine() {
+        let db = Database::new_embedded().await.unwrap();
+        let repaired = Collection::new("Repaired".to_string(), None);
+        let fixed = Collection::new("Fixed".to_string(), None);
+        for collection in [&repaired, &fixed] {
+            db.collections
+                .insert(collection.id.to_string(), &b"{not json"[..])
+                .unwrap();
+        }
+        db.get_collections().await.unwrap();
+        assert_eq!(db.get_corrupt_records().await.unwrap().len(), 2);
+
+        // Saved again through the app
+        db.create_collection(&repaired).await.unwrap();
+        assert_eq!(db.get_corrupt_records().await.unwrap().len(), 1);
+
+        // Fixed outside the app: deleting the stale entry keeps the record
+        db.collections
+            .insert(fixed.id.to_string(), serde_json::to_vec(&fixed).unwrap())
+            .unwrap();
+        db.delete_corrupt_record("collections", &fixed.id.to_string())
+            .await
+            .unwrap();
+        assert!(db.get_corrupt_records().await.unwrap().is_empty());
+        assert_eq!(db.get_collections().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_activating_environment_leaves_others_untouched() {
+        let db = Database::new_embedded().await.unwrap();
+        let dev = Environment::new("Dev".to_string(), HashMap::new());
+        let prod = Environment::new("Prod".to_string(), HashMap::new());
+        db.create_environment(&dev).await.unwrap();
+        db.create_environment(&prod).await.unwrap();
+        let prod_before = db.environments.get(prod.id.to_string()).unwrap();
+
+        db.set_active_environment(Some(dev.id)).await.unwrap();
+        db.set_active_environment(Some(prod.id)).await.unwrap();
+        db.set_active_environment(Some(dev.id)).await.unwrap();
+
+        assert_eq!(
+            db.environments.get(prod.id.to_string()).unwrap(),
+            prod_before
+        );
+        assert_eq!(
+            db.get_active_environment().await.unwrap().unwrap().id,
+            dev.id
+        );
+
+        let environments = db.get_environments().await.unwrap();
+        let active: Vec<Uuid> = environments
+            .iter()
+            .filter(|e| e.is_active)
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(active, vec![dev.id]);
+        let prod_loaded = environments.iter().find(|e| e.id == prod.id).unwrap();
+        assert_eq!(prod_loaded.updated_at, prod.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_active_flag_is_migrated() {
+        let db = Database::new_embedded().await.unwrap();
+        let mut legacy = Environment::new("Legacy".to_string(), HashMap::new());
+        legacy.is_active = true;
+        db.create_environment(&legacy).await.unwrap();
+
+        assert_eq!(
+            db.get_active_environment().await.unwrap().unwrap().id,
+            legacy.id
+        );
+        db.set_active_environment(None).await.unwrap();
+        assert!(db.get_active_environment().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_alias_resolves_and_must_be_unique() {
+        let db = Database::new_embedded().await.unwrap();
+        let login = HttpRequest::new("Login".to_string(), HttpMethod::POST, "https://".into());
+        let login = db.save_request(&login).await.unwrap();
+        let me = HttpRequest::new("Me".to_string(), HttpMethod::GET, "https://".into());
+        let me = db.save_request(&me).await.unwrap();
+
+        db.set_request_alias(login.id.unwrap(), Some("login".to_string()))
+            .await
+            .unwrap();
+        let resolved = db.find_request_by_alias("LOGIN").await.unwrap().unwrap();
+        assert_eq!(resolved.id, login.id);
+
+        // Saving from the editor keeps the alias
+        db.save_request(&resolved).await.unwrap();
+        assert!(db.find_request_by_alias("login").await.unwrap().is_some());
+
+        let err = db
+            .set_request_alias(me.id.unwrap(), Some("Login".to_string()))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("already used by request 'Login'"));
+        assert!(db.find_request_by_alias("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reset_sync_state_marks_everything_unsynced() {
+        let db = Database::new_embedded().await.unwrap();
+        let collection = Collection::new("Synced".to_string(), None);
+        db.create_collection(&collection).await.unwrap();
+        let request = db
+            .save_request(&HttpRequest::new(
+                "Get".to_string(),
+                HttpMethod::GET,
+                "https://example.com".to_string(),
+            ))
+            .await
+            .unwrap();
+        let request_id = request.id.unwrap();
+        let environment = Environment::new("Prod".to_string(), HashMap::new());
+        db.create_environment(&environment).await.unwrap();
+
+        db.mark_collection_synced(collection.id, "c-1".to_string(), 1)
+            .await
+            .unwrap();
+        db.mark_request_synced(request_id, "r-1".to_string(), 1)
+            .await
+            .unwrap();
+        db.mark_environment_synced(environment.id, "e-1".to_string(), 1)
+            .await
+            .unwrap();
+        assert!(db.get_unsynced_requests().await.unwrap().is_empty());
+
+        assert_eq!(db.reset_sync_state().await.unwrap(), 3);
+
+        let collections = db.get_unsynced_collections().await.unwrap();
+        let requests = db.get_unsynced_requests().await.unwrap();
+        let environments = db.get_unsynced_environments().await.unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(environments.len(), 1);
+        assert!(collections[0].cloud_id.is_none());
+        assert!(requests[0].cloud_id.is_none());
+        assert!(environments[0].cloud_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_request_counts_follow_saves_and_deletes() {
+        let db = Database::new_embedded().await.unwrap();
+        let users = Collection::new("Users".to_string(), None);
+        let orders = Collection::new("Orders".to_string(), None);
+        db.create_collection(&users).await.unwrap();
+        db.create_collection(&orders).await.unwrap();
+
+        let mut first = HttpRequest::new("List".to_string(), HttpMethod::GET, "https://".into());
+        first.collection_id = Some(users.id);
+        let first = db.save_request(&first).await.unwrap();
+
+        // The first read builds the cache by scanning
+        let counts = db.get_collection_request_counts().await.unwrap();
+        assert_eq!((counts[&users.id], counts[&orders.id]), (1, 0));
+
+        let mut second = HttpRequest::new("Get".to_string(), HttpMethod::GET, "https://".into());
+        second.collection_id = Some(users.id);
+        let second = db.save_request(&second).await.unwrap();
+        db.move_request(first.id.unwrap(), orders.id).await.unwrap();
+        db.save_request(&second).await.unwrap();
+        let counts = db.get_collection_request_counts().await.unwrap();
+        assert_eq!((counts[&users.id], counts[&orders.id]), (1, 1));
+
+        db.delete_request(second.id.unwrap()).await.unwrap();
+        let counts = db.get_collection_request_counts().await.unwrap();
+        assert_eq!((counts[&users.id], counts[&orders.id]), (0, 1));
+        assert!(!db
+            .get_config_entries()
+            .await
+            .unwrap()
+            .keys()
+            .any(|key| key.starts_with(REQUEST_COUNT_PREFIX)));
+    }
+
+    #[tokio::test]
+    async fn test_database_stats_and_compaction() {
+        let db = Database::new_embedded().await.unwrap();
+
+        let collection = Collection::new("Users".to_string(), None);
+        db.create_collection(&collection).await.unwrap();
+        for i in 0..3 {
+            let mut request =
+                HttpRequest::new(format!("Request {}", i), HttpMethod::GET, "https://".into());
+            request.collection_id = Some(collection.id);
+            db.save_request(&request).await.unwrap();
+        }
+        db.create_environment(&Environment::new("Dev".to_string(), HashMap::new()))
+            .await
+            .unwrap();
+
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats.collections_count, 1);
+        assert_eq!(stats.requests_count, 3);
+        assert_eq!(stats.environments_count, 1);
+        assert_eq!(stats.history_count, 0);
+
+        db.delete_collection(collection.id).await.unwrap();
+        db.compact().await.unwrap();
+        assert_eq!(db.get_stats().await.unwrap().requests_count, 0);
+    }
 }
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+// Values that would be mangled unquoted: whitespace, quotes, comments, escapes
+fn needs_quotes(value: &str) -> bool {
+    value
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '#' | '\\'))
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Renders variables as sorted KEY=value lines. Credential-like names are left out
+// unless `include_secrets` is set.
+pub fn to_dotenv(variables: &HashMap<String, String>, include_secrets: bool) -> String {
+    let mut keys: Vec<&String> = variables
+        .keys()
+        .filter(|key| include_secrets || !crate::secrets::is_sensitive_name(key))
+        .collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for key in keys {
+        let value = &variables[key];
+        out.push_str(key);
+        out.push('=');
+        if needs_quotes(value) {
+            out.push_str(&quote(value));
+        } else {
+            out.push_str(value);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Parses KEY=value lines: `#` comments, an optional `export ` prefix, double quotes
+// with escapes, and single quotes taken literally
+pub fn parse_dotenv(content: &str) -> Result<HashMap<String, String>> {
+    let mut variables = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (key, raw_value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Line {}: expected KEY=value", index + 1))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(anyhow::anyhow!("Line {}: missing variable name", index + 1));
+        }
+
+        let raw_value = raw_value.trim();
+        let value = if let Some(rest) = raw_value.strip_prefix('"') {
+            unquote_double(rest)
+                .ok_or_else(|| anyhow::anyhow!("Line {}: unterminated double quote", index + 1))?
+        } else if let Some(rest) = raw_value.strip_prefix('\'') {
+            rest.split_once('\'')
+                .map(|(value, _)| value.to_string())
+                .ok_or_else(|| anyhow::anyhow!("Line {}: unterminated single quote", index + 1))?
+        } else {
+            // Unquoted values end at an inline comment
+            match raw_value.find(" #") {
+                Some(comment) => raw_value[..comment].trim_end().to_string(),
+                None => raw_value.to_string(),
+            }
+        };
+
+        variables.insert(key.to_string(), value);
+    }
+
+    Ok(variables)
+}
+
+// Reads up to the closing quote, resolving escapes; None when the quote never closes
+fn unquote_double(rest: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = rest.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dotenv_round_trip() {
+        let variables = HashMap::from([
+            (
+                "BASE_URL".to_string(),
+                "https://api.example.com".to_string(),
+            ),
+            ("GREETING".to_string(), "hello world".to_string()),
+            (
+                "MULTILINE".to_string(),
+                "line one\nline \"two\"".to_string(),
+            ),
+            (
+                "WINDOWS_PATH".to_string(),
+                "C:\\temp # not a comment".to_string(),
+            ),
+            ("EMPTY".to_string(), String::new()),
+            ("API_TOKEN".to_string(), "s3cr3t".to_string()),
+            (
+                "AUTH_URL".to_string(),
+                "https://auth.example.com".to_string(),
+            ),
+        ]);
+
+        let exported = to_dotenv(&variables, true);
+        assert!(exported.contains("GREETING=\"hello world\"\n"));
+        assert_eq!(parse_dotenv(&exported).unwrap(), variables);
+
+        let without_secrets = parse_dotenv(&to_dotenv(&variables, false)).unwrap();
+        assert!(!without_secrets.contains_key("API_TOKEN"));
+        assert!(without_secrets.contains_key("AUTH_URL"));
+        assert_eq!(without_secrets.len(), variables.len() - 1);
+    }
+}
@@ -0,0 +1,414 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+use crate::models::{
+    AuthConfig, Collection, FormDataField, HttpRequest, RequestBody, RequestHistory,
+    WorkspaceBundle,
+};
+
+// Config entries holding credentials (sync provider sessions and keys, proxy logins,
+// cookie jars)
+const SECRET_CONFIG_PREFIXES: &[&str] = &["sync_provider_", "proxy_settings", "cookie_jar:"];
+
+// Table cells can't contain raw pipes or newlines
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn heading(level: usize, text: &str) -> String {
+    format!("{} {}\n\n", "#".repeat(level.min(6)), text)
+}
+
+fn key_value_table(out: &mut String, title: &str, rows: &[(String, String)]) {
+    if rows.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "| {} | Value |", title);
+    out.push_str("| --- | --- |\n");
+    for (key, value) in rows {
+        let _ = writeln!(out, "| {} | {} |", escape_cell(key), escape_cell(value));
+    }
+    out.push('\n');
+}
+
+fn fenced(out: &mut String, language: &str, content: &str) {
+    // Use a longer fence if the content itself contains backticks
+    let fence = if content.contains("```") {
+        "~~~~"
+    } else {
+        "```"
+    };
+    let _ = writeln!(
+        out,
+        "{}{}\n{}\n{}\n",
+        fence,
+        language,
+        content.trim_end(),
+        fence
+    );
+}
+
+fn language_for(content_type: &str) -> &'static str {
+    let content_type = content_type.to_lowercase();
+    if content_type.contains("json") {
+        "json"
+    } else if content_type.contains("xml") {
+        "xml"
+    } else if content_type.contains("html") {
+        "html"
+    } else {
+        ""
+    }
+}
+
+fn write_body(out: &mut String, body: &RequestBody) {
+    out.push_str("**Body**\n\n");
+    match body {
+        RequestBody::Raw {
+            content,
+            content_type,
+        } => fenced(out, language_for(content_type), content),
+        RequestBody::Json(value) => {
+            let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+            fenced(out, "json", &pretty);
+        }
+        RequestBody::FormData(fields) => {
+            let mut rows: Vec<_> = fields
+                .iter()
+                .map(|(key, field)| match field {
+                    FormDataField::Text { value } => (key.clone(), value.clone()),
+                    FormDataField::File { path } => (key.clone(), format!("@{}", path)),
+                })
+                .collect();
+            rows.sort();
+            key_value_table(out, "Field", &rows);
+        }
+        RequestBody::UrlEncoded(fields) => {
+            let mut rows: Vec<_> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            rows.sort();
+            key_value_table(out, "Field", &rows);
+        }
+        RequestBody::BinaryFile { path } => {
+            let _ = writeln!(out, "File: `{}`\n", path);
+        }
+    }
+}
+
+fn write_request(out: &mut String, level: usize, request: &HttpRequest) {
+    out.push_str(&heading(level, &request.name));
+    let _ = writeln!(out, "`{} {}`\n", request.method.to_string(), request.url);
+
+    key_value_table(out, "Header", &request.headers);
+
+    let query: Vec<_> = request
+        .query_params
+        .iter()
+        .filter(|(_, _, enabled)| *enabled)
+        .map(|(key, value, _)| (key.clone(), value.clone()))
+        .collect();
+    key_value_table(out, "Query parameter", &query);
+
+    if let Some(body) = &request.body {
+        write_body(out, body);
+    }
+
+    for example in &request.examples {
+        let _ = writeln!(out, "**Example: {}** ({})\n", example.name, example.status);
+        let content_type = example
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        fenced(out, language_for(content_type), &example.body);
+    }
+}
+
+fn write_collection(
+    out: &mut String,
+    level: usize,
+    collection: &Collection,
+    collections: &[Collection],
+    requests: &[HttpRequest],
+) {
+    out.push_str(&heading(level, &collection.name));
+    if let Some(description) = collection.description.as_deref().filter(|d| !d.is_empty()) {
+        let _ = writeln!(out, "{}\n", description);
+    }
+
+    let mut own_requests: Vec<_> = requests
+        .iter()
+        .filter(|r| r.collection_id == Some(collection.id))
+        .collect();
+    own_requests.sort_by(|a, b| a.name.cmp(&b.name));
+    for request in own_requests {
+        write_request(out, level + 1, request);
+    }
+
+    let mut children: Vec<_> = collections
+        .iter()
+        .filter(|c| c.parent_id == Some(collection.id))
+        .collect();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    for child in children {
+        write_collection(out, level + 1, child, collections, requests);
+    }
+}
+
+// Renders a collection subtree as Markdown. Variables are left as {{tokens}}.
+pub fn collection_to_markdown(
+    root: &Collection,
+    collections: &[Collection],
+    requests: &[HttpRequest],
+) -> String {
+    let mut out = String::new();
+    write_collection(&mut out, 1, root, collections, requests);
+    out.trim_end().to_string() + "\n"
+}
+
+// Single-quotes a shell argument
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// The curl command for a request, sent to `url` (which may differ from the templated one)
+pub fn request_to_curl(request: &HttpRequest, url: &str) -> String {
+    let mut args = vec![format!(
+        "curl -X {} {}",
+        request.method.to_string(),
+        shell_quote(url)
+    )];
+    let has_content_type = request
+        .headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+    for (key, value) in &request.headers {
+        args.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+
+    match &request.body {
+        Some(RequestBody::Raw {
+            content,
+            content_type,
+        }) => {
+            if !has_content_type {
+                let header = format!("Content-Type: {}", content_type);
+                args.push(format!("-H {}", shell_quote(&header)));
+            }
+            args.push(format!("--data-raw {}", shell_quote(content)));
+        }
+        Some(RequestBody::Json(value)) => {
+            if !has_content_type {
+                args.push("-H 'Content-Type: application/json'".to_string());
+            }
+            args.push(format!("--data-raw {}", shell_quote(&value.to_string())));
+        }
+        Some(RequestBody::UrlEncoded(fields)) => {
+            let mut fields: Vec<_> = fields.iter().collect();
+            fields.sort();
+            for (key, value) in fields {
+                let field = format!("{}={}", key, value);
+                args.push(format!("--data-urlencode {}", shell_quote(&field)));
+            }
+        }
+        Some(RequestBody::FormData(fields)) => {
+            for (key, field) in fields {
+                let field = match field {
+                    FormDataField::Text { value } => format!("{}={}", key, value),
+                    FormDataField::File { path } => format!("{}=@{}", key, path),
+                };
+                args.push(format!("-F {}", shell_quote(&field)));
+            }
+        }
+        Some(RequestBody::BinaryFile { path }) => {
+            if !has_content_type {
+                let header = format!("Content-Type: {}", crate::http::file_content_type(path));
+                args.push(format!("-H {}", shell_quote(&header)));
+            }
+            args.push(format!(
+                "--data-binary {}",
+                shell_quote(&format!("@{}", path))
+            ));
+        }
+        None => {}
+    }
+
+    args.join(" \\\n  ")
+}
+
+// Renders a history entry's request line, status, headers and body as Markdown
+pub fn response_to_markdown(entry: &RequestHistory) -> String {
+    let mut out = heading(1, &entry.request.name);
+    let url = if entry.resolved_url.is_empty() {
+        &entry.request.url
+    } else {
+        &entry.resolved_url
+    };
+    let _ = writeln!(out, "`{} {}`\n", entry.request.method.to_string(), url);
+
+    if let Some(response) = &entry.response {
+        let _ = writeln!(
+            out,
+            "**Status:** {} {} ({} ms)\n",
+            response.status, response.status_text, response.response_time
+        );
+
+        key_value_table(&mut out, "Header", &response.headers.0);
+
+        if !response.body.is_empty() {
+            let content_type = response.headers.get("content-type").unwrap_or_default();
+            out.push_str("**Body**\n\n");
+            fenced(&mut out, language_for(content_type), &response.body);
+        }
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+// Drops credentials from a workspace bundle: sync provider config, collection and request
+// auth passwords and tokens, OAuth2 client secrets, and the values of credential-like
+// environment variables (the names stay, so the variables can be filled in again)
+pub fn strip_workspace_secrets(bundle: &mut WorkspaceBundle) {
+    bundle.config.retain(|key, _| {
+        !SECRET_CONFIG_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+    });
+
+    let collection_auth = bundle
+        .collections
+        .iter_mut()
+        .filter_map(|c| c.auth.as_mut());
+    let request_auth = bundle.requests.iter_mut().filter_map(|r| r.auth.as_mut());
+    for auth in collection_auth.chain(request_auth) {
+        strip_auth_secrets(auth);
+    }
+
+    for environment in &mut bundle.environments {
+        for (name, value) in environment.variables.iter_mut() {
+            if crate::secrets::is_sensitive_name(name) {
+                value.clear();
+            }
+        }
+    }
+}
+
+fn strip_auth_secrets(auth: &mut AuthConfig) {
+    if let Some(basic) = &mut auth.basic {
+        basic.password.clear();
+    }
+    if let Some(bearer) = &mut auth.bearer {
+        bearer.token.clear();
+    }
+    if let Some(api_key) = &mut auth.api_key {
+        api_key.value.clear();
+    }
+    if let Some(digest) = &mut auth.digest {
+        digest.password.clear();
+    }
+    if let Some(aws) = &mut auth.aws_sigv4 {
+        aws.secret_key.clear();
+        aws.session_token = None;
+    }
+    if let Some(credentials) = &mut auth.oauth2_client_credentials {
+        credentials.client_secret.clear();
+    }
+    if let Some(oauth2) = &mut auth.oauth2 {
+        oauth2.client_secret = None;
+        oauth2.token = None;
+    }
+}
+
+// Recursively sorts object keys so the same data always serializes the same way
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, item)| (key.clone(), canonicalize(item)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+// Hex SHA-256 over the canonical collection and requests of an export; the other
+// fields, including "checksum" itself, aren't covered
+pub fn export_checksum(export: &Value) -> String {
+    let content = canonicalize(&serde_json::json!({
+        "collection": export.get("collection"),
+        "requests": export.get("requests"),
+    }));
+    Sha256::digest(content.to_string().as_bytes())
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+// A warning when the export carries a checksum that no longer matches its content.
+// Exports without a checksum (older versions) are accepted silently.
+pub fn checksum_warning(export: &Value) -> Option<String> {
+    let expected = export.get("checksum")?.as_str()?;
+    (expected != export_checksum(export)).then(|| {
+        "The export's checksum doesn't match its content; it was modified after export".to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HttpMethod;
+
+    #[test]
+    fn test_collection_to_markdown() {
+        let root = Collection::new("Users API".to_string(), Some("User endpoints".to_string()));
+        let folder = Collection::new_with_parent("Admin".to_string(), None, Some(root.id));
+
+        let mut list = HttpRequest::new(
+            "List users".to_string(),
+            HttpMethod::GET,
+            "{{base_url}}/users".to_string(),
+        );
+        list.collection_id = Some(root.id);
+        list.headers
+            .push(("Authorization".to_string(), "Bearer {{token}}".to_string()));
+        list.headers
+            .push(("Accept".to_string(), "application/json".to_string()));
+
+        let mut delete = HttpRequest::new(
+            "Delete user".to_string(),
+            HttpMethod::DELETE,
+            "{{base_url}}/users/:id".to_string(),
+        );
+        delete.collection_id = Some(folder.id);
+        delete.body = Some(RequestBody::Json(serde_json::json!({ "reason": "spam" })));
+
+        let markdown =
+            collection_to_markdown(&root, &[root.clone(), folder.clone()], &[list, delete]);
+
+        assert!(markdown.starts_with("# Users API\n"));
+        assert!(markdown.contains("## List users"));
+        assert!(markdown.contains("`GET {{base_url}}/users`"));
+        assert!(markdown.contains("| Header | Value |"));
+        // Headers keep the order they are sent in
+        assert!(markdown
+            .contains("| Authorization | Bearer {{token}} |\n| Accept | application/json |"));
+        assert!(markdown.contains("## Admin"));
+        assert!(markdown.contains("### Delete user"));
+        assert!(markdown.contains("```json"));
+    }
+}
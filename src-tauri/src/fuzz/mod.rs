@@ -0,0 +1,96 @@
+use anyhow::Result;
+
+use crate::models::{FormDataField, FuzzTarget, RequestBody, SendRequestPayload};
+
+// Responses are cut to this many characters in fuzz results
+pub const BODY_SNIPPET_CHARS: usize = 200;
+
+// Built-in payload sets, selectable by name
+pub fn builtin_payloads(name: &str) -> Option<Vec<String>> {
+    let payloads = match name {
+        "basic" => vec![
+            String::new(),
+            "A".repeat(10_000),
+            "' OR '1'='1".to_string(),
+            "ünïcødé 🚀 文字".to_string(),
+        ],
+        "sql" => vec![
+            "'".to_string(),
+            "' OR '1'='1".to_string(),
+            "1; DROP TABLE users--".to_string(),
+            "\" OR \"\"=\"".to_string(),
+        ],
+        "unicode" => vec![
+            "ünïcødé".to_string(),
+            "🚀🔥".to_string(),
+            "文字".to_string(),
+            "\u{202e}txt.exe".to_string(),
+            "\u{0}".to_string(),
+        ],
+        _ => return None,
+    };
+    Some(payloads)
+}
+
+// Puts the value into the targeted query param, header or top-level body field,
+// adding it when the request doesn't have one yet
+pub fn apply_fuzz_value(
+    payload: &mut SendRequestPayload,
+    target: &FuzzTarget,
+    value: &str,
+) -> Result<()> {
+    match target {
+        FuzzTarget::QueryParam(name) => {
+            let mut found = false;
+            for (key, param_value, enabled) in payload.query_params.iter_mut() {
+                if key == name {
+                    *param_value = value.to_string();
+                    *enabled = true;
+                    found = true;
+                }
+            }
+            if !found {
+                payload
+                    .query_params
+                    .push((name.clone(), value.to_string(), true));
+            }
+        }
+        FuzzTarget::Header(name) => {
+            crate::http::set_header(&mut payload.headers, name, value.to_string());
+        }
+        FuzzTarget::BodyField(name) => match payload.body.as_mut() {
+            Some(RequestBody::Json(serde_json::Value::Object(map))) => {
+                map.insert(name.clone(), serde_json::Value::String(value.to_string()));
+            }
+            Some(RequestBody::UrlEncoded(fields)) => {
+                fields.insert(name.clone(), value.to_string());
+            }
+            Some(RequestBody::FormData(fields)) => {
+                let field = FormDataField::Text {
+                    value: value.to_string(),
+                };
+                // Every part under a repeated name gets the value
+                let mut found = false;
+                for (_, existing) in fields.iter_mut().filter(|(key, _)| key == name) {
+                    *existing = field.clone();
+                    found = true;
+                }
+                if !found {
+                    fields.push((name.clone(), field));
+                }
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Body field '{}' needs a JSON object, form or URL-encoded body",
+                    name
+                ))
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn body_snippet(body: &str) -> String {
+    body.chars().take(BODY_SNIPPET_CHARS).collect()
+}
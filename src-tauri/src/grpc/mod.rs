@@ -0,0 +1,410 @@
+use anyhow::{anyhow, Result};
+use prost_reflect::prost::Message;
+use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Status;
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+use crate::models::GrpcResponse;
+
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+// Encodes any dynamic message and decodes incoming ones as `decode_as`, so calls can be
+// made against descriptors only known at runtime
+#[derive(Clone)]
+pub struct DynamicCodec {
+    decode_as: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    pub fn new(decode_as: MessageDescriptor) -> Self {
+        Self { decode_as }
+    }
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicCodec;
+    type Decoder = DynamicCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: DynamicMessage, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(format!("Failed to encode message: {}", e)))
+    }
+}
+
+impl Decoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<DynamicMessage>, Status> {
+        DynamicMessage::decode(self.decode_as.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(format!("Failed to decode message: {}", e)))
+    }
+}
+
+// Plaintext HTTP/2 connection; a missing scheme means http://
+pub async fn connect(endpoint: &str) -> Result<Channel> {
+    if crate::http::proxy_configured() {
+        return Err(anyhow!(
+            "gRPC calls can't go through the configured proxy; turn off the proxy to call {}",
+            endpoint
+        ));
+    }
+    let endpoint = if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("http://{}", endpoint)
+    };
+
+    Endpoint::from_shared(endpoint.clone())
+        .map_err(|e| anyhow!("Invalid gRPC endpoint '{}': {}", endpoint, e))?
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .connect()
+        .await
+        .map_err(|e| anyhow!("Failed to connect to {}: {}", endpoint, e))
+}
+
+// Asks the server's reflection service for the file defining `symbol`, then for any
+// dependency it didn't send along
+pub async fn fetch_descriptors(channel: Channel, symbol: &str) -> Result<DescriptorPool> {
+    let mut client = ServerReflectionClient::new(channel);
+    let mut files: Vec<FileDescriptorProto> = Vec::new();
+    let mut requested = HashSet::new();
+    let mut pending = vec![MessageRequest::FileContainingSymbol(symbol.to_string())];
+
+    while let Some(request) = pending.pop() {
+        for file in reflect(&mut client, request).await? {
+            if files.iter().any(|known| known.name == file.name) {
+                continue;
+            }
+            for dependency in &file.dependency {
+                let known = files.iter().any(|f| f.name() == dependency);
+                if !known && requested.insert(dependency.clone()) {
+                    pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                }
+            }
+            files.push(file);
+        }
+    }
+
+    DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: files })
+        .map_err(|e| anyhow!("Server returned invalid descriptors: {}", e))
+}
+
+async fn reflect(
+    client: &mut ServerReflectionClient<Channel>,
+    request: MessageRequest,
+) -> Result<Vec<FileDescriptorProto>> {
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    };
+    let mut responses = client
+        .server_reflection_info(tonic::codegen::tokio_stream::iter(vec![request]))
+        .await
+        .map_err(|status| {
+            anyhow!(
+                "Server reflection failed ({:?}): {}",
+                status.code(),
+                status.message()
+            )
+        })?
+        .into_inner();
+
+    let response = responses
+        .message()
+        .await
+        .map_err(|status| anyhow!("Server reflection failed: {}", status.message()))?
+        .ok_or_else(|| anyhow!("Server reflection returned no response"))?;
+
+    match response.message_response {
+        Some(MessageResponse::FileDescriptorResponse(files)) => files
+            .file_descriptor_proto
+            .iter()
+            .map(|bytes| {
+                FileDescriptorProto::decode(bytes.as_slice())
+                    .map_err(|e| anyhow!("Invalid file descriptor from server: {}", e))
+            })
+            .collect(),
+        Some(MessageResponse::ErrorResponse(error)) => Err(anyhow!(
+            "Server reflection error {}: {}",
+            error.error_code,
+            error.error_message
+        )),
+        _ => Err(anyhow!("Unexpected server reflection response")),
+    }
+}
+
+// Sends a unary call built from JSON. Non-OK statuses are returned, not raised, so the
+// caller can show them like an HTTP error status.
+pub async fn unary_call(
+    endpoint: &str,
+    service: &str,
+    method: &str,
+    message_json: &str,
+    use_reflection: bool,
+) -> Result<GrpcResponse> {
+    if !use_reflection {
+        return Err(anyhow!(
+            "gRPC calls need the server's descriptors; enable reflection to fetch them"
+        ));
+    }
+
+    let channel = connect(endpoint).await?;
+    let pool = fetch_descriptors(channel.clone(), service).await?;
+
+    let service_descriptor = pool
+        .get_service_by_name(service)
+        .ok_or_else(|| anyhow!("Service '{}' not found on the server", service))?;
+    let method_descriptor = service_descriptor
+        .methods()
+        .find(|m| m.name() == method)
+        .ok_or_else(|| anyhow!("Method '{}' not found in service '{}'", method, service))?;
+    if method_descriptor.is_client_streaming() || method_descriptor.is_server_streaming() {
+        return Err(anyhow!(
+            "'{}' is a streaming method; only unary calls are supported",
+            method
+        ));
+    }
+
+    let input = method_descriptor.input();
+    let mut deserializer = serde_json::Deserializer::from_str(message_json);
+    let message = DynamicMessage::deserialize(input.clone(), &mut deserializer)
+        .and_then(|message| deserializer.end().map(|_| message))
+        .map_err(|e| anyhow!("Invalid {} message: {}", input.full_name(), e))?;
+
+    let path = PathAndQuery::from_maybe_shared(format!("/{}/{}", service, method))
+        .map_err(|e| anyhow!("Invalid method path: {}", e))?;
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| anyhow!("gRPC connection not ready: {}", e))?;
+
+    let start = Instant::now();
+    let result = grpc
+        .unary(
+            tonic::Request::new(message),
+            path,
+            DynamicCodec::new(method_descriptor.output()),
+        )
+        .await;
+    let response_time = start.elapsed().as_millis() as u64;
+
+    Ok(match result {
+        Ok(response) => GrpcResponse {
+            status: tonic::Code::Ok as i32,
+            status_name: format!("{:?}", tonic::Code::Ok),
+            message: None,
+            body: Some(serde_json::to_value(response.into_inner())?),
+            response_time,
+        },
+        Err(status) => GrpcResponse {
+            status: status.code() as i32,
+            status_name: format!("{:?}", status.code()),
+            message: Some(status.message().to_string()),
+            body: None,
+            response_time,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_reflect::prost_types::field_descriptor_proto::{Label, Type};
+    use prost_reflect::prost_types::{
+        DescriptorProto, FieldDescriptorProto, MethodDescriptorProto, ServiceDescriptorProto,
+    };
+    use std::convert::Infallible;
+    use std::task::{Context, Poll};
+    use tonic::codegen::{http, BoxFuture, Service};
+
+    // test.proto: `service Echo { rpc Say(EchoRequest) returns (EchoReply); }`
+    fn echo_descriptors() -> FileDescriptorSet {
+        let field = |name: &str, number: i32, kind: Type| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(kind as i32),
+            json_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        let message = |name: &str, fields: Vec<FieldDescriptorProto>| DescriptorProto {
+            name: Some(name.to_string()),
+            field: fields,
+            ..Default::default()
+        };
+
+        FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("test.proto".to_string()),
+                package: Some("test".to_string()),
+                syntax: Some("proto3".to_string()),
+                message_type: vec![
+                    message("EchoRequest", vec![field("text", 1, Type::String)]),
+                    message(
+                        "EchoReply",
+                        vec![
+                            field("text", 1, Type::String),
+                            field("length", 2, Type::Int32),
+                        ],
+                    ),
+                ],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Echo".to_string()),
+                    method: vec![MethodDescriptorProto {
+                        name: Some("Say".to_string()),
+                        input_type: Some(".test.EchoRequest".to_string()),
+                        output_type: Some(".test.EchoReply".to_string()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+
+    // Upper-cases `text`, or fails with INVALID_ARGUMENT when it is empty
+    #[derive(Clone)]
+    struct EchoServer {
+        pool: DescriptorPool,
+    }
+
+    impl tonic::server::NamedService for EchoServer {
+        const NAME: &'static str = "test.Echo";
+    }
+
+    struct Say {
+        reply: MessageDescriptor,
+    }
+
+    impl tonic::server::UnaryService<DynamicMessage> for Say {
+        type Response = DynamicMessage;
+        type Future = BoxFuture<tonic::Response<DynamicMessage>, Status>;
+
+        fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+            let reply = self.reply.clone();
+            Box::pin(async move {
+                let text = request
+                    .into_inner()
+                    .get_field_by_name("text")
+                    .and_then(|value| value.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                if text.is_empty() {
+                    return Err(Status::invalid_argument("text is required"));
+                }
+
+                let mut message = DynamicMessage::new(reply);
+                message.set_field_by_name("length", prost_reflect::Value::I32(text.len() as i32));
+                message
+                    .set_field_by_name("text", prost_reflect::Value::String(text.to_uppercase()));
+                Ok(tonic::Response::new(message))
+            })
+        }
+    }
+
+    impl<B> Service<http::Request<B>> for EchoServer
+    where
+        B: tonic::codegen::Body + Send + 'static,
+        B::Error: Into<tonic::codegen::StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = Infallible;
+        type Future = BoxFuture<Self::Response, Infallible>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<B>) -> Self::Future {
+            let message = |name: &str| self.pool.get_message_by_name(name).unwrap();
+            let codec = DynamicCodec::new(message("test.EchoRequest"));
+            let say = Say {
+                reply: message("test.EchoReply"),
+            };
+
+            Box::pin(async move {
+                match request.uri().path() {
+                    "/test.Echo/Say" => {
+                        Ok(tonic::server::Grpc::new(codec).unary(say, request).await)
+                    }
+                    _ => Ok(Status::unimplemented("unknown method").into_http()),
+                }
+            })
+        }
+    }
+
+    async fn start_echo_server() -> String {
+        let descriptors = echo_descriptors();
+        let echo = EchoServer {
+            pool: DescriptorPool::from_file_descriptor_set(descriptors.clone()).unwrap(),
+        };
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_file_descriptor_set(descriptors)
+            .build_v1()
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming =
+            tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(reflection)
+                .add_service(echo)
+                .serve_with_incoming(incoming),
+        );
+
+        address.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_unary_call_through_reflection() {
+        let endpoint = start_echo_server().await;
+
+        let response = unary_call(&endpoint, "test.Echo", "Say", r#"{"text":"hello"}"#, true)
+            .await
+            .unwrap();
+        assert_eq!(response.status, 0);
+        assert_eq!(
+            response.body.unwrap(),
+            serde_json::json!({"text": "HELLO", "length": 5})
+        );
+
+        let response = unary_call(&endpoint, "test.Echo", "Say", "{}", true)
+            .await
+            .unwrap();
+        assert_eq!(response.status_name, "InvalidArgument");
+        assert_eq!(response.message.as_deref(), Some("text is required"));
+
+        let err = unary_call(&endpoint, "test.Echo", "Shout", "{}", true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Method 'Shout' not found"));
+    }
+}
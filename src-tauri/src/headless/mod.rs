@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use crate::commands::{self, AppState};
+use crate::db::Database;
+use crate::models::PrettyResponse;
+
+// Must match "identifier" in tauri.conf.json, which names the app data directory
+const APP_IDENTIFIER: &str = "com.dadangsutanto.geni";
+
+// Exit codes: the request ran and passed, ran and failed, or could not run
+const EXIT_PASSED: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, PartialEq)]
+pub struct HeadlessArgs {
+    pub request_id: String,
+    pub environment: Option<String>,
+    pub data_dir: Option<PathBuf>, // defaults to the GUI's app data directory
+}
+
+// Some when the binary was launched with --run-request, so the GUI should not start
+pub fn parse_args<I>(args: I) -> Option<Result<HeadlessArgs, String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut request_id = None;
+    let mut environment = None;
+    let mut data_dir = None;
+
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--run-request" => &mut request_id,
+            "--env" => &mut environment,
+            "--data-dir" => &mut data_dir,
+            _ => continue, // the GUI's own arguments
+        };
+        match args.next() {
+            Some(value) => *slot = Some(value),
+            None => return Some(Err(format!("{} needs a value", arg))),
+        }
+    }
+
+    let request_id = request_id?;
+    Some(Ok(HeadlessArgs {
+        request_id,
+        environment,
+        data_dir: data_dir.map(PathBuf::from),
+    }))
+}
+
+// Where Tauri keeps the app data on this platform
+fn default_data_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.map(|home| home.join("Library").join("Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".local").join("share")))
+    };
+    base.map(|base| base.join(APP_IDENTIFIER))
+}
+
+// Runs the saved request and its dependencies, under the named environment if one is given.
+// The environment is only used for this run; the active one stays as it is.
+pub async fn run(state: &AppState, args: &HeadlessArgs) -> Result<PrettyResponse, String> {
+    let environment_id = match &args.environment {
+        Some(name) => Some(
+            state
+                .db
+                .get_environments()
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|env| env.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Environment '{}' not found", name))?
+                .id,
+        ),
+        None => None,
+    };
+
+    commands::run_with_dependencies(state, &args.request_id, environment_id).await
+}
+
+pub fn exit_code(result: &Result<PrettyResponse, String>) -> i32 {
+    match result {
+        Ok(response)
+            if response
+                .is_success_by_criteria
+                .unwrap_or(response.status < 400) =>
+        {
+            EXIT_PASSED
+        }
+        Ok(_) => EXIT_FAILED,
+        Err(_) => EXIT_ERROR,
+    }
+}
+
+// Release builds on Windows use the GUI subsystem and start without a console, so output would
+// be lost; write to the console of the shell that launched us instead
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails when there is no parent console (e.g. started from Explorer), which is fine
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+// Entry point for --run-request: prints the response as JSON and returns the exit code
+pub fn main(args: Result<HeadlessArgs, String>) -> i32 {
+    #[cfg(windows)]
+    attach_parent_console();
+
+    let args = match args {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_ERROR;
+        }
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    runtime.block_on(async {
+        let Some(data_dir) = args.data_dir.clone().or_else(default_data_dir) else {
+            eprintln!("Could not find the app data directory; pass --data-dir");
+            return EXIT_ERROR;
+        };
+        let state = match Database::new_with_path(data_dir.join("geni_db")).await {
+            Ok(db) => AppState::load(db, data_dir).await,
+            Err(e) => Err(e),
+        };
+        let state = match state {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Failed to open the database: {}", e);
+                return EXIT_ERROR;
+            }
+        };
+
+        let result = run(&state, &args).await;
+        match &result {
+            Ok(response) => match serde_json::to_string_pretty(response) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Failed to serialize response: {}", e),
+            },
+            Err(e) => eprintln!("{}", e),
+        }
+        exit_code(&result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock_server::{response, MockServer};
+    use crate::models::{Environment, HttpMethod, HttpRequest};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_args() {
+        let args = |list: &[&str]| parse_args(list.iter().map(|arg| arg.to_string()));
+
+        assert_eq!(args(&["geni"]), None);
+        assert_eq!(
+            args(&["geni", "--run-request", "abc", "--env", "staging"]),
+            Some(Ok(HeadlessArgs {
+                request_id: "abc".to_string(),
+                environment: Some("staging".to_string()),
+                data_dir: None,
+            }))
+        );
+        assert!(matches!(args(&["geni", "--run-request"]), Some(Err(_))));
+    }
+
+    #[tokio::test]
+    async fn test_headless_run_exit_codes() {
+        let server = MockServer::start(vec![
+            response("200 OK", &[], "ok"),
+            response("500 Internal Server Error", &[], "boom"),
+        ])
+        .await;
+        let db = Database::new_embedded().await.unwrap();
+        let state = AppState::load(db, std::env::temp_dir()).await.unwrap();
+
+        let request = HttpRequest::new(
+            "Health".to_string(),
+            HttpMethod::GET,
+            "{{base_url}}/health".to_string(),
+        );
+        let request = state.db.save_request(&request).await.unwrap();
+        let variables = HashMap::from([("base_url".to_string(), server.url.clone())]);
+        let staging = Environment::new("staging".to_string(), variables);
+        state.db.create_environment(&staging).await.unwrap();
+        let production = Environment::new("production".to_string(), HashMap::new());
+        state.db.create_environment(&production).await.unwrap();
+        state
+            .db
+            .set_active_environment(Some(production.id))
+            .await
+            .unwrap();
+
+        let args = HeadlessArgs {
+            request_id: request.id.unwrap().to_string(),
+            environment: Some("Staging".to_string()),
+            data_dir: None,
+        };
+        assert_eq!(exit_code(&run(&state, &args).await), EXIT_PASSED);
+        assert_eq!(exit_code(&run(&state, &args).await), EXIT_FAILED);
+        assert!(server.requests()[0].starts_with("GET /health "));
+        // The named environment is only used for the run
+        let active = state.db.get_active_environment().await.unwrap().unwrap();
+        assert_eq!(active.id, production.id);
+
+        let missing = HeadlessArgs {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            environment: None,
+            data_dir: None,
+        };
+        assert_eq!(exit_code(&run(&state, &missing).await), EXIT_ERROR);
+    }
+}
@@ -0,0 +1,219 @@
+// Cookie jars shared by sequential requests, one per scope (the active environment). They
+// use the cookie_store engine behind reqwest's own Jar, which can't be listed or saved.
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use std::sync::RwLock;
+use url::Url;
+
+use crate::models::{ResponseCookie, StoredCookie};
+
+#[derive(Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+}
+
+impl CookieJar {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let store = cookie_store::serde::json::load_all(json.as_bytes())
+            .map_err(|e| anyhow!("Invalid cookie jar: {}", e))?;
+        Ok(Self {
+            store: RwLock::new(store),
+        })
+    }
+
+    // Session cookies are saved too, so a login survives a restart
+    pub fn to_json(&self) -> Result<String> {
+        let mut json = Vec::new();
+        let store = self.store.read().unwrap();
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut json)
+            .map_err(|e| anyhow!("Failed to save cookie jar: {}", e))?;
+        Ok(String::from_utf8(json)?)
+    }
+
+    pub fn list(&self) -> Vec<StoredCookie> {
+        self.store
+            .read()
+            .unwrap()
+            .iter_unexpired()
+            .map(|cookie| StoredCookie {
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                domain: String::from(&cookie.domain),
+                path: String::from(&cookie.path),
+                expires: match cookie.expires {
+                    CookieExpiration::AtUtc(at) => {
+                        DateTime::<Utc>::from_timestamp(at.unix_timestamp(), 0)
+                    }
+                    CookieExpiration::SessionEnd => None,
+                },
+                http_only: cookie.http_only().unwrap_or(false),
+                secure: cookie.secure().unwrap_or(false),
+            })
+            .collect()
+    }
+
+    // Adds (or replaces) a cookie as if `domain` had sent it with Set-Cookie
+    pub fn add(&self, cookie: &StoredCookie) -> Result<()> {
+        let domain = cookie.domain.trim_start_matches('.');
+        let path = if cookie.path.is_empty() {
+            "/"
+        } else {
+            &cookie.path
+        };
+        let url = Url::parse(&format!("https://{}{}", domain, path))
+            .map_err(|e| anyhow!("Invalid cookie domain '{}': {}", cookie.domain, e))?;
+
+        let mut set_cookie = format!(
+            "{}={}; Domain={}; Path={}",
+            cookie.name, cookie.value, domain, path
+        );
+        if let Some(expires) = cookie.expires {
+            set_cookie.push_str(
+                &expires
+                    .format("; Expires=%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            );
+        }
+        if cookie.http_only {
+            set_cookie.push_str("; HttpOnly");
+        }
+        if cookie.secure {
+            set_cookie.push_str("; Secure");
+        }
+
+        self.store
+            .write()
+            .unwrap()
+            .parse(&set_cookie, &url)
+            .map_err(|e| anyhow!("Invalid cookie '{}': {}", cookie.name, e))?;
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.store.write().unwrap().clear();
+    }
+}
+
+// Every Set-Cookie header of a response; values that don't parse are skipped. The
+// collapsed header map keeps only one of them, so this reads the raw map.
+pub fn response_cookies(headers: &HeaderMap) -> Vec<ResponseCookie> {
+    headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| RawCookie::parse(value).ok())
+        .map(|cookie| ResponseCookie {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain: cookie.domain().map(str::to_string),
+            path: cookie.path().map(str::to_string),
+            expires: match cookie.max_age() {
+                Some(max_age) => {
+                    Some(Utc::now() + chrono::Duration::seconds(max_age.whole_seconds()))
+                }
+                None => cookie
+                    .expires_datetime()
+                    .and_then(|at| DateTime::<Utc>::from_timestamp(at.unix_timestamp(), 0)),
+            },
+            http_only: cookie.http_only().unwrap_or(false),
+            secure: cookie.secure().unwrap_or(false),
+        })
+        .collect()
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok());
+        self.store
+            .write()
+            .unwrap()
+            .store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let value = self
+            .store
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&value)
+            .ok()
+            .filter(|_| !value.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    #[test]
+    fn test_jar_round_trips_through_json() {
+        let jar = CookieJar::default();
+        let url = Url::parse("https://api.example.com/login").unwrap();
+        let set_cookie = HeaderValue::from_static("session=abc; Path=/; HttpOnly");
+        jar.set_cookies(&mut std::iter::once(&set_cookie), &url);
+        jar.add(&StoredCookie {
+            name: "theme".to_string(),
+            value: "dark".to_string(),
+            domain: "example.com".to_string(),
+            path: "/".to_string(),
+            expires: Some(Utc::now() + chrono::Duration::days(1)),
+            http_only: false,
+            secure: true,
+        })
+        .unwrap();
+
+        let restored = CookieJar::from_json(&jar.to_json().unwrap()).unwrap();
+
+        let mut names: Vec<String> = restored.list().into_iter().map(|c| c.name).collect();
+        names.sort();
+        assert_eq!(names, vec!["session", "theme"]);
+        let header = restored.cookies(&url).unwrap();
+        assert!(header.to_str().unwrap().contains("session=abc"));
+        // Secure cookies stay off plain HTTP
+        let plain = Url::parse("http://api.example.com/").unwrap();
+        assert_eq!(restored.cookies(&plain).unwrap(), "session=abc");
+
+        restored.clear();
+        assert!(restored.list().is_empty());
+    }
+
+    #[test]
+    fn test_response_cookies_reads_every_set_cookie_header() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static(
+                "session=abc; Domain=example.com; Path=/api; Expires=Wed, 21 Oct 2026 07:28:00 GMT; HttpOnly; Secure",
+            ),
+        );
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static("theme=dark; Max-Age=60"),
+        );
+        headers.append(SET_COOKIE, HeaderValue::from_static("not a cookie"));
+
+        let cookies = response_cookies(&headers);
+
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0].name, "session");
+        assert_eq!(cookies[0].domain.as_deref(), Some("example.com"));
+        assert_eq!(cookies[0].path.as_deref(), Some("/api"));
+        assert_eq!(
+            cookies[0].expires.unwrap().to_rfc3339(),
+            "2026-10-21T07:28:00+00:00"
+        );
+        assert!(cookies[0].http_only && cookies[0].secure);
+        assert_eq!(cookies[1].value, "dark");
+        assert!(cookies[1].path.is_none() && !cookies[1].http_only);
+        assert!(cookies[1].expires.unwrap() > Utc::now());
+    }
+}
@@ -0,0 +1,70 @@
+// Content-Encoding support. reqwest's own decompression drops the header and the wire
+// size, so bodies are read as sent and decoded here instead.
+use anyhow::{anyhow, Result};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use std::io::Read;
+
+// Sent unless the request sets its own Accept-Encoding
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+// Undoes every coding in a Content-Encoding value, last applied first
+pub fn decode(body: &[u8], content_encoding: &str) -> Result<Vec<u8>> {
+    let mut decoded = body.to_vec();
+    for coding in content_encoding.rsplit(',').map(str::trim) {
+        decoded = match coding.to_ascii_lowercase().as_str() {
+            "" | "identity" => decoded,
+            "gzip" | "x-gzip" => read_all(GzDecoder::new(decoded.as_slice()))?,
+            // Meant to be zlib-wrapped, but some servers send a raw deflate stream
+            "deflate" => read_all(ZlibDecoder::new(decoded.as_slice()))
+                .or_else(|_| read_all(DeflateDecoder::new(decoded.as_slice())))?,
+            "br" => read_all(brotli::Decompressor::new(decoded.as_slice(), 4096))?,
+            other => return Err(anyhow!("Unsupported content encoding '{}'", other)),
+        };
+    }
+    Ok(decoded)
+}
+
+fn read_all(mut reader: impl Read) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decode_handles_each_coding() {
+        assert_eq!(decode(&gzip(b"hello"), "gzip").unwrap(), b"hello");
+
+        let mut brotli_body = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut brotli_body, 4096, 5, 22);
+            writer.write_all(b"hello").unwrap();
+        }
+        assert_eq!(decode(&brotli_body, "br").unwrap(), b"hello");
+
+        let mut raw_deflate =
+            flate2::write::DeflateEncoder::new(Vec::new(), Compression::default());
+        raw_deflate.write_all(b"hello").unwrap();
+        assert_eq!(
+            decode(&raw_deflate.finish().unwrap(), "deflate").unwrap(),
+            b"hello"
+        );
+
+        // Stacked codings are undone in reverse
+        assert_eq!(decode(&gzip(&gzip(b"hi")), "gzip, x-gzip").unwrap(), b"hi");
+        assert!(decode(b"hello", "zstd").is_err());
+        assert!(decode(b"not gzip", "gzip").is_err());
+    }
+}
@@ -0,0 +1,150 @@
+// reqwest hides 1xx responses, so requests that want them recorded are sent over a
+// plain hyper HTTP/1.1 connection instead. This path doesn't use the shared client
+// settings (custom CA bundle, connection pooling) and HTTP/2 never reports 1xx here.
+use anyhow::{anyhow, Result};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper_util::rt::TokioIo;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use crate::models::{InformationalResponse, ResponseCookie, ResponseHeaders, Timings};
+
+pub struct RawResponse {
+    pub status: u16,
+    pub headers: ResponseHeaders,
+    pub body: Bytes,
+    pub informational: Vec<InformationalResponse>,
+    pub cookies: Vec<ResponseCookie>,
+    pub timings: Timings, // every phase measured apart, as the connection is our own
+}
+
+fn header_map_to_hash(headers: &hyper::HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_str().unwrap_or("").to_string()))
+        .collect()
+}
+
+pub async fn send_http1(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+    timeout: Duration,
+    insecure: bool, // skip certificate verification
+) -> Result<RawResponse> {
+    let uri: hyper::Uri = url.parse()?;
+    let https = match uri.scheme_str() {
+        Some("http") => false,
+        Some("https") => true,
+        _ => return Err(anyhow!("Only http and https URLs are supported")),
+    };
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow!("URL has no host"))?
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+    let mut builder = hyper::Request::builder().method(method).uri(path);
+    if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("host")) {
+        let host_header = match uri.port_u16() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.clone(),
+        };
+        builder = builder.header("Host", host_header);
+    }
+    if !headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("user-agent"))
+    {
+        builder = builder.header("User-Agent", "Geni API Client/0.1.0");
+    }
+    for (key, value) in headers {
+        builder = builder.header(key, value);
+    }
+    let mut request = builder.body(Full::new(Bytes::from(body)))?;
+
+    let informational = Arc::new(Mutex::new(Vec::new()));
+    let captured = informational.clone();
+    hyper::ext::on_informational(&mut request, move |res| {
+        captured.lock().unwrap().push(InformationalResponse {
+            status: res.status().as_u16(),
+            headers: header_map_to_hash(res.headers()),
+        });
+    });
+
+    let exchange = async {
+        let mut timings = Timings::default();
+        let start = Instant::now();
+        let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), port))
+            .await?
+            .collect();
+        timings.dns_lookup = Some(elapsed_ms(start));
+
+        let start = Instant::now();
+        let tcp = TcpStream::connect(&addrs[..]).await?;
+        timings.tcp_connect = Some(elapsed_ms(start));
+        let (parts, body) = if https {
+            let connector = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(insecure)
+                .build()?;
+            let connector = tokio_native_tls::TlsConnector::from(connector);
+            let start = Instant::now();
+            let tls = connector.connect(&host, tcp).await?;
+            timings.tls_handshake = Some(elapsed_ms(start));
+            exchange(TokioIo::new(tls), request, &mut timings).await?
+        } else {
+            exchange(TokioIo::new(tcp), request, &mut timings).await?
+        };
+        Ok::<_, anyhow::Error>((parts, body, timings))
+    };
+    let (parts, body, timings) = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| anyhow!("Request timed out after {}s", timeout.as_secs()))??;
+
+    let informational = std::mem::take(&mut *informational.lock().unwrap());
+    Ok(RawResponse {
+        status: parts.status.as_u16(),
+        headers: parts
+            .headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_str().unwrap_or("").to_string()))
+            .collect(),
+        body,
+        informational,
+        cookies: super::cookies::response_cookies(&parts.headers),
+        timings,
+    })
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+async fn exchange<T>(
+    io: T,
+    request: hyper::Request<Full<Bytes>>,
+    timings: &mut Timings,
+) -> Result<(hyper::http::response::Parts, Bytes)>
+where
+    T: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(io).await?;
+    tokio::spawn(async move {
+        let _ = connection.await;
+    });
+
+    let start = Instant::now();
+    let response = sender.send_request(request).await?;
+    timings.time_to_first_byte = elapsed_ms(start);
+
+    let start = Instant::now();
+    let (parts, body) = response.into_parts();
+    let body = body.collect().await?.to_bytes();
+    timings.content_download = elapsed_ms(start);
+    Ok((parts, body))
+}
@@ -0,0 +1,119 @@
+// Minimal HTTP/1.1 server used by tests to capture outgoing requests
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    // Responses are served in order; the last one is repeated for extra connections
+    pub async fn start(responses: Vec<String>) -> Self {
+        Self::start_raw(responses.into_iter().map(String::into_bytes).collect()).await
+    }
+
+    // Like `start`, for responses with binary bodies
+    pub async fn start_raw(responses: Vec<Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let raw = read_request(&mut stream).await;
+                recorded.lock().unwrap().push(raw);
+
+                let response = &responses[index.min(responses.len() - 1)];
+                index += 1;
+                let _ = stream.write_all(response).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    // Like `start`, over TLS with the given server identity
+    pub async fn start_tls(responses: Vec<String>, identity: native_tls::Identity) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("https://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let acceptor =
+            tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    continue;
+                };
+                let raw = read_request(&mut stream).await;
+                recorded.lock().unwrap().push(raw);
+
+                let response = &responses[index.min(responses.len() - 1)];
+                index += 1;
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let mut raw = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (key, value) in headers {
+        raw.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    raw.push_str("\r\n");
+    raw.push_str(body);
+    raw
+}
+
+async fn read_request(stream: &mut (impl AsyncRead + Unpin)) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+
+        let text = String::from_utf8_lossy(&data).to_string();
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let head = text[..header_end].to_lowercase();
+            let body_len = data.len() - header_end - 4;
+            let content_length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok());
+
+            let complete = match content_length {
+                Some(len) => body_len >= len,
+                None if head.contains("transfer-encoding: chunked") => text.ends_with("0\r\n\r\n"),
+                None => true,
+            };
+            if complete {
+                break;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&data).to_string()
+}
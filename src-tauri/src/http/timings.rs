@@ -0,0 +1,185 @@
+// Connection setup timings for the request being sent. reqwest has no per-request hooks,
+// so the shared clients resolve and connect through wrappers that report into a recorder
+// set for the duration of each send. A request that reuses a pooled connection records
+// nothing. The connector runs TCP and TLS as one future, so the wrapper follows its polls:
+// the socket connecting is the first wake-up after the TCP connect starts, and the TLS
+// handshake starts in that same poll. Through a proxy, the tunnel setup counts toward the
+// handshake.
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower_layer::Layer;
+use tower_service::Service;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConnectionTimings {
+    pub dns_lookup: Option<Duration>,
+    pub tcp_connect: Option<Duration>,
+    pub tls_handshake: Option<Duration>, // None unless a connection did one
+    pub connect: Option<Duration>,       // DNS lookup and TLS handshake included
+    lookups_in_flight: usize,
+}
+
+tokio::task_local! {
+    static RECORDER: Arc<Mutex<ConnectionTimings>>;
+}
+
+// Runs a send, collecting the connection timings of every connection it opens
+pub async fn record<F: Future>(send: F) -> (F::Output, ConnectionTimings) {
+    let recorder = Arc::new(Mutex::new(ConnectionTimings::default()));
+    let output = RECORDER.scope(recorder.clone(), send).await;
+    let timings = *recorder.lock().unwrap();
+    (output, timings)
+}
+
+// Outside `record`, or when the connect runs on a background task, this is a no-op
+fn add(update: impl FnOnce(&mut ConnectionTimings)) {
+    let _ = RECORDER.try_with(|recorder| update(&mut recorder.lock().unwrap()));
+}
+
+fn lookups_in_flight() -> usize {
+    RECORDER
+        .try_with(|recorder| recorder.lock().unwrap().lookups_in_flight)
+        .unwrap_or(0)
+}
+
+fn sum(total: Option<Duration>, elapsed: Duration) -> Option<Duration> {
+    Some(total.unwrap_or_default() + elapsed)
+}
+
+// The system resolver, as reqwest uses by default, timed
+pub struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            add(|timings| timings.lookups_in_flight += 1);
+            let start = Instant::now();
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await;
+            add(|timings| timings.lookups_in_flight -= 1);
+            let addrs = addrs?;
+            add(|timings| timings.dns_lookup = sum(timings.dns_lookup, start.elapsed()));
+            Ok(Box::new(addrs.collect::<Vec<_>>().into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct TimedConnectLayer;
+
+impl<S> Layer<S> for TimedConnectLayer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct TimedConnect<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for TimedConnect<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        Box::pin(TimedConnecting {
+            inner: Box::pin(self.inner.call(request)),
+            start: Instant::now(),
+            phase: Phase::Resolving,
+        })
+    }
+}
+
+// How far a connection has got, as seen between polls of its connect future
+#[derive(Clone, Copy)]
+enum Phase {
+    Resolving,
+    Connecting {
+        since: Instant,
+    },
+    Handshaking {
+        tcp: Duration,
+        since: Instant,
+        first_poll: bool,
+    },
+}
+
+struct TimedConnecting<F> {
+    inner: Pin<Box<F>>,
+    start: Instant,
+    phase: Phase,
+}
+
+impl<F, T, E> Future for TimedConnecting<F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let polled_at = Instant::now();
+        self.phase = match self.phase {
+            Phase::Connecting { since } => Phase::Handshaking {
+                tcp: polled_at - since,
+                since: polled_at,
+                first_poll: true,
+            },
+            Phase::Handshaking { tcp, since, .. } => Phase::Handshaking {
+                tcp,
+                since,
+                first_poll: false,
+            },
+            phase => phase,
+        };
+
+        let result = self.inner.as_mut().poll(cx);
+        let now = Instant::now();
+        match &result {
+            // Once no lookup is in flight the TCP connect is under way (an IP address
+            // needs no lookup at all)
+            Poll::Pending if matches!(self.phase, Phase::Resolving) && lookups_in_flight() == 0 => {
+                self.phase = Phase::Connecting { since: now };
+            }
+            Poll::Pending | Poll::Ready(Err(_)) => {}
+            Poll::Ready(Ok(_)) => {
+                let connect = now - self.start;
+                // Ready in the poll the socket connected means no handshake followed
+                let (tcp, tls) = match self.phase {
+                    Phase::Handshaking {
+                        tcp,
+                        since,
+                        first_poll: false,
+                    } => (Some(tcp), Some(now - since)),
+                    Phase::Handshaking { tcp, .. } => (Some(tcp), None),
+                    Phase::Connecting { since } => (Some(now - since), None),
+                    Phase::Resolving => (None, None),
+                };
+                add(|timings| {
+                    timings.connect = sum(timings.connect, connect);
+                    if let Some(tcp) = tcp {
+                        timings.tcp_connect = sum(timings.tcp_connect, tcp);
+                    }
+                    if let Some(tls) = tls {
+                        timings.tls_handshake = sum(timings.tls_handshake, tls);
+                    }
+                });
+            }
+        }
+        result
+    }
+}
@@ -2,20 +2,27 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod contract;
 mod db;
+mod dotenv;
+mod export;
+mod fuzz;
+mod grpc;
+mod headless;
 mod http;
 mod models;
+mod oauth;
 mod openapi;
 mod postman;
+mod request_log;
+mod secrets;
+mod share;
 mod sync;
+mod url_list;
 
 use commands::AppState;
 use db::Database;
-use http::HttpClient;
-use std::sync::Arc;
-use sync::{ProviderConfig, SyncClient, SyncProvider};
 use tauri::{Builder, Manager};
-use tokio::sync::Mutex;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -24,6 +31,11 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // `--run-request <id>` runs a saved request without opening a window (e.g. in CI)
+    if let Some(args) = headless::parse_args(std::env::args()) {
+        std::process::exit(headless::main(args));
+    }
+
     Builder::default()
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
@@ -47,30 +59,10 @@ pub fn run() {
                 let db = Database::new_with_path(app_data_dir.join("geni_db"))
                     .await
                     .expect("Failed to initialize database");
-                let http_client = HttpClient::new();
-
-                // Initialize sync client with default provider (API Server for demo)
-                // TODO: Load from config or settings
-                let config = ProviderConfig {
-                    provider: SyncProvider::ApiServer,
-                    api_server_url: Some("http://localhost:3000".to_string()),
-                    supabase_url: None,
-                    supabase_api_key: None,
-                    supabase_db_uri: None,
-                    google_client_id: None,
-                    google_client_secret: None,
-                    google_redirect_uri: None,
-                };
 
-                let sync_client =
-                    SyncClient::new(config).expect("Failed to initialize sync client");
-                let sync_client = Arc::new(Mutex::new(sync_client));
-
-                let state = AppState {
-                    db,
-                    http_client,
-                    sync_client,
-                };
+                let state = AppState::load(db, app_data_dir.clone())
+                    .await
+                    .expect("Failed to initialize app state");
 
                 // Manage the state so it's available to all commands
                 app.manage(state);
@@ -84,39 +76,122 @@ pub fn run() {
             greet,
             // HTTP request commands
             commands::send_request,
+            commands::send_request_streaming,
+            commands::cancel_request,
+            commands::list_timeout_presets,
+            commands::save_timeout_preset,
+            commands::get_format_preferences,
+            commands::set_format_preference,
             // Collection commands
             commands::create_collection,
             commands::get_collections,
             commands::delete_collection,
             commands::move_collection,
             commands::update_collection_auth,
+            commands::update_collection_defaults,
+            commands::save_auth_preset,
+            commands::list_auth_presets,
+            commands::apply_auth_preset,
             commands::update_collection_name,
+            // OAuth2 commands
+            commands::oauth2_get_auth_url,
+            commands::oauth2_exchange_code,
             // Request commands
             commands::save_request,
+            commands::send_and_save,
+            commands::save_draft,
+            commands::get_draft,
+            commands::discard_draft,
+            commands::save_request_view_state,
+            commands::get_request_view_state,
+            commands::set_request_alias,
+            commands::set_request_auth,
+            commands::resolve_alias,
+            commands::set_request_dependencies,
+            commands::run_request_with_deps,
+            commands::set_request_environment_exclusions,
+            commands::run_collection,
+            commands::get_collection_request_counts,
+            commands::grpc_unary_call,
             commands::get_requests,
             commands::delete_request,
             commands::move_request,
             commands::update_request_name,
+            commands::save_response_example,
+            commands::compare_to_example,
+            commands::fuzz_request,
+            commands::suggest_extractors,
             // Environment commands
             commands::create_environment,
             commands::get_environments,
             commands::set_active_environment,
             commands::get_active_environment,
             commands::update_environment,
+            commands::set_environment_base_url_override,
             commands::delete_environment,
+            commands::import_system_env,
+            commands::import_dotenv,
+            commands::environment_to_dotenv,
+            commands::diff_environments,
             // History commands
             commands::get_request_history,
             commands::clear_request_history,
+            commands::get_history_body_length,
+            commands::get_history_body_window,
+            commands::render_response_as,
+            // Database maintenance commands
+            commands::get_database_stats,
+            commands::compact_database,
+            commands::get_corrupt_records,
+            commands::delete_corrupt_record,
+            // TLS settings commands
+            commands::set_custom_ca_bundle,
+            commands::clear_custom_ca_bundle,
+            // Proxy settings commands
+            commands::get_proxy_settings,
+            commands::set_proxy_settings,
+            commands::clear_proxy_settings,
+            // Cookie jar commands
+            commands::set_cookie_jar_enabled,
+            commands::get_cookie_jar_enabled,
+            commands::get_cookies,
+            commands::add_cookie,
+            commands::clear_cookies,
+            // Request log commands
+            commands::enable_request_log,
+            commands::disable_request_log,
             // Utility commands
             commands::format_json,
+            commands::save_response_body,
+            commands::analyze_json,
+            commands::merge_json,
             commands::validate_url,
             commands::extract_env_variables,
             commands::extract_path_params,
+            commands::parse_query_params,
+            commands::build_url_with_params,
+            commands::inspect_jwt,
+            commands::scan_request_for_secrets,
+            commands::check_mixed_content,
+            commands::validate_request,
+            commands::redact_response,
             commands::highlight_response,
+            commands::set_respect_content_type_strictly,
+            commands::get_respect_content_type_strictly,
             // Import/Export commands
             commands::export_collection,
+            commands::export_collection_markdown,
+            commands::export_workspace,
+            commands::import_workspace,
             commands::import_collection,
             commands::import_postman_collection,
+            commands::import_postman_folder,
+            commands::import_url_list,
+            commands::import_postman_collection_merge,
+            commands::import_openapi_filtered,
+            // Request sharing commands
+            commands::encode_request_link,
+            commands::decode_request_link,
             // Cloud Sync commands - Configuration
             commands::initialize_sync,
             commands::load_saved_sync_config,
@@ -135,9 +210,13 @@ pub fn run() {
             commands::logout,
             commands::is_authenticated,
             commands::get_current_user,
+            commands::get_provider_capabilities,
             commands::sync_push,
             commands::sync_pull,
             commands::sync_full,
+            commands::reset_sync_state,
+            commands::start_auto_sync,
+            commands::stop_auto_sync,
             commands::get_sync_status,
         ])
         .run(tauri::generate_context!())
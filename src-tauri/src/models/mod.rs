@@ -11,6 +11,16 @@ pub enum AuthType {
     Basic,
     #[serde(rename = "bearer")]
     Bearer,
+    #[serde(rename = "oauth2_auth_code")]
+    OAuth2AuthCode,
+    #[serde(rename = "api_key")]
+    ApiKey,
+    #[serde(rename = "oauth2_client_credentials")]
+    OAuth2ClientCredentials,
+    #[serde(rename = "digest")]
+    Digest,
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,12 +34,126 @@ pub struct BearerAuth {
     pub token: String,
 }
 
+// Credentials for answering an HTTP Digest challenge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestAuth {
+    pub username: String,
+    pub password: String,
+}
+
+// AWS Signature Version 4; the request is signed just before it is sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsSigV4Auth {
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+    pub service: String, // e.g. "s3", "execute-api"
+    #[serde(default)]
+    pub session_token: Option<String>, // for temporary credentials
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyPlacement {
+    Header,
+    Query,
+}
+
+// Client certificate for mutual TLS: a PKCS#12 archive (.p12/.pfx) or a PEM certificate
+// chain with its PKCS#8 private key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCertificate {
+    pub cert_path: String,
+    #[serde(default)]
+    pub key_path: Option<String>, // PEM key, when it isn't in the certificate file
+    #[serde(default)]
+    pub passphrase: Option<String>, // PKCS#12 only
+}
+
+// Outgoing proxy for every HTTP client: http(s):// or socks5(h):// with optional
+// credentials. Hosts in `no_proxy` (e.g. "localhost", ".internal.corp") go direct.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxySettings {
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+// A cookie held in a cookie jar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>, // None for session cookies
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+}
+
+// A named key sent as a header (e.g. X-API-Key) or a query parameter (e.g. api_key)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyAuth {
+    pub name: String,
+    pub value: String,
+    pub placement: ApiKeyPlacement,
+}
+
+// Authorization-code flow (with PKCE) against any OAuth2 provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuth2AuthCode {
+    pub auth_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub token: Option<OAuth2Token>, // set once the code has been exchanged
+}
+
+// Client-credentials grant for machine-to-machine APIs; the token is fetched at send time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuth2ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuth2Token {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
     #[serde(rename = "type")]
     pub auth_type: AuthType,
     pub basic: Option<BasicAuth>,
     pub bearer: Option<BearerAuth>,
+    #[serde(default)]
+    pub oauth2: Option<OAuth2AuthCode>,
+    #[serde(default)]
+    pub api_key: Option<ApiKeyAuth>,
+    #[serde(default)]
+    pub oauth2_client_credentials: Option<OAuth2ClientCredentials>,
+    #[serde(default)]
+    pub digest: Option<DigestAuth>,
+    #[serde(default)]
+    pub aws_sigv4: Option<AwsSigV4Auth>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,10 +162,13 @@ pub struct HttpRequest {
     pub name: String,
     pub method: HttpMethod,
     pub url: String,
-    pub headers: HashMap<String, String>,
+    #[serde(with = "ordered_headers")]
+    pub headers: Vec<(String, String)>, // in the order they are sent
     pub body: Option<RequestBody>,
     #[serde(default)]
     pub path_params: HashMap<String, String>,
+    #[serde(default)]
+    pub query_params: Vec<(String, String, bool)>, // (key, value, enabled)
     pub collection_id: Option<Uuid>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -50,18 +177,205 @@ pub struct HttpRequest {
     #[serde(default)]
     pub version: i64,
     pub cloud_id: Option<String>,
+    #[serde(default)]
+    pub last_status: Option<u16>,
+    #[serde(default)]
+    pub last_response_time: Option<u64>, // in milliseconds
+    #[serde(default)]
+    pub last_run_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub examples: Vec<SavedResponse>, // named example responses, used as contract baselines
+    #[serde(default)]
+    pub success_criteria: Option<SuccessCriteria>,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>, // overrides the collection's auth when set
+    #[serde(default)]
+    pub alias: Option<String>, // short handle, unique across the workspace
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>, // requests run first by run_request_with_deps
+    #[serde(default)]
+    pub depends_on_cloud_ids: Vec<String>, // depends_on as cloud ids, filled in when pushed
+    #[serde(default)]
+    pub disabled_in_environments: Vec<String>, // environment names run_collection skips it in
+}
+
+// Headers are written as a list of [name, value] pairs, which keeps their order and repeated names
+// through JSONB columns; older data written as a map is still read
+mod ordered_headers {
+    pub fn serialize<S>(headers: &[(String, String)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(headers)
+    }
+
+    // Accepts a map (kept in document order) or a list of pairs
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        V: serde::Deserialize<'de>,
+    {
+        struct PairsVisitor<V>(std::marker::PhantomData<V>);
+
+        impl<'de, V: serde::Deserialize<'de>> serde::de::Visitor<'de> for PairsVisitor<V> {
+            type Value = Vec<(String, V)>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a list of key-value pairs or a map")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut pairs = Vec::new();
+                while let Some(pair) = seq.next_element()? {
+                    pairs.push(pair);
+                }
+                Ok(pairs)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut pairs = Vec::new();
+                while let Some(pair) = map.next_entry()? {
+                    pairs.push(pair);
+                }
+                Ok(pairs)
+            }
+        }
+
+        deserializer.deserialize_any(PairsVisitor(std::marker::PhantomData))
+    }
+}
+
+// Form fields are written as a JSON object while their keys are unique, which is what the UI
+// reads, and as a list of [key, field] pairs once a key repeats (e.g. several `files[]`)
+mod form_fields {
+    use super::FormDataField;
+
+    pub fn serialize<S>(
+        fields: &[(String, FormDataField)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let unique_keys = fields
+            .iter()
+            .enumerate()
+            .all(|(i, (key, _))| fields[..i].iter().all(|(other, _)| other != key));
+        if unique_keys {
+            serializer.collect_map(fields.iter().map(|(key, field)| (key, field)))
+        } else {
+            serializer.collect_seq(fields)
+        }
+    }
+
+    pub use super::ordered_headers::deserialize;
+}
+
+// Captures the value at `path` (JSONPath) of a response into `variable`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseExtractor {
+    pub variable: String,
+    pub path: String,
+}
+
+// Decides pass/fail for APIs that don't signal errors through the status code alone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SuccessCriteria {
+    StatusRange(u16, u16), // inclusive
+    JsonPathEquals {
+        path: String,
+        value: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedResponse {
+    pub name: String,
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractResult {
+    pub matches: bool,
+    pub differences: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub status: u16,
     pub status_text: String,
-    pub headers: HashMap<String, String>,
+    pub headers: ResponseHeaders,
     pub body: String,
     pub response_time: u64, // in milliseconds
     pub size: usize,        // in bytes
 }
 
+// Response headers in the order they arrived. Names may repeat (Set-Cookie, Vary), so
+// this is a list of pairs rather than a map; lookups ignore case.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResponseHeaders(pub Vec<(String, String)>);
+
+impl ResponseHeaders {
+    // First value of a header, for the ones that only appear once (e.g. Content-Type)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    // One value per name, repeated headers joined with ", " as RFC 9110 allows
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut map: HashMap<String, String> = HashMap::new();
+        for (key, value) in self.iter() {
+            map.entry(key.to_string())
+                .and_modify(|joined| {
+                    joined.push_str(", ");
+                    joined.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+        map
+    }
+}
+
+impl FromIterator<(String, String)> for ResponseHeaders {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+// History saved before duplicates were kept stored headers as a map
+impl<'de> Deserialize<'de> for ResponseHeaders {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            List(Vec<(String, String)>),
+            Map(HashMap<String, String>),
+        }
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::List(list) => Self(list),
+            Stored::Map(map) => map.into_iter().collect(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HttpMethod {
     GET,
@@ -100,8 +414,46 @@ pub enum RequestBody {
         content_type: String,
     },
     Json(serde_json::Value),
-    FormData(HashMap<String, FormDataField>),
+    FormData(#[serde(with = "form_fields")] Vec<(String, FormDataField)>), // keys may repeat
     UrlEncoded(HashMap<String, String>),
+    BinaryFile {
+        path: String, // sent as the whole body, typed by its extension
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutPreset {
+    pub name: String,
+    pub seconds: u64,
+}
+
+impl TimeoutPreset {
+    pub fn defaults() -> Vec<Self> {
+        [("fast", 5), ("default", 30), ("long-poll", 300)]
+            .into_iter()
+            .map(|(name, seconds)| Self {
+                name: name.to_string(),
+                seconds,
+            })
+            .collect()
+    }
+}
+
+// How a response body of a given content type is shown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FormatMode {
+    Pretty, // format the body for its content type (the default)
+    Raw,    // show the body exactly as received
+}
+
+// Reusable auth settings; values may contain {{variables}} resolved at send time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthPreset {
+    pub id: Uuid,
+    pub name: String,
+    pub auth: AuthConfig,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +470,10 @@ pub struct Collection {
     #[serde(default)]
     pub version: i64,
     pub cloud_id: Option<String>,
+    #[serde(default)]
+    pub default_timeout: Option<u64>, // in seconds, for requests that don't set one
+    #[serde(default)]
+    pub default_retries: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,6 +489,23 @@ pub struct Environment {
     #[serde(default)]
     pub version: i64,
     pub cloud_id: Option<String>,
+    #[serde(default)]
+    pub base_url_override: Option<String>, // replaces the scheme and host of every request
+}
+
+// Variable differences between two environments, keys sorted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub differing: Vec<EnvValueDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvValueDiff {
+    pub key: String,
+    pub value_a: Option<String>, // None for credential-like keys, which only report that they differ
+    pub value_b: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,17 +514,103 @@ pub struct RequestHistory {
     pub request: HttpRequest,
     pub response: Option<HttpResponse>,
     pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub environment_name: Option<String>, // environment active when the request ran
+    #[serde(default)]
+    pub resolved_url: String, // URL actually sent, credentials redacted
+    #[serde(default)]
+    pub resolved_headers: Option<Vec<(String, String)>>, // headers actually sent, auth included, credentials redacted; None in older entries
+    #[serde(default)]
+    pub resolved_body: Option<RequestBody>, // body actually sent, secret variable values redacted
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendRequestPayload {
     pub method: HttpMethod,
     pub url: String,
-    pub headers: HashMap<String, String>,
+    #[serde(with = "ordered_headers")]
+    pub headers: Vec<(String, String)>, // in the order they are sent
     pub body: Option<RequestBody>,
     #[serde(default)]
     pub path_params: HashMap<String, String>,
+    #[serde(default)]
+    pub query_params: Vec<(String, String, bool)>, // (key, value, enabled)
     pub timeout: Option<u64>, // in seconds
+    #[serde(default)]
+    pub timeout_preset: Option<String>, // used when `timeout` isn't set
+    #[serde(default)]
+    pub connect_timeout: Option<u64>, // in seconds, separate from the total `timeout`
+    #[serde(default)]
+    pub body_file: Option<String>, // raw/JSON body loaded from this file at send time
+    #[serde(default)]
+    pub body_file_raw: bool, // send the body file verbatim, without variable substitution
+    #[serde(default)]
+    pub collection_id: Option<String>, // collection whose auth applies to this request
+    #[serde(default)]
+    pub request_id: Option<String>, // saved request whose last-run summary is updated
+    #[serde(default)]
+    pub capture_informational: bool, // record 1xx responses (HTTP/1.1 only)
+    #[serde(default)]
+    pub force_no_body: bool, // never send a body, whatever the method
+    #[serde(default)]
+    pub success_criteria: Option<SuccessCriteria>,
+    #[serde(default)]
+    pub method_override: bool, // send as POST with X-HTTP-Method-Override
+    #[serde(default = "default_true")]
+    pub apply_base_url_override: bool, // false opts out of the environment's host override
+    #[serde(default)]
+    pub resolve_trace: Option<bool>, // report how each {{token}} was resolved
+    #[serde(default)]
+    pub user_agent: Option<String>, // replaces the client's User-Agent for this request
+    #[serde(default)]
+    pub retry_count: Option<u32>, // extra attempts after a failure listed in `retry_on`
+    #[serde(default)]
+    pub ephemeral: bool, // leave no trace: no history, request log, last-run summary or stored token
+    #[serde(default)]
+    pub digest_auth: Option<DigestAuth>, // answers a 401 Digest challenge with these
+    #[serde(default)]
+    pub aws_sigv4: Option<AwsSigV4Auth>, // signs the request as it is sent
+    #[serde(default)]
+    pub auth_headers: Vec<String>, // names of headers added by auth, dropped on a cross-origin redirect
+    #[serde(default)]
+    pub follow_redirects: Option<bool>, // false returns the 3xx response itself
+    #[serde(default)]
+    pub max_redirects: Option<usize>, // defaults to 10 when following
+    #[serde(default)]
+    pub insecure: Option<bool>, // skip TLS certificate verification (self-signed dev servers)
+    #[serde(default)]
+    pub client_certificate: Option<ClientCertificate>, // presented for mutual TLS
+    #[serde(default)]
+    pub cookie_jar: Option<String>, // scope of the jar whose cookies are sent and updated
+    #[serde(default)]
+    pub retry_on: Option<Vec<RetryCondition>>, // defaults to network errors only
+    #[serde(default)]
+    pub retry_backoff_ms: Option<u64>, // first delay between attempts, doubled after each
+    #[serde(default)]
+    pub download_to: Option<String>, // stream the body to this file instead of memory
+    #[serde(default)]
+    pub decompress: Option<bool>, // false keeps a compressed body as sent; defaults to true
+    #[serde(default)]
+    pub environment_id: Option<String>, // run under this environment instead of the active one
+}
+
+// What makes a request worth another attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCondition {
+    NetworkError,
+    Status(u16), // e.g. 429 or 503
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SendRequestPayload {
+    // HEAD requests never carry a body
+    pub fn sends_body(&self) -> bool {
+        !self.force_no_body && !matches!(self.method, HttpMethod::HEAD)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -167,11 +626,18 @@ pub struct SaveRequestPayload {
     pub name: String,
     pub method: HttpMethod,
     pub url: String,
-    pub headers: HashMap<String, String>,
+    #[serde(with = "ordered_headers")]
+    pub headers: Vec<(String, String)>, // in the order they are sent
     pub body: Option<RequestBody>,
     #[serde(default)]
     pub path_params: HashMap<String, String>,
+    #[serde(default)]
+    pub query_params: Vec<(String, String, bool)>,
     pub collection_id: Option<String>,
+    #[serde(default)]
+    pub success_criteria: Option<SuccessCriteria>,
+    #[serde(default)]
+    pub draft_key: Option<String>, // draft of an unsaved tab, discarded once it is saved
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -198,16 +664,274 @@ pub struct UpdateRequestNamePayload {
     pub name: String,
 }
 
+// The stored request and the response to sending exactly that request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendAndSaveResult {
+    pub request: HttpRequest,
+    pub response: PrettyResponse,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrettyResponse {
     pub status: u16,
     pub status_text: String,
-    pub headers: HashMap<String, String>,
+    pub headers: ResponseHeaders,
     pub body: String,
     pub formatted_body: Option<String>,
     pub highlighted_body: Option<String>,
     pub response_time: u64,
     pub size: usize,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub informational: Vec<InformationalResponse>, // 1xx responses received before the final one
+    #[serde(default)]
+    pub redirect_chain: Vec<RedirectHop>, // every response along a followed redirect chain
+    #[serde(default)]
+    pub is_success_by_criteria: Option<bool>, // set when the request has success criteria
+    #[serde(default)]
+    pub request_size: usize, // bytes of request body sent
+    #[serde(default)]
+    pub transfer_encoding: Option<String>,
+    #[serde(default)]
+    pub trailers: HashMap<String, String>, // HTTP trailers sent after the body
+    #[serde(default)]
+    pub body_incomplete: bool, // the connection ended before the whole body arrived
+    #[serde(default)]
+    pub variable_trace: Option<VariableTrace>, // set when the request asked for a resolve trace
+    #[serde(default)]
+    pub cookies: Vec<ResponseCookie>, // parsed from every Set-Cookie header
+    #[serde(default)]
+    pub attempts: u32, // 1 unless the request was retried
+    #[serde(default)]
+    pub total_time: u64, // ms across every attempt and backoff; response_time is the last one
+    #[serde(default)]
+    pub saved_to: Option<String>, // file the body was streamed to; `body` is then empty
+    #[serde(default)]
+    pub timings: Timings,
+    #[serde(default)]
+    pub content_encoding: Option<String>, // as sent by the server; `size` is still decoded
+    #[serde(default)]
+    pub compressed_size: Option<usize>, // bytes on the wire when the body was encoded
+}
+
+// Where the final attempt's time went, in ms. Connection phases are None when a pooled
+// connection was reused.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timings {
+    pub dns_lookup: Option<u64>,
+    pub tcp_connect: Option<u64>,
+    pub tls_handshake: Option<u64>,
+    pub time_to_first_byte: u64, // from the request going out to the response headers
+    pub content_download: u64,
+}
+
+// A cookie set by a response. Attributes the server left out are None.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub expires: Option<DateTime<Utc>>, // from Max-Age when present, else Expires
+    pub http_only: bool,
+    pub secure: bool,
+}
+
+// Layer a {{token}} took its value from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum VariableSource {
+    Environment,
+    Response, // {{response.<request>.<path>}}, read from history
+    Default,  // unset, so the token's `|default` is used
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracedVariable {
+    pub token: String,
+    pub source: VariableSource,
+    pub value: Option<String>, // masked for credential-like names
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariableTrace {
+    pub variables: Vec<TracedVariable>, // in the order they first appear
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InformationalResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+}
+
+// Where fuzz values are injected into a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FuzzTarget {
+    QueryParam(String),
+    Header(String),
+    BodyField(String), // top-level field of a JSON, form or URL-encoded body
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzResult {
+    pub payload: String,
+    pub status: Option<u16>, // None when the request itself failed
+    pub response_time: Option<u64>,
+    pub body_snippet: String, // start of the body, or the error message
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunOutcome {
+    Passed,
+    Failed,
+    Skipped, // excluded in the active environment
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionRunResult {
+    pub request_id: Uuid,
+    pub name: String,
+    pub outcome: RunOutcome,
+    pub status: Option<u16>, // None when skipped or the request itself failed
+    pub response_time: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcResponse {
+    pub status: i32,                     // grpc-status code, 0 for OK
+    pub status_name: String,             // e.g. "Ok", "NotFound"
+    pub message: Option<String>,         // grpc-message of a failed call
+    pub body: Option<serde_json::Value>, // decoded response message, when OK
+    pub response_time: u64,              // in milliseconds
+}
+
+// Download progress reported while a streamed response body arrives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestProgress {
+    pub client_request_id: String,
+    pub bytes_received: u64,
+    pub total_bytes: Option<u64>, // from Content-Length, when the server sends it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectHop {
+    pub status: u16,
+    pub location: Option<String>,
+    pub url: String,
+}
+
+// A slice of a stored response body; `offset` and `length` are the actual byte range,
+// snapped to UTF-8 character boundaries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyWindow {
+    pub offset: usize,
+    pub length: usize,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeImportResult {
+    pub created: usize,
+    pub updated: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    pub collections_count: usize,
+    pub requests_count: usize,
+    pub environments_count: usize,
+    pub history_count: usize,
+    pub on_disk_bytes: u64,
+}
+
+// Everything needed to move a workspace to another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceBundle {
+    pub version: String,
+    pub exported_at: DateTime<Utc>,
+    pub collections: Vec<Collection>,
+    pub requests: Vec<HttpRequest>,
+    pub environments: Vec<Environment>,
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+}
+
+// What importing a collection does when a same-named one already exists
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImportConflict {
+    Rename,    // import alongside as "Name (2)", "Name (3)", ...
+    Overwrite, // keep the existing id and replace its contents
+    Skip,      // leave the existing collection untouched
+}
+
+// An imported collection, with anything the user should know about the import file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionImport {
+    #[serde(flatten)]
+    pub collection: Collection,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ImportMode {
+    Replace, // wipe collections, requests and environments first
+    Merge,   // only add records whose id isn't already present
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptRecord {
+    pub tree: String,
+    pub key: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtInfo {
+    pub exp: Option<i64>,
+    pub iat: Option<i64>,
+    pub sub: Option<String>,
+    pub is_expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretFinding {
+    pub location: String, // "url", "header:<name>", "body" or "body:<field>"
+    pub pattern_name: String,
+    pub snippet: String, // masked
+}
+
+// An http:// URL referenced by a request sent over https
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedContentWarning {
+    pub location: String, // "header:<name>", "query:<name>", "body" or "body:<field>"
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,   // the request can't be sent as is
+    Warning, // it can, but probably not as intended
+}
+
+// A problem found by checking a request before it is sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub location: String, // "url", "header:<name>", "body" or "body:<field>"
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonMetrics {
+    pub total_keys: usize,
+    pub max_depth: usize,
+    pub array_lengths: Vec<usize>,
+    pub approximate_bytes: usize,
 }
 
 impl Default for HttpRequest {
@@ -217,15 +941,26 @@ impl Default for HttpRequest {
             name: "New Request".to_string(),
             method: HttpMethod::GET,
             url: "https://".to_string(),
-            headers: HashMap::new(),
+            headers: Vec::new(),
             body: None,
             path_params: HashMap::new(),
+            query_params: Vec::new(),
             collection_id: None,
             created_at: None,
             updated_at: None,
             synced: false,
             version: 0,
             cloud_id: None,
+            last_status: None,
+            last_response_time: None,
+            last_run_at: None,
+            examples: Vec::new(),
+            success_criteria: None,
+            auth: None,
+            alias: None,
+            depends_on: Vec::new(),
+            depends_on_cloud_ids: Vec::new(),
+            disabled_in_environments: Vec::new(),
         }
     }
 }
@@ -237,15 +972,26 @@ impl HttpRequest {
             name,
             method,
             url,
-            headers: HashMap::new(),
+            headers: Vec::new(),
             body: None,
             path_params: HashMap::new(),
+            query_params: Vec::new(),
             collection_id: None,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
             synced: false,
             version: 0,
             cloud_id: None,
+            last_status: None,
+            last_response_time: None,
+            last_run_at: None,
+            examples: Vec::new(),
+            success_criteria: None,
+            auth: None,
+            alias: None,
+            depends_on: Vec::new(),
+            depends_on_cloud_ids: Vec::new(),
+            disabled_in_environments: Vec::new(),
         }
     }
 }
@@ -264,6 +1010,8 @@ impl Collection {
             synced: false,
             version: 0,
             cloud_id: None,
+            default_timeout: None,
+            default_retries: None,
         }
     }
 
@@ -284,6 +1032,8 @@ impl Collection {
             synced: false,
             version: 0,
             cloud_id: None,
+            default_timeout: None,
+            default_retries: None,
         }
     }
 }
@@ -301,6 +1051,7 @@ impl Environment {
             synced: false,
             version: 0,
             cloud_id: None,
+            base_url_override: None,
         }
     }
 }
@@ -312,6 +1063,10 @@ impl RequestHistory {
             request,
             response,
             timestamp: Utc::now(),
+            environment_name: None,
+            resolved_url: String::new(),
+            resolved_headers: None,
+            resolved_body: None,
         }
     }
 }
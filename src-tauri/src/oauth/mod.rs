@@ -0,0 +1,146 @@
+use anyhow::Result;
+use chrono::Utc;
+use oauth2::{
+    basic::BasicClient, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, HttpRequest,
+    HttpResponse, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken, Scope,
+    TokenResponse, TokenUrl,
+};
+
+use crate::models::{OAuth2AuthCode, OAuth2Token};
+
+// Tokens are refreshed slightly before they actually expire
+const EXPIRY_MARGIN_SECS: i64 = 30;
+
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub pkce_verifier: String,
+    pub csrf_state: String, // the `state` the provider must send back with the code
+}
+
+fn oauth_client(config: &OAuth2AuthCode) -> Result<BasicClient> {
+    Ok(BasicClient::new(
+        ClientId::new(config.client_id.clone()),
+        config
+            .client_secret
+            .clone()
+            .filter(|secret| !secret.is_empty())
+            .map(ClientSecret::new),
+        AuthUrl::new(config.auth_url.clone())?,
+        Some(TokenUrl::new(config.token_url.clone())?),
+    )
+    .set_redirect_uri(RedirectUrl::new(config.redirect_uri.clone())?))
+}
+
+// Token requests go through the app's client, so the custom CA and proxy apply. Redirects
+// aren't followed, as in oauth2's own client.
+async fn http_client(request: HttpRequest) -> Result<HttpResponse, reqwest::Error> {
+    let client = crate::http::client_builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+        .unwrap_or(reqwest::Method::POST);
+    let mut builder = client.request(method, request.url.as_str());
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+    let response = builder.body(request.body).send().await?;
+
+    let status_code = oauth2::http::StatusCode::from_u16(response.status().as_u16())
+        .unwrap_or(oauth2::http::StatusCode::BAD_GATEWAY);
+    let mut headers = oauth2::http::HeaderMap::new();
+    for (name, value) in response.headers() {
+        if let (Ok(name), Ok(value)) = (
+            oauth2::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            oauth2::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let body = response.bytes().await?.to_vec();
+
+    Ok(HttpResponse {
+        status_code,
+        headers,
+        body,
+    })
+}
+
+// Builds the provider's authorization URL with a fresh PKCE challenge and CSRF state. Both
+// must be kept until the code comes back.
+pub fn authorization_request(config: &OAuth2AuthCode) -> Result<AuthorizationRequest> {
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let (url, csrf_token) = oauth_client(config)?
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(config.scopes.iter().cloned().map(Scope::new))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    Ok(AuthorizationRequest {
+        url: url.to_string(),
+        pkce_verifier: pkce_verifier.secret().to_string(),
+        csrf_state: csrf_token.secret().to_string(),
+    })
+}
+
+pub async fn exchange_code(
+    config: &OAuth2AuthCode,
+    code: &str,
+    pkce_verifier: &str,
+) -> Result<OAuth2Token> {
+    let token_result = oauth_client(config)?
+        .exchange_code(AuthorizationCode::new(code.to_string()))
+        .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier.to_string()))
+        .request_async(http_client)
+        .await
+        .map_err(|e| anyhow::anyhow!("Token exchange failed: {}", e))?;
+
+    Ok(to_token(&token_result, None))
+}
+
+// Exchanges the stored refresh token; providers that don't rotate it keep the old one
+pub async fn refresh(config: &OAuth2AuthCode) -> Result<OAuth2Token> {
+    let refresh_token = config
+        .token
+        .as_ref()
+        .and_then(|token| token.refresh_token.clone())
+        .ok_or_else(|| anyhow::anyhow!("No refresh token available"))?;
+
+    let token_result = oauth_client(config)?
+        .exchange_refresh_token(&RefreshToken::new(refresh_token.clone()))
+        .request_async(http_client)
+        .await
+        .map_err(|e| anyhow::anyhow!("Token refresh failed: {}", e))?;
+
+    Ok(to_token(&token_result, Some(refresh_token)))
+}
+
+fn to_token(
+    token_result: &impl TokenResponse<oauth2::basic::BasicTokenType>,
+    previous_refresh: Option<String>,
+) -> OAuth2Token {
+    OAuth2Token {
+        access_token: token_result.access_token().secret().to_string(),
+        refresh_token: token_result
+            .refresh_token()
+            .map(|t| t.secret().to_string())
+            .or(previous_refresh),
+        expires_at: token_result
+            .expires_in()
+            .and_then(|expires_in| chrono::Duration::from_std(expires_in).ok())
+            .map(|expires_in| Utc::now() + expires_in),
+    }
+}
+
+// True when the token has expired or is about to
+pub fn is_expiring(token: &OAuth2Token) -> bool {
+    token.expires_at.is_some_and(|expires_at| {
+        expires_at <= Utc::now() + chrono::Duration::seconds(EXPIRY_MARGIN_SECS)
+    })
+}
+
+// True when the token has expired (or is about to) and can be refreshed
+pub fn needs_refresh(token: &OAuth2Token) -> bool {
+    token.refresh_token.is_some() && is_expiring(token)
+}
@@ -1,5 +1,6 @@
 use crate::models::{
-    Collection, HttpMethod, HttpRequest, RequestBody as ModelRequestBody,
+    Collection, Environment, HttpMethod, HttpRequest, RequestBody as ModelRequestBody,
+    SavedResponse,
 };
 use chrono::Utc;
 use serde::Deserialize;
@@ -27,6 +28,57 @@ pub struct Info {
 pub struct Server {
     pub url: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, ServerVariable>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServerVariable {
+    pub default: Option<String>,
+    #[serde(rename = "enum")]
+    pub enum_values: Option<Vec<String>>,
+    pub description: Option<String>,
+}
+
+impl Server {
+    // Substitutes variable defaults into the URL; variables without one become
+    // {{name}} tokens, returned alongside with their suggested value
+    pub fn resolve_url(&self) -> (String, HashMap<String, String>) {
+        let mut url = self.url.clone();
+        let mut unresolved = HashMap::new();
+
+        for (name, variable) in &self.variables {
+            let placeholder = format!("{{{}}}", name);
+            match &variable.default {
+                Some(default) => url = url.replace(&placeholder, default),
+                None => {
+                    url = url.replace(&placeholder, &format!("{{{{{}}}}}", name));
+                    let suggested = variable
+                        .enum_values
+                        .as_ref()
+                        .and_then(|values| values.first().cloned())
+                        .unwrap_or_default();
+                    unresolved.insert(name.clone(), suggested);
+                }
+            }
+        }
+
+        (url, unresolved)
+    }
+}
+
+// Environment holding the first server's variables that have no default, if any
+pub fn server_variables_environment(spec: &OpenApiSpec) -> Option<Environment> {
+    let server = spec.servers.as_ref()?.first()?;
+    let (_, variables) = server.resolve_url();
+    if variables.is_empty() {
+        return None;
+    }
+
+    Some(Environment::new(
+        format!("{} servers", spec.info.title),
+        variables,
+    ))
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,6 +103,8 @@ pub struct Operation {
     #[serde(rename = "requestBody")]
     pub request_body: Option<RequestBody>,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub responses: HashMap<String, Response>, // keyed by status code
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,6 +126,19 @@ pub struct RequestBody {
 pub struct MediaType {
     pub schema: Option<Value>,
     pub example: Option<Value>,
+    #[serde(default)]
+    pub examples: HashMap<String, Example>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    pub content: HashMap<String, MediaType>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Example {
+    pub value: Option<Value>, // unset for $ref and externalValue examples
 }
 
 #[derive(Debug, Deserialize)]
@@ -147,6 +214,94 @@ fn resolve_schema_properties(schema: &Value, components: &Option<Components>) ->
     properties
 }
 
+// Saved examples from the documented JSON responses, named by status code. Ranges like
+// "2XX" and "default" have no status to save, so they are skipped.
+fn response_examples(responses: &HashMap<String, Response>) -> Vec<SavedResponse> {
+    let mut examples = Vec::new();
+    for (code, response) in responses {
+        let Ok(status) = code.parse::<u16>() else {
+            continue;
+        };
+        let Some(media) = response.content.get("application/json") else {
+            continue;
+        };
+
+        let mut named: Vec<(String, &Value)> = media
+            .examples
+            .iter()
+            .filter_map(|(name, example)| {
+                let value = example.value.as_ref()?;
+                Some((format!("{} {}", code, name), value))
+            })
+            .collect();
+        if let Some(example) = &media.example {
+            named.push((code.clone(), example));
+        }
+
+        for (name, value) in named {
+            examples.push(SavedResponse {
+                name,
+                status,
+                headers: HashMap::from([(
+                    "Content-Type".to_string(),
+                    "application/json".to_string(),
+                )]),
+                body: serde_json::to_string_pretty(value).unwrap_or_default(),
+                saved_at: Utc::now(),
+            });
+        }
+    }
+
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    examples
+}
+
+fn operation_matches(op: &Operation, operation_ids: &[String], tags: &[String]) -> bool {
+    let id_match = op
+        .operation_id
+        .as_ref()
+        .is_some_and(|id| operation_ids.contains(id));
+    let tag_match = op.tags.iter().flatten().any(|tag| tags.contains(tag));
+    id_match || tag_match
+}
+
+// Keeps only operations whose operationId or a tag is in the filters.
+// Empty filters keep everything.
+pub fn filter_operations(spec: &mut OpenApiSpec, operation_ids: &[String], tags: &[String]) {
+    if operation_ids.is_empty() && tags.is_empty() {
+        return;
+    }
+
+    for item in spec.paths.values_mut() {
+        for op in [
+            &mut item.get,
+            &mut item.post,
+            &mut item.put,
+            &mut item.delete,
+            &mut item.patch,
+            &mut item.head,
+            &mut item.options,
+        ] {
+            if !op
+                .as_ref()
+                .is_some_and(|op| operation_matches(op, operation_ids, tags))
+            {
+                *op = None;
+            }
+        }
+    }
+
+    spec.paths.retain(|_, item| {
+        item.get.is_some()
+            || item.post.is_some()
+            || item.put.is_some()
+            || item.delete.is_some()
+            || item.patch.is_some()
+            || item.head.is_some()
+            || item.options.is_some()
+    });
+}
+
 pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>) {
     let root_collection_id = Uuid::new_v4();
     let root_collection = Collection {
@@ -160,6 +315,8 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
         synced: false,
         version: 0,
         cloud_id: None,
+        default_timeout: None,
+        default_retries: None,
     };
 
     let mut collections = vec![root_collection];
@@ -172,7 +329,7 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
         .servers
         .as_ref()
         .and_then(|s| s.first())
-        .map(|s| s.url.clone())
+        .map(|s| s.resolve_url().0)
         .unwrap_or_else(|| "http://localhost".to_string());
 
     // Group paths by tags (if available) to simulate folders
@@ -197,7 +354,7 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                     .or(op.operation_id)
                     .unwrap_or_else(|| format!("{} {}", method.to_string(), path));
 
-                let mut headers = HashMap::new();
+                let mut headers = Vec::new();
                 let mut path_params = HashMap::new();
                 let mut query_params = Vec::new();
                 
@@ -207,7 +364,7 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                 for param in all_params {
                     match param.in_loc.as_str() {
                         "header" => {
-                            headers.insert(param.name.clone(), "".to_string());
+                            headers.push((param.name.clone(), "".to_string()));
                         }
                         "path" => {
                             path_params.insert(param.name.clone(), "".to_string());
@@ -258,7 +415,7 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                          }
                          Some(ModelRequestBody::UrlEncoded(form_data))
                     } else if let Some(form) = rb.content.get("multipart/form-data") {
-                         let mut form_data = HashMap::new();
+                         let mut form_data = Vec::new();
                          if let Some(schema) = &form.schema {
                              let properties = resolve_schema_properties(schema, &spec.components);
                              for (key, val) in properties {
@@ -267,7 +424,7 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                                  } else {
                                      crate::models::FormDataField::Text { value: "".to_string() }
                                  };
-                                 form_data.insert(key, field);
+                                 form_data.push((key, field));
                              }
                          }
                          Some(ModelRequestBody::FormData(form_data))
@@ -298,6 +455,8 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                                 synced: false,
                                 version: 0,
                                 cloud_id: None,
+                                default_timeout: None,
+                                default_retries: None,
                             };
                             collections.push(sub_collection);
                             tag_collections.insert(first_tag.clone(), new_id);
@@ -318,12 +477,23 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
                     headers,
                     body,
                     path_params,
+                    query_params: Vec::new(),
                     collection_id: Some(parent_collection_id),
                     created_at: Some(Utc::now()),
                     updated_at: Some(Utc::now()),
                     synced: false,
                     version: 0,
                     cloud_id: None,
+                    last_status: None,
+                    last_response_time: None,
+                    last_run_at: None,
+                    examples: response_examples(&op.responses),
+                    success_criteria: None,
+                    auth: None,
+                    alias: None,
+                    depends_on: Vec::new(),
+                    depends_on_cloud_ids: Vec::new(),
+                    disabled_in_environments: Vec::new(),
                 };
                 requests.push(request);
             }
@@ -340,3 +510,97 @@ pub fn convert_openapi(spec: OpenApiSpec) -> (Vec<Collection>, Vec<HttpRequest>)
 
     (collections, requests)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_operations_by_operation_id() {
+        let spec_json = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0" },
+            "paths": {
+                "/pets": {
+                    "get": { "operationId": "listPets", "tags": ["pets"] },
+                    "post": { "operationId": "createPet", "tags": ["pets"] }
+                },
+                "/pets/{id}": {
+                    "get": { "operationId": "getPet", "tags": ["pets"] },
+                    "delete": { "operationId": "deletePet", "tags": ["admin"] }
+                }
+            }
+        });
+        let mut spec: OpenApiSpec = serde_json::from_value(spec_json).unwrap();
+
+        let operation_ids = vec!["listPets".to_string(), "getPet".to_string()];
+        filter_operations(&mut spec, &operation_ids, &[]);
+        let (_, requests) = convert_openapi(spec);
+
+        let mut names: Vec<_> = requests.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["getPet", "listPets"]);
+    }
+
+    #[test]
+    fn test_server_variables_substitute_defaults() {
+        let spec_json = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0" },
+            "servers": [{
+                "url": "https://{region}.api.example.com/{basePath}",
+                "variables": {
+                    "basePath": { "default": "v2" },
+                    "region": { "enum": ["eu", "us"] }
+                }
+            }],
+            "paths": {
+                "/pets": { "get": { "operationId": "listPets" } }
+            }
+        });
+        let spec: OpenApiSpec = serde_json::from_value(spec_json).unwrap();
+
+        let environment = server_variables_environment(&spec).unwrap();
+        assert_eq!(environment.name, "Pets servers");
+        assert_eq!(environment.variables.get("region").unwrap(), "eu");
+        assert!(!environment.variables.contains_key("basePath"));
+
+        let (_, requests) = convert_openapi(spec);
+        assert_eq!(requests[0].url, "https://{{region}}.api.example.com/v2/pets");
+    }
+
+    #[test]
+    fn test_documented_response_example_becomes_saved_example() {
+        let spec_json = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0" },
+            "paths": {
+                "/pets/{id}": {
+                    "get": {
+                        "operationId": "getPet",
+                        "responses": {
+                            "200": {
+                                "description": "A pet",
+                                "content": {
+                                    "application/json": {
+                                        "example": { "id": 1, "name": "Rex" }
+                                    }
+                                }
+                            },
+                            "404": { "description": "Not found" }
+                        }
+                    }
+                }
+            }
+        });
+        let spec: OpenApiSpec = serde_json::from_value(spec_json).unwrap();
+
+        let (_, requests) = convert_openapi(spec);
+        let examples = &requests[0].examples;
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].name, "200");
+        assert_eq!(examples[0].status, 200);
+        let body: Value = serde_json::from_str(&examples[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "id": 1, "name": "Rex" }));
+    }
+}
@@ -150,9 +150,16 @@ pub struct PostmanBody {
     pub raw: Option<String>,
     pub urlencoded: Option<Vec<PostmanKeyValue>>,
     pub formdata: Option<Vec<PostmanFormData>>,
+    #[serde(default)]
+    pub file: Option<PostmanFile>,
     pub options: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostmanFile {
+    pub src: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostmanKeyValue {
     pub key: String,
@@ -210,6 +217,8 @@ pub fn convert_postman_collection(
         synced: false,
         version: 0,
         cloud_id: None,
+        default_timeout: None,
+        default_retries: None,
     };
 
     let mut collections = vec![collection.clone()];
@@ -225,6 +234,68 @@ pub fn convert_postman_collection(
     (collections, requests)
 }
 
+// Converts only the folder at `folder_path` (folder names from the top level down) into a
+// standalone collection named after it
+pub fn convert_postman_folder(
+    postman: &PostmanCollection,
+    folder_path: &[String],
+) -> Result<(Vec<Collection>, Vec<HttpRequest>), String> {
+    if folder_path.is_empty() {
+        return Err("Folder path is required".to_string());
+    }
+
+    // The folder keeps the nearest auth defined above it
+    let mut auth = postman.auth.as_ref();
+    let mut items = &postman.item;
+    let mut folder = None;
+    for name in folder_path {
+        let found = items.iter().find_map(|item| match item {
+            PostmanItem::Folder(folder) if &folder.name == name => Some(folder),
+            _ => None,
+        });
+        let Some(found) = found else {
+            return Err(format!(
+                "No folder '{}' in the Postman collection",
+                folder_path.join(" / ")
+            ));
+        };
+        auth = found.auth.as_ref().or(auth);
+        items = &found.item;
+        folder = Some(found);
+    }
+    let folder = folder.expect("path is not empty");
+
+    let collection = Collection {
+        id: Uuid::new_v4(),
+        name: folder.name.clone(),
+        description: folder.description.as_ref().map(|d| match d {
+            PostmanDescription::String(s) => s.clone(),
+            PostmanDescription::Object { content } => content.clone(),
+        }),
+        parent_id: None,
+        auth: auth.map(convert_postman_auth),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        synced: false,
+        version: 0,
+        cloud_id: None,
+        default_timeout: None,
+        default_retries: None,
+    };
+
+    let mut collections = vec![collection.clone()];
+    let mut requests = Vec::new();
+    process_postman_items(
+        &folder.item,
+        &collection.id,
+        None,
+        &mut collections,
+        &mut requests,
+    );
+
+    Ok((collections, requests))
+}
+
 fn process_postman_items(
     items: &[PostmanItem],
     collection_id: &Uuid,
@@ -259,6 +330,8 @@ fn process_postman_items(
                     synced: false,
                     version: 0,
                     cloud_id: None,
+                    default_timeout: None,
+                    default_retries: None,
                 };
 
                 let sub_collection_id = sub_collection.id;
@@ -297,15 +370,46 @@ fn convert_postman_request(postman: &PostmanRequest, collection_id: &Uuid) -> Ht
         headers,
         body,
         path_params,
+        query_params: Vec::new(),
         collection_id: Some(*collection_id),
         created_at: Some(Utc::now()),
         updated_at: Some(Utc::now()),
         synced: false,
         version: 0,
         cloud_id: None,
+        last_status: None,
+        last_response_time: None,
+        last_run_at: None,
+        examples: Vec::new(),
+        success_criteria: None,
+        auth: None,
+        alias: None,
+        depends_on: Vec::new(),
+        depends_on_cloud_ids: Vec::new(),
+        disabled_in_environments: Vec::new(),
     }
 }
 
+// Identifies a request across re-imports by method and URL path, ignoring the host
+// (or leading {{base_url}} variable), query string and trailing slash
+pub fn request_match_key(request: &HttpRequest) -> String {
+    let url = request.url.split(['?', '#']).next().unwrap_or("");
+    let path = if let Some((_, rest)) = url.split_once("://") {
+        rest.find('/').map(|i| &rest[i..]).unwrap_or("")
+    } else if url.starts_with("{{") {
+        url.find("}}").map(|i| &url[i + 2..]).unwrap_or("")
+    } else {
+        url.find('/').map(|i| &url[i..]).unwrap_or("")
+    };
+    let path = path.trim_end_matches('/');
+
+    format!(
+        "{} /{}",
+        request.method.to_string(),
+        path.trim_start_matches('/')
+    )
+}
+
 fn convert_method(method: &str) -> HttpMethod {
     match method.to_uppercase().as_str() {
         "GET" => HttpMethod::GET,
@@ -373,12 +477,12 @@ fn convert_url(url: &PostmanUrl) -> String {
     }
 }
 
-fn convert_headers(headers: &Vec<PostmanHeader>) -> HashMap<String, String> {
-    let mut result = HashMap::new();
+fn convert_headers(headers: &Vec<PostmanHeader>) -> Vec<(String, String)> {
+    let mut result = Vec::new();
 
     for header in headers {
         if !header.disabled {
-            result.insert(header.key.clone(), header.value.clone());
+            result.push((header.key.clone(), header.value.clone()));
         }
     }
 
@@ -421,7 +525,7 @@ fn convert_body(body: &Option<PostmanBody>) -> Option<RequestBody> {
         }
         "formdata" => {
             if let Some(form_data) = &b.formdata {
-                let mut data = HashMap::new();
+                let mut data = Vec::new();
                 for item in form_data {
                     if !item.disabled {
                         let field = if item.field_type == "file" {
@@ -433,7 +537,7 @@ fn convert_body(body: &Option<PostmanBody>) -> Option<RequestBody> {
                                 value: item.value.clone().unwrap_or_default(),
                             }
                         };
-                        data.insert(item.key.clone(), field);
+                        data.push((item.key.clone(), field));
                     }
                 }
                 if !data.is_empty() {
@@ -445,6 +549,12 @@ fn convert_body(body: &Option<PostmanBody>) -> Option<RequestBody> {
                 None
             }
         }
+        "file" => b
+            .file
+            .as_ref()
+            .and_then(|file| file.src.clone())
+            .filter(|src| !src.is_empty())
+            .map(|path| RequestBody::BinaryFile { path }),
         _ => None,
     })
 }
@@ -467,6 +577,11 @@ fn convert_postman_auth(auth: &PostmanAuth) -> AuthConfig {
                 auth_type: AuthType::Basic,
                 basic: Some(BasicAuth { username, password }),
                 bearer: None,
+                oauth2: None,
+                api_key: None,
+                oauth2_client_credentials: None,
+                digest: None,
+                aws_sigv4: None,
             }
         }
         PostmanAuth::Bearer { bearer } => {
@@ -483,12 +598,22 @@ fn convert_postman_auth(auth: &PostmanAuth) -> AuthConfig {
                 auth_type: AuthType::Bearer,
                 basic: None,
                 bearer: Some(BearerAuth { token }),
+                oauth2: None,
+                api_key: None,
+                oauth2_client_credentials: None,
+                digest: None,
+                aws_sigv4: None,
             }
         }
         PostmanAuth::NoAuth => AuthConfig {
             auth_type: AuthType::None,
             basic: None,
             bearer: None,
+            oauth2: None,
+            api_key: None,
+            oauth2_client_credentials: None,
+            digest: None,
+            aws_sigv4: None,
         },
     }
 }
@@ -512,6 +637,65 @@ fn extract_path_params(url: &str) -> HashMap<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_convert_nested_postman_folder() {
+        let json = r#"{
+            "info": { "name": "Shop" },
+            "item": [
+                {
+                    "name": "Users",
+                    "item": [
+                        {
+                            "name": "Admin",
+                            "item": [
+                                {
+                                    "name": "List admins",
+                                    "request": { "method": "GET", "url": "http://shop/admins" }
+                                },
+                                {
+                                    "name": "Audit",
+                                    "item": [
+                                        {
+                                            "name": "Audit log",
+                                            "request": { "method": "GET", "url": "http://shop/audit" }
+                                        }
+                                    ]
+                                }
+                            ]
+                        },
+                        {
+                            "name": "List users",
+                            "request": { "method": "GET", "url": "http://shop/users" }
+                        }
+                    ]
+                },
+                {
+                    "name": "Orders",
+                    "item": [
+                        {
+                            "name": "List orders",
+                            "request": { "method": "GET", "url": "http://shop/orders" }
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        let postman: PostmanCollection = serde_json::from_str(json).unwrap();
+
+        let path = vec!["Users".to_string(), "Admin".to_string()];
+        let (collections, requests) = convert_postman_folder(&postman, &path).unwrap();
+
+        assert_eq!(collections.len(), 2);
+        assert_eq!(collections[0].name, "Admin");
+        assert_eq!(collections[1].parent_id, Some(collections[0].id));
+        let mut names: Vec<_> = requests.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Audit log", "List admins"]);
+
+        let missing = vec!["Users".to_string(), "List users".to_string()];
+        assert!(convert_postman_folder(&postman, &missing).is_err());
+    }
+
     #[test]
     fn test_parse_postman_item_folder() {
         let json = r#"{
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// The log is rotated to `<name>.1` once it grows past this size
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+// Serializes appends and rotation across concurrent requests
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub url: String, // already redacted
+    pub status: Option<u16>,
+    pub response_time: Option<u64>,
+    pub error: Option<String>,
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+// Appends one JSON line, rotating first when the file is over `max_bytes`
+pub fn append_entry(path: &Path, entry: &RequestLogEntry, max_bytes: u64) -> Result<()> {
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    if std::fs::metadata(path).is_ok_and(|meta| meta.len() >= max_bytes) {
+        std::fs::rename(path, rotated_path(path))?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+// Writes the entry on the blocking pool so the request never waits on disk I/O
+pub fn spawn_append(path: PathBuf, entry: RequestLogEntry) {
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = append_entry(&path, &entry, MAX_LOG_BYTES) {
+            eprintln!("Failed to write request log '{}': {}", path.display(), e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str) -> RequestLogEntry {
+        RequestLogEntry {
+            timestamp: Utc::now(),
+            method: "GET".to_string(),
+            url: url.to_string(),
+            status: Some(200),
+            response_time: Some(12),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_append_rotates_when_over_threshold() {
+        let dir = std::env::temp_dir().join(format!("geni_log_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("requests.log");
+
+        append_entry(&path, &entry("https://a.example.com"), 64).unwrap();
+        append_entry(&path, &entry("https://b.example.com"), 64).unwrap();
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        let rotated = std::fs::read_to_string(dir.join("requests.log.1")).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains("b.example.com"));
+        assert!(rotated.contains("a.example.com"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
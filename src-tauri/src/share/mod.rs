@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+use crate::models::HttpRequest;
+
+const LINK_PREFIX: &str = "geni://import/";
+// Decoded links larger than this are rejected to avoid decompression bombs
+const MAX_DECODED_LINK_BYTES: u64 = 1024 * 1024;
+
+// Strip local identity and sync state so the request can be imported anywhere
+fn to_portable(request: &HttpRequest) -> HttpRequest {
+    let mut portable = request.clone();
+    portable.id = None;
+    portable.collection_id = None;
+    portable.created_at = None;
+    portable.updated_at = None;
+    portable.synced = false;
+    portable.version = 0;
+    portable.cloud_id = None;
+    portable.last_status = None;
+    portable.last_response_time = None;
+    portable.last_run_at = None;
+    portable.examples = Vec::new();
+    portable.auth = None; // may hold credentials
+    portable.alias = None; // only unique within the sender's workspace
+    portable.depends_on = Vec::new(); // ids of the sender's requests
+    portable.depends_on_cloud_ids = Vec::new();
+    portable
+}
+
+pub fn encode_request_link(request: &HttpRequest) -> Result<String> {
+    let json = serde_json::to_vec(&to_portable(request))?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json)?;
+    let compressed = encoder.finish()?;
+
+    Ok(format!(
+        "{}{}",
+        LINK_PREFIX,
+        general_purpose::URL_SAFE_NO_PAD.encode(compressed)
+    ))
+}
+
+pub fn decode_request_link(link: &str) -> Result<HttpRequest> {
+    let blob = link
+        .trim()
+        .strip_prefix(LINK_PREFIX)
+        .ok_or_else(|| anyhow!("Not a Geni request link"))?;
+
+    let compressed = general_purpose::URL_SAFE_NO_PAD
+        .decode(blob)
+        .map_err(|e| anyhow!("Invalid request link encoding: {}", e))?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .take(MAX_DECODED_LINK_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| anyhow!("Invalid request link data: {}", e))?;
+
+    if json.len() as u64 > MAX_DECODED_LINK_BYTES {
+        return Err(anyhow!("Request link is too large"));
+    }
+
+    serde_json::from_slice(&json).map_err(|e| anyhow!("Invalid request in link: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HttpMethod, RequestBody};
+
+    #[test]
+    fn test_request_link_round_trip() {
+        let mut request = HttpRequest::new(
+            "Create user".to_string(),
+            HttpMethod::POST,
+            "{{base_url}}/users".to_string(),
+        );
+        request
+            .headers
+            .push(("Authorization".to_string(), "Bearer {{token}}".to_string()));
+        request.body = Some(RequestBody::Json(serde_json::json!({"name": "Ada"})));
+
+        let link = encode_request_link(&request).unwrap();
+        assert!(link.starts_with(LINK_PREFIX));
+
+        let decoded = decode_request_link(&link).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(to_portable(&request)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_corrupted_request_link() {
+        assert!(decode_request_link("geni://import/not-a-valid-blob").is_err());
+        assert!(decode_request_link("https://example.com").is_err());
+    }
+}
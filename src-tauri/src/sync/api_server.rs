@@ -1,6 +1,7 @@
+use super::SyncError;
 use crate::models::*;
 use anyhow::{anyhow, Result};
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
@@ -39,10 +40,17 @@ struct AuthResponse {
     user: ApiUser,
 }
 
+// Turns a failed response into a SyncError, keeping 401s and 409s distinguishable
+async fn status_error(response: Response, action: &str) -> anyhow::Error {
+    let status = response.status().as_u16();
+    let error_text = response.text().await.unwrap_or_default();
+    SyncError::from_status(status, format!("{}: {}", action, error_text)).into()
+}
+
 impl ApiServerClient {
     pub fn new(base_url: &str) -> Result<Self> {
         Ok(Self {
-            client: Client::new(),
+            client: crate::http::client_builder().build()?,
             base_url: base_url.trim_end_matches('/').to_string(),
             access_token: None,
             refresh_token: None,
@@ -123,6 +131,11 @@ impl ApiServerClient {
         })
     }
 
+    pub fn rebuild_http_client(&mut self) -> Result<()> {
+        self.client = crate::http::client_builder().build()?;
+        Ok(())
+    }
+
     pub fn is_authenticated(&self) -> bool {
         self.access_token.is_some()
     }
@@ -144,7 +157,7 @@ impl ApiServerClient {
     async fn ensure_authenticated(&self) -> Result<String> {
         self.access_token
             .clone()
-            .ok_or_else(|| anyhow!("Not authenticated"))
+            .ok_or_else(|| SyncError::NotAuthenticated.into())
     }
 
     pub async fn create_collection(&self, collection: &Collection) -> Result<String> {
@@ -159,8 +172,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to create collection: {}", error_text));
+            return Err(status_error(response, "Failed to create collection").await);
         }
 
         let created: Collection = response.json().await?;
@@ -178,8 +190,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to get collections: {}", error_text));
+            return Err(status_error(response, "Failed to get collections").await);
         }
 
         let collections: Vec<Collection> = response.json().await?;
@@ -198,8 +209,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to update collection: {}", error_text));
+            return Err(status_error(response, "Failed to update collection").await);
         }
 
         Ok(())
@@ -216,8 +226,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to delete collection: {}", error_text));
+            return Err(status_error(response, "Failed to delete collection").await);
         }
 
         Ok(())
@@ -235,8 +244,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to create request: {}", error_text));
+            return Err(status_error(response, "Failed to create request").await);
         }
 
         let created: HttpRequest = response.json().await?;
@@ -254,8 +262,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to get requests: {}", error_text));
+            return Err(status_error(response, "Failed to get requests").await);
         }
 
         let requests: Vec<HttpRequest> = response.json().await?;
@@ -274,8 +281,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to update request: {}", error_text));
+            return Err(status_error(response, "Failed to update request").await);
         }
 
         Ok(())
@@ -292,8 +298,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to delete request: {}", error_text));
+            return Err(status_error(response, "Failed to delete request").await);
         }
 
         Ok(())
@@ -311,8 +316,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to create environment: {}", error_text));
+            return Err(status_error(response, "Failed to create environment").await);
         }
 
         let created: Environment = response.json().await?;
@@ -330,8 +334,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to get environments: {}", error_text));
+            return Err(status_error(response, "Failed to get environments").await);
         }
 
         let environments: Vec<Environment> = response.json().await?;
@@ -354,8 +357,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to update environment: {}", error_text));
+            return Err(status_error(response, "Failed to update environment").await);
         }
 
         Ok(())
@@ -372,8 +374,7 @@ impl ApiServerClient {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to delete environment: {}", error_text));
+            return Err(status_error(response, "Failed to delete environment").await);
         }
 
         Ok(())
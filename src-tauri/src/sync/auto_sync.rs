@@ -0,0 +1,36 @@
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+pub const AUTO_SYNC_EVENT: &str = "auto-sync";
+
+// Result of a single auto-sync cycle, emitted to the frontend
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum AutoSyncEvent {
+    Synced,
+    Skipped { reason: String },
+    Failed { error: String },
+}
+
+// Runs `cycle` every `interval`, starting one interval from now.
+// Aborting the returned handle stops the loop.
+pub fn spawn_auto_sync<F, Fut>(interval: Duration, mut cycle: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // Don't burst missed cycles after a slow sync
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        ticker.tick().await; // The first tick completes immediately
+
+        loop {
+            ticker.tick().await;
+            cycle().await;
+        }
+    })
+}
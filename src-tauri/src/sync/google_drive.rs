@@ -1,3 +1,4 @@
+use super::{SyncData, SyncError};
 use crate::models::*;
 use anyhow::{anyhow, Result};
 use oauth2::{
@@ -43,15 +44,6 @@ struct DriveFileList {
     files: Vec<DriveFile>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct SyncData {
-    collections: Vec<Collection>,
-    requests: Vec<HttpRequest>,
-    environments: Vec<Environment>,
-    version: String,
-    last_updated: chrono::DateTime<chrono::Utc>,
-}
-
 impl GoogleDriveClient {
     pub fn new(client_id: &str, client_secret: &str, redirect_uri: &str) -> Result<Self> {
         let oauth_client = BasicClient::new(
@@ -63,7 +55,7 @@ impl GoogleDriveClient {
         .set_redirect_uri(RedirectUrl::new(redirect_uri.to_string())?);
 
         Ok(Self {
-            client: Client::new(),
+            client: crate::http::client_builder().build()?,
             oauth_client,
             access_token: None,
             refresh_token: None,
@@ -125,7 +117,7 @@ impl GoogleDriveClient {
         let token = self
             .access_token
             .as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+            .ok_or(SyncError::NotAuthenticated)?;
 
         let response = self
             .client
@@ -146,7 +138,7 @@ impl GoogleDriveClient {
         let token = self
             .access_token
             .as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+            .ok_or(SyncError::NotAuthenticated)?;
 
         // Search for existing Geni folder
         let response = self.client
@@ -196,6 +188,12 @@ impl GoogleDriveClient {
         Ok(())
     }
 
+    pub fn rebuild_http_client(&mut self) -> Result<()> {
+        // OAuth token exchange goes through the oauth2 crate's own client
+        self.client = crate::http::client_builder().build()?;
+        Ok(())
+    }
+
     pub fn is_authenticated(&self) -> bool {
         self.access_token.is_some()
     }
@@ -219,7 +217,7 @@ impl GoogleDriveClient {
         let token = self
             .access_token
             .as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+            .ok_or(SyncError::NotAuthenticated)?;
 
         let folder_id = self
             .folder_id
@@ -252,7 +250,7 @@ impl GoogleDriveClient {
         let token = self
             .access_token
             .as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+            .ok_or(SyncError::NotAuthenticated)?;
 
         let folder_id = self
             .folder_id
@@ -297,18 +295,10 @@ impl GoogleDriveClient {
             };
 
             // Use multipart upload
-            let boundary = "boundary123456789";
-            let metadata_part = format!(
-                "--{}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n",
-                boundary,
-                serde_json::to_string(&metadata)?
-            );
-            let file_part = format!(
-                "--{}\r\nContent-Type: application/json\r\n\r\n{}\r\n--{}--",
-                boundary, json_data, boundary
-            );
-
-            let body = format!("{}{}", metadata_part, file_part);
+            let (boundary, body) =
+                multipart_related_body(&serde_json::to_string(&metadata)?, &json_data, || {
+                    format!("geni-{}", uuid::Uuid::new_v4().simple())
+                });
 
             let response = self
                 .client
@@ -319,6 +309,7 @@ impl GoogleDriveClient {
                     "Content-Type",
                     format!("multipart/related; boundary={}", boundary),
                 )
+                .header("Content-Length", body.len())
                 .body(body)
                 .send()
                 .await?;
@@ -336,7 +327,7 @@ impl GoogleDriveClient {
         let token = self
             .access_token
             .as_ref()
-            .ok_or_else(|| anyhow!("Not authenticated"))?;
+            .ok_or(SyncError::NotAuthenticated)?;
 
         let file_id = self.get_or_create_data_file("geni_data.json").await?;
 
@@ -392,3 +383,49 @@ impl GoogleDriveClient {
         Ok(())
     }
 }
+
+// Builds a multipart/related body from the metadata and file JSON, drawing boundaries
+// from `next_boundary` until one doesn't occur in either part
+fn multipart_related_body(
+    metadata: &str,
+    data: &str,
+    mut next_boundary: impl FnMut() -> String,
+) -> (String, String) {
+    let boundary = loop {
+        let boundary = next_boundary();
+        if !metadata.contains(&boundary) && !data.contains(&boundary) {
+            break boundary;
+        }
+    };
+
+    let body = format!(
+        "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n\
+         --{b}\r\nContent-Type: application/json\r\n\r\n{}\r\n--{b}--",
+        metadata,
+        data,
+        b = boundary
+    );
+    (boundary, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_boundary_regenerated_when_data_contains_it() {
+        let data = r#"{"note":"--boundary123456789 inside the payload"}"#;
+        let mut candidates = vec!["geni-second".to_string(), "boundary123456789".to_string()];
+
+        let (boundary, body) = multipart_related_body(r#"{"name":"geni_data.json"}"#, data, || {
+            candidates.pop().unwrap()
+        });
+
+        assert_eq!(boundary, "geni-second");
+        let parts: Vec<&str> = body.split("--geni-second").collect();
+        // Leading empty chunk, metadata part, file part and the closing "--"
+        assert_eq!(parts.len(), 4);
+        assert!(parts[2].ends_with(&format!("\r\n\r\n{}\r\n", data)));
+        assert_eq!(parts[3], "--");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod api_server;
+pub mod auto_sync;
 pub mod google_drive;
 pub mod supabase;
 
@@ -52,6 +53,40 @@ pub struct ProviderConfig {
     pub google_client_id: Option<String>,
     pub google_client_secret: Option<String>,
     pub google_redirect_uri: Option<String>,
+    // Supabase blob mode: sync geni_data.json in this Storage bucket instead of the tables
+    #[serde(default)]
+    pub supabase_storage_bucket: Option<String>,
+    // Skip certificate checks on the direct PostgreSQL connection used for schema creation
+    #[serde(default)]
+    pub supabase_accept_invalid_certs: bool,
+}
+
+// Bulk sync file shared by Google Drive and Supabase Storage
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncData {
+    pub collections: Vec<Collection>,
+    pub requests: Vec<HttpRequest>,
+    pub environments: Vec<Environment>,
+    pub version: String,
+    pub last_updated: chrono::DateTime<chrono::Utc>,
+}
+
+// The app starts on a local API server until a provider is configured
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            provider: SyncProvider::ApiServer,
+            api_server_url: Some("http://localhost:3000".to_string()),
+            supabase_url: None,
+            supabase_api_key: None,
+            supabase_db_uri: None,
+            google_client_id: None,
+            google_client_secret: None,
+            google_redirect_uri: None,
+            supabase_storage_bucket: None,
+            supabase_accept_invalid_certs: false,
+        }
+    }
 }
 
 impl ProviderConfig {
@@ -64,6 +99,103 @@ impl ProviderConfig {
     }
 }
 
+// What the active provider can do, so the UI can hide unsupported actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    pub supports_individual_ops: bool, // push or delete one item at a time
+    pub supports_incremental: bool,    // only changed items are pushed
+    pub supports_delete: bool,
+    pub requires_auth_flow: bool, // the user must sign in before syncing
+}
+
+// Why a sync failed, so the UI can react (e.g. prompt to sign in) instead of showing raw text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum SyncError {
+    NotAuthenticated,
+    Network(String),
+    SchemaMissing(String), // carries the setup instructions
+    Conflict,
+    Provider(String),
+}
+
+impl SyncError {
+    // Maps a failed provider response by its HTTP status
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            401 => Self::NotAuthenticated,
+            409 => Self::Conflict,
+            _ => Self::Provider(message),
+        }
+    }
+
+    pub fn classify(error: &anyhow::Error) -> Self {
+        if let Some(sync_error) = error.downcast_ref::<SyncError>() {
+            return sync_error.clone();
+        }
+        if let Some(schema_error) = error.downcast_ref::<supabase::SchemaError>() {
+            return if schema_error.is_transient() {
+                Self::Network(schema_error.to_string())
+            } else {
+                Self::Provider(schema_error.to_string())
+            };
+        }
+        if let Some(http_error) = error.downcast_ref::<reqwest::Error>() {
+            if http_error.is_connect() || http_error.is_timeout() {
+                return Self::Network(http_error.to_string());
+            }
+            if let Some(status) = http_error.status() {
+                return Self::from_status(status.as_u16(), http_error.to_string());
+            }
+        }
+        Self::Provider(error.to_string())
+    }
+}
+
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAuthenticated => write!(f, "Not authenticated"),
+            Self::Network(e) => write!(f, "Network error: {}", e),
+            Self::SchemaMissing(e) => write!(f, "{}", e),
+            Self::Conflict => write!(f, "The remote data changed; pull before pushing again"),
+            Self::Provider(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+// A SyncError as returned by the sync commands, naming the provider that raised it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSyncError {
+    pub provider: SyncProvider,
+    #[serde(flatten)]
+    pub error: SyncError,
+    pub message: String,
+}
+
+impl ProviderSyncError {
+    pub fn new(provider: SyncProvider, error: SyncError) -> Self {
+        let message = error.to_string();
+        Self {
+            provider,
+            error,
+            message,
+        }
+    }
+
+    pub fn from_anyhow(provider: SyncProvider, error: &anyhow::Error) -> Self {
+        Self::new(provider, SyncError::classify(error))
+    }
+}
+
+impl std::fmt::Display for ProviderSyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.provider.as_str(), self.message)
+    }
+}
+
 pub enum SyncClient {
     ApiServer(ApiServerClient),
     Supabase(SupabaseClient),
@@ -86,11 +218,11 @@ impl SyncClient {
                 let api_key = config
                     .supabase_api_key
                     .ok_or_else(|| anyhow!("Supabase API key required"))?;
-                Ok(Self::Supabase(SupabaseClient::new_with_db_uri(
-                    &url,
-                    &api_key,
-                    config.supabase_db_uri,
-                )?))
+                Ok(Self::Supabase(
+                    SupabaseClient::new_with_db_uri(&url, &api_key, config.supabase_db_uri)?
+                        .with_storage_bucket(config.supabase_storage_bucket)
+                        .with_accept_invalid_certs(config.supabase_accept_invalid_certs),
+                ))
             }
             SyncProvider::GoogleDrive => {
                 let client_id = config
@@ -131,6 +263,15 @@ impl SyncClient {
         )?))
     }
 
+    // Rebuilds the provider's HTTP client, e.g. after the custom CA bundle changes
+    pub fn rebuild_http_client(&mut self) -> Result<()> {
+        match self {
+            Self::ApiServer(client) => client.rebuild_http_client(),
+            Self::Supabase(client) => client.rebuild_http_client(),
+            Self::GoogleDrive(client) => client.rebuild_http_client(),
+        }
+    }
+
     pub fn is_authenticated(&self) -> bool {
         match self {
             Self::ApiServer(client) => client.is_authenticated(),
@@ -147,6 +288,33 @@ impl SyncClient {
         }
     }
 
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        match self {
+            Self::ApiServer(_) => ProviderCapabilities {
+                supports_individual_ops: true,
+                supports_incremental: true,
+                supports_delete: true,
+                requires_auth_flow: true,
+            },
+            // Blob mode replaces one file, like Google Drive
+            Self::Supabase(client) => {
+                let table_mode = client.storage_bucket().is_none();
+                ProviderCapabilities {
+                    supports_individual_ops: table_mode,
+                    supports_incremental: table_mode,
+                    supports_delete: table_mode,
+                    requires_auth_flow: false, // the API key is enough
+                }
+            }
+            Self::GoogleDrive(_) => ProviderCapabilities {
+                supports_individual_ops: false,
+                supports_incremental: false,
+                supports_delete: false,
+                requires_auth_flow: true,
+            },
+        }
+    }
+
     // Auto-create database schema (Supabase only)
     pub async fn ensure_schema(&self) -> Result<()> {
         match self {
@@ -255,7 +423,7 @@ impl SyncClient {
     }
 
     pub async fn push_sync(
-        &self,
+        &mut self,
         collections: Vec<Collection>,
         requests: Vec<HttpRequest>,
         environments: Vec<Environment>,
@@ -295,6 +463,9 @@ impl SyncClient {
 
                 Ok(())
             }
+            Self::Supabase(client) if client.storage_bucket().is_some() => {
+                client.upload_sync_data(data).await
+            }
             Self::Supabase(client) => {
                 // Supabase: push each item individually with create/update logic
                 for collection in &data.collections {
@@ -330,7 +501,7 @@ impl SyncClient {
         }
     }
 
-    pub async fn pull_sync(&self) -> Result<SyncPullResponse> {
+    pub async fn pull_sync(&mut self) -> Result<SyncPullResponse> {
         match self {
             Self::ApiServer(client) => {
                 let collections = client.get_collections().await?;
@@ -343,6 +514,9 @@ impl SyncClient {
                     environments,
                 })
             }
+            Self::Supabase(client) if client.storage_bucket().is_some() => {
+                client.download_sync_data().await
+            }
             Self::Supabase(client) => {
                 let collections = client.get_collections().await?;
                 let requests = client.get_requests().await?;
@@ -359,8 +533,11 @@ impl SyncClient {
     }
 
     // Individual item operations (mainly for API Server and Supabase)
-    pub async fn push_collection(&self, collection: &Collection) -> Result<String> {
+    pub async fn push_collection(&mut self, collection: &Collection) -> Result<String> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => {
                 if let Some(cloud_id) = &collection.cloud_id {
                     client.update_collection(cloud_id, collection).await?;
@@ -383,8 +560,11 @@ impl SyncClient {
         }
     }
 
-    pub async fn delete_collection(&self, cloud_id: &str) -> Result<()> {
+    pub async fn delete_collection(&mut self, cloud_id: &str) -> Result<()> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => client.delete_collection(cloud_id).await,
             Self::Supabase(client) => client.delete_collection(cloud_id).await,
             Self::GoogleDrive(_) => {
@@ -393,8 +573,11 @@ impl SyncClient {
         }
     }
 
-    pub async fn push_request(&self, request: &HttpRequest) -> Result<String> {
+    pub async fn push_request(&mut self, request: &HttpRequest) -> Result<String> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => {
                 if let Some(cloud_id) = &request.cloud_id {
                     client.update_request(cloud_id, request).await?;
@@ -417,8 +600,11 @@ impl SyncClient {
         }
     }
 
-    pub async fn delete_request(&self, cloud_id: &str) -> Result<()> {
+    pub async fn delete_request(&mut self, cloud_id: &str) -> Result<()> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => client.delete_request(cloud_id).await,
             Self::Supabase(client) => client.delete_request(cloud_id).await,
             Self::GoogleDrive(_) => {
@@ -427,8 +613,11 @@ impl SyncClient {
         }
     }
 
-    pub async fn push_environment(&self, environment: &Environment) -> Result<String> {
+    pub async fn push_environment(&mut self, environment: &Environment) -> Result<String> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => {
                 if let Some(cloud_id) = &environment.cloud_id {
                     client.update_environment(cloud_id, environment).await?;
@@ -451,8 +640,11 @@ impl SyncClient {
         }
     }
 
-    pub async fn delete_environment(&self, cloud_id: &str) -> Result<()> {
+    pub async fn delete_environment(&mut self, cloud_id: &str) -> Result<()> {
         match self {
+            Self::Supabase(client) if client.storage_bucket().is_some() => Err(anyhow!(
+                "Individual operations not supported in Supabase Storage mode, use push_sync"
+            )),
             Self::ApiServer(client) => client.delete_environment(cloud_id).await,
             Self::Supabase(client) => client.delete_environment(cloud_id).await,
             Self::GoogleDrive(_) => {
@@ -473,7 +665,7 @@ impl SyncOrchestrator {
     }
 
     pub async fn perform_full_sync(
-        &self,
+        &mut self,
         local_collections: Vec<Collection>,
         local_requests: Vec<HttpRequest>,
         local_environments: Vec<Environment>,
@@ -518,3 +710,47 @@ impl SyncOrchestrator {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock_server::{response, MockServer};
+
+    #[test]
+    fn test_provider_capabilities() {
+        let api_server = SyncClient::new_api_server("http://localhost:3000").unwrap();
+        assert!(api_server.capabilities().supports_individual_ops);
+
+        let google_drive =
+            SyncClient::new_google_drive("client-id", "secret", "http://localhost/callback")
+                .unwrap();
+        let capabilities = google_drive.capabilities();
+        assert!(!capabilities.supports_individual_ops);
+        assert!(!capabilities.supports_delete);
+        assert!(capabilities.requires_auth_flow);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_api_server_response_is_not_authenticated() {
+        let json = [("Content-Type", "application/json")];
+        let signed_in = r#"{"access_token":"token","refresh_token":null,"user":{"id":"u1","email":"dev@example.com","name":null}}"#;
+        let server = MockServer::start(vec![
+            response("200 OK", &json, signed_in),
+            response("401 Unauthorized", &json, r#"{"error":"token revoked"}"#),
+        ])
+        .await;
+
+        let mut client = ApiServerClient::new(&server.url).unwrap();
+        client
+            .sign_in("dev@example.com".to_string(), "secret".to_string())
+            .await
+            .unwrap();
+        let error = client.get_collections().await.unwrap_err();
+
+        let error = ProviderSyncError::from_anyhow(SyncProvider::ApiServer, &error);
+        assert_eq!(error.error, SyncError::NotAuthenticated);
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["provider"], "api_server");
+        assert_eq!(json["kind"], "not_authenticated");
+    }
+}
@@ -0,0 +1,87 @@
+use crate::models::{Collection, HttpMethod, HttpRequest};
+
+fn parse_method(token: &str) -> Option<HttpMethod> {
+    match token.to_uppercase().as_str() {
+        "GET" => Some(HttpMethod::GET),
+        "POST" => Some(HttpMethod::POST),
+        "PUT" => Some(HttpMethod::PUT),
+        "DELETE" => Some(HttpMethod::DELETE),
+        "PATCH" => Some(HttpMethod::PATCH),
+        "HEAD" => Some(HttpMethod::HEAD),
+        "OPTIONS" => Some(HttpMethod::OPTIONS),
+        _ => None,
+    }
+}
+
+// The path of a URL without its query, e.g. "/users/42"; falls back to the host or the
+// whole URL. Works on templated URLs like {{base_url}}/users too.
+fn request_name(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_query = without_scheme
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(without_scheme);
+    match without_query.find('/') {
+        Some(index) if without_query.len() > index + 1 => without_query[index..].to_string(),
+        _ if !without_query.is_empty() => without_query.trim_end_matches('/').to_string(),
+        _ => url.to_string(),
+    }
+}
+
+// One request per line: "<url>" or "<METHOD> <url>". Blank lines and # comments are
+// skipped; requests are GETs unless a method is given.
+pub fn convert_url_list(text: &str, collection_name: &str) -> (Collection, Vec<HttpRequest>) {
+    let collection = Collection::new(collection_name.to_string(), None);
+
+    let requests = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (method, url) = match line.split_once(char::is_whitespace) {
+                Some((token, rest)) => match parse_method(token) {
+                    Some(method) => (method, rest.trim()),
+                    None => (HttpMethod::GET, line),
+                },
+                None => (HttpMethod::GET, line),
+            };
+            let mut request = HttpRequest::new(request_name(url), method, url.to_string());
+            request.collection_id = Some(collection.id);
+            request
+        })
+        .collect();
+
+    (collection, requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_url_list() {
+        let text = "https://api.example.com/users?page=1\n\
+                    # health checks\n\
+                    \n\
+                    post {{base_url}}/users/42/avatar\n\
+                    https://status.example.com\n";
+
+        let (collection, requests) = convert_url_list(text, "Quick start");
+
+        assert_eq!(collection.name, "Quick start");
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.collection_id == Some(collection.id)));
+
+        assert!(matches!(requests[0].method, HttpMethod::GET));
+        assert_eq!(requests[0].name, "/users");
+        assert_eq!(requests[0].url, "https://api.example.com/users?page=1");
+
+        assert!(matches!(requests[1].method, HttpMethod::POST));
+        assert_eq!(requests[1].name, "/users/42/avatar");
+        assert_eq!(requests[1].url, "{{base_url}}/users/42/avatar");
+
+        assert_eq!(requests[2].name, "status.example.com");
+    }
+}
@@ -88,24 +88,19 @@ const RequestPanel: React.FC<RequestPanelProps> = ({ tab }) => {
 
   // Header management
   const addHeader = () => {
-    const newHeaders = { ...tab.request.headers, "": "" };
+    const newHeaders: [string, string][] = [...tab.request.headers, ["", ""]];
     updateTabRequest(tab.id, { headers: newHeaders });
   };
 
-  const updateHeader = (oldKey: string, newKey: string, value: string) => {
-    const newHeaders = { ...tab.request.headers };
-    if (oldKey !== newKey) {
-      delete newHeaders[oldKey];
-    }
-    if (newKey.trim()) {
-      newHeaders[newKey] = value;
-    }
+  const updateHeader = (index: number, key: string, value: string) => {
+    const newHeaders = tab.request.headers.map(
+      (header, i): [string, string] => (i === index ? [key, value] : header),
+    );
     updateTabRequest(tab.id, { headers: newHeaders });
   };
 
-  const removeHeader = (key: string) => {
-    const newHeaders = { ...tab.request.headers };
-    delete newHeaders[key];
+  const removeHeader = (index: number) => {
+    const newHeaders = tab.request.headers.filter((_, i) => i !== index);
     updateTabRequest(tab.id, { headers: newHeaders });
   };
 
@@ -269,7 +264,7 @@ const RequestPanel: React.FC<RequestPanelProps> = ({ tab }) => {
     }
   };
 
-  const headerEntries = Object.entries(tab.request.headers);
+  const headerEntries = tab.request.headers;
 
   return (
     <div className="flex flex-col h-full bg-white dark:bg-gray-800 overflow-hidden">
@@ -412,19 +407,19 @@ const RequestPanel: React.FC<RequestPanelProps> = ({ tab }) => {
                     <input
                       type="text"
                       value={key}
-                      onChange={(e) => updateHeader(key, e.target.value, value)}
+                      onChange={(e) => updateHeader(index, e.target.value, value)}
                       className="flex-1 form-input text-sm"
                       placeholder="Header name"
                     />
                     <input
                       type="text"
                       value={value}
-                      onChange={(e) => updateHeader(key, key, e.target.value)}
+                      onChange={(e) => updateHeader(index, key, e.target.value)}
                       className="flex-1 form-input text-sm"
                       placeholder="Header value"
                     />
                     <button
-                      onClick={() => removeHeader(key)}
+                      onClick={() => removeHeader(index)}
                       className="text-red-600 hover:text-red-800 p-1"
                     >
                       <Trash2 className="h-4 w-4" />
@@ -62,8 +62,9 @@ const ResponsePanel: React.FC<ResponsePanelProps> = ({ tab }) => {
       try {
         const contentType =
           response.content_type ||
-          response.headers["content-type"] ||
-          response.headers["Content-Type"] ||
+          response.headers.find(
+            ([key]) => key.toLowerCase() === "content-type",
+          )?.[1] ||
           undefined;
 
         const contentToHighlight =
@@ -193,7 +194,7 @@ const ResponsePanel: React.FC<ResponsePanelProps> = ({ tab }) => {
     );
   }
 
-  const headerEntries = Object.entries(response.headers);
+  const headerEntries = response.headers;
 
   return (
     <div className="flex flex-col h-full bg-white dark:bg-gray-800 overflow-hidden">
@@ -227,7 +227,7 @@ const Sidebar: React.FC<SidebarProps> = () => {
         name: newRequestName.trim(),
         method: newRequestMethod as any,
         url: newRequestUrl,
-        headers: {},
+        headers: [] as [string, string][],
         body: undefined,
         collection_id: targetCollectionId || undefined,
       };
@@ -54,6 +54,19 @@ const SYNC_PROVIDERS: SyncProvider[] = [
   },
 ];
 
+// Sync commands reject with { provider, kind, detail, message }
+interface SyncError {
+  provider: string;
+  kind: string;
+  message: string;
+}
+
+function syncErrorMessage(err: unknown): string {
+  return typeof err === "object" && err !== null && "message" in err
+    ? (err as SyncError).message
+    : String(err);
+}
+
 interface SyncSettingsProps {
   onClose: () => void;
 }
@@ -336,7 +349,7 @@ export default function SyncSettings({ onClose }: SyncSettingsProps) {
       success("Sync completed successfully!");
       await loadSyncStatus();
     } catch (err) {
-      error(`Sync failed: ${err}`);
+      error(`Sync failed: ${syncErrorMessage(err)}`);
     } finally {
       setSyncing(false);
     }
@@ -349,7 +362,7 @@ export default function SyncSettings({ onClose }: SyncSettingsProps) {
       success("Pushed local changes to cloud");
       await loadSyncStatus();
     } catch (err) {
-      error(`Push failed: ${err}`);
+      error(`Push failed: ${syncErrorMessage(err)}`);
     } finally {
       setSyncing(false);
     }
@@ -362,7 +375,7 @@ export default function SyncSettings({ onClose }: SyncSettingsProps) {
       success("Pulled changes from cloud");
       await loadSyncStatus();
     } catch (err) {
-      error(`Pull failed: ${err}`);
+      error(`Pull failed: ${syncErrorMessage(err)}`);
     } finally {
       setSyncing(false);
     }
@@ -46,7 +46,8 @@ export interface HttpRequest {
   name: string;
   method: keyof HttpMethod;
   url: string;
-  headers: Record<string, string>;
+  // In send order; a name may repeat
+  headers: [string, string][];
   body?: RequestBody;
   path_params?: Record<string, string>;
   collection_id?: string;
@@ -57,7 +58,8 @@ export interface HttpRequest {
 export interface HttpResponse {
   status: number;
   status_text: string;
-  headers: Record<string, string>;
+  // In arrival order; a name repeats for each Set-Cookie, Vary, etc.
+  headers: [string, string][];
   body: string;
   formatted_body?: string;
   highlighted_body?: string;
@@ -188,7 +190,7 @@ const createNewRequest = (): HttpRequest => ({
   name: "New Request",
   method: "GET",
   url: "https://",
-  headers: {},
+  headers: [],
   body: undefined,
 });
 
@@ -244,11 +246,15 @@ const getCollectionAuthHeaders = (
 };
 
 const mergeHeaders = (
-  requestHeaders: Record<string, string>,
+  requestHeaders: [string, string][],
   authHeaders: Record<string, string>,
-): Record<string, string> => {
+): [string, string][] => {
   // Request headers take precedence over auth headers
-  return { ...authHeaders, ...requestHeaders };
+  const authOnly = Object.entries(authHeaders).filter(
+    ([name]) =>
+      !requestHeaders.some(([key]) => key.toLowerCase() === name.toLowerCase()),
+  );
+  return [...authOnly, ...requestHeaders.filter(([key]) => key.trim())];
 };
 
 export const useAppStore = create<AppState>((set, get) => ({